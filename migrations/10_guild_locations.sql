@@ -0,0 +1,10 @@
+CREATE TABLE guild_locations (
+    guild        TEXT NOT NULL PRIMARY KEY ON CONFLICT REPLACE,
+    place_name   TEXT,
+    country      TEXT,
+    feature_code TEXT,
+    longitude    REAL NOT NULL,
+    latitude     REAL NOT NULL,
+    elevation    REAL,
+    CHECK ( (place_name IS NULL) = (feature_code IS NULL) ) 
+);
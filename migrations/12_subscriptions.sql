@@ -0,0 +1,17 @@
+CREATE TABLE subscriptions (
+    user               INTEGER NOT NULL PRIMARY KEY ON CONFLICT REPLACE,
+    forecast           TEXT NOT NULL,
+    theme              TEXT NOT NULL,
+    layout             TEXT NOT NULL,
+    units              TEXT NOT NULL,
+    place              TEXT NOT NULL,
+    longitude          REAL NOT NULL,
+    latitude           REAL NOT NULL,
+    timezone           TEXT,
+    utc_offset_seconds INTEGER NOT NULL,
+    hour               INTEGER NOT NULL,
+    weekday            INTEGER,
+    next_run           INTEGER NOT NULL
+);
+
+CREATE INDEX subscriptions_next_run ON subscriptions (next_run);
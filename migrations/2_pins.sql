@@ -0,0 +1,10 @@
+CREATE TABLE pins (
+    message      INTEGER NOT NULL PRIMARY KEY,
+    channel      INTEGER NOT NULL,
+    forecast     TEXT NOT NULL,
+    place        TEXT NOT NULL,
+    longitude    REAL NOT NULL,
+    latitude     REAL NOT NULL
+);
+
+CREATE INDEX pins_channel ON pins (channel);
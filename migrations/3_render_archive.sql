@@ -0,0 +1,8 @@
+CREATE TABLE render_archive (
+    id           INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    forecast     TEXT NOT NULL,
+    longitude    REAL NOT NULL,
+    latitude     REAL NOT NULL,
+    created      INTEGER NOT NULL,
+    input        TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE pins ADD COLUMN theme TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE render_archive ADD COLUMN theme TEXT NOT NULL DEFAULT 'default';
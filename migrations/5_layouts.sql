@@ -0,0 +1,2 @@
+ALTER TABLE pins ADD COLUMN layout TEXT NOT NULL DEFAULT 'headers';
+ALTER TABLE render_archive ADD COLUMN layout TEXT NOT NULL DEFAULT 'headers';
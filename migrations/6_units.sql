@@ -0,0 +1,7 @@
+CREATE TABLE user_settings (
+    user         INTEGER NOT NULL PRIMARY KEY ON CONFLICT REPLACE,
+    units        TEXT NOT NULL DEFAULT 'metric'
+);
+
+ALTER TABLE pins ADD COLUMN units TEXT NOT NULL DEFAULT 'metric';
+ALTER TABLE render_archive ADD COLUMN units TEXT NOT NULL DEFAULT 'metric';
@@ -0,0 +1,25 @@
+CREATE TABLE user_locations_labelled (
+    domain       TEXT NOT NULL,
+    user         TEXT NOT NULL,
+    label        TEXT NOT NULL COLLATE NOCASE,
+    is_primary   BOOLEAN NOT NULL DEFAULT FALSE,
+    place_name   TEXT,
+    country      TEXT,
+    feature_code TEXT,
+    longitude    REAL NOT NULL,
+    latitude     REAL NOT NULL,
+    PRIMARY KEY (
+        domain COLLATE NOCASE,
+        user COLLATE NOCASE,
+        label COLLATE NOCASE
+    )
+    ON CONFLICT REPLACE,
+    CHECK ( (place_name IS NULL) = (feature_code IS NULL) ) 
+);
+
+INSERT INTO user_locations_labelled (domain, user, label, is_primary, place_name, country, feature_code, longitude, latitude)
+SELECT domain, user, 'default', TRUE, place_name, country, feature_code, longitude, latitude
+FROM user_locations;
+
+DROP TABLE user_locations;
+ALTER TABLE user_locations_labelled RENAME TO user_locations;
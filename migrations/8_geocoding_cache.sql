@@ -0,0 +1,6 @@
+CREATE TABLE geocoding_cache (
+    query        TEXT NOT NULL PRIMARY KEY ON CONFLICT REPLACE,
+    count        INTEGER NOT NULL,
+    fetched      INTEGER NOT NULL,
+    results      TEXT NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE user_locations ADD COLUMN elevation REAL;
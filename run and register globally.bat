@@ -0,0 +1,2 @@
+"target/release/weatherbot.exe" register global
+PAUSE
\ No newline at end of file
@@ -0,0 +1,273 @@
+use chrono::{DateTime, FixedOffset};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+	CreateEmbed, CreateEmbedFooter, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, ResponseExt},
+	reply_shortcuts::ReplyShortcuts,
+};
+
+/// The most alerts listed in one reply. Discord allows 25 embed fields, but a long list of warnings for the same storm helps no one.
+const MAX_ALERTS: usize = 10;
+/// The longest embed field name Discord accepts.
+const MAX_FIELD_NAME_LENGTH: usize = 256;
+
+/// A warning from a national weather service, in the form every provider's feed is turned into.
+#[derive(Debug, PartialEq)]
+struct Alert {
+	headline: String,
+	/// Like "Severe" or "Moderate", as in the Common Alerting Protocol.
+	severity: String,
+	/// When the hazard ends, or else when the warning expires, if either is given.
+	until: Option<DateTime<FixedOffset>>,
+}
+
+impl Alert {
+	/// The severity and end of the alert, for under its headline.
+	fn describe(&self) -> String {
+		match self.until {
+			Some(until) => format!("{}, until <t:{}:f>", self.severity, until.timestamp()),
+			None => self.severity.clone(),
+		}
+	}
+}
+
+/// The services alerts can be looked up from. To add one, give it a variant, say which coordinates it covers in [`AlertProvider::for_coordinates`] and turn its feed into [`Alert`]s in [`AlertProvider::get`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AlertProvider {
+	/// The US National Weather Service.
+	Nws,
+}
+
+impl AlertProvider {
+	/// The provider covering these coordinates, if there is one.
+	fn for_coordinates(coordinates: Coordinates) -> Option<Self> {
+		NWS_AREAS
+			.iter()
+			.any(|area| area.contains(coordinates))
+			.then_some(Self::Nws)
+	}
+	fn name(self) -> &'static str {
+		match self {
+			Self::Nws => "US National Weather Service",
+		}
+	}
+	/// The alerts active at these coordinates right now.
+	async fn get(self, coordinates: Coordinates, client: &Client) -> Result<Vec<Alert>, Error> {
+		match self {
+			Self::Nws => {
+				let alerts: NwsAlerts = client
+					.get("https://api.weather.gov/alerts/active")
+					.query(&[(
+						"point",
+						format!("{:.4},{:.4}", coordinates.latitude, coordinates.longitude),
+					)])
+					.send()
+					.await?
+					.error_for_status()?
+					.json_limited()
+					.await?;
+				Ok(alerts.into_alerts())
+			}
+		}
+	}
+}
+
+/// A rectangle of latitudes and longitudes, in degrees.
+struct Area {
+	south: f32,
+	north: f32,
+	west: f32,
+	east: f32,
+}
+
+impl Area {
+	fn contains(&self, coordinates: Coordinates) -> bool {
+		(self.south..=self.north).contains(&coordinates.latitude)
+			&& (self.west..=self.east).contains(&coordinates.longitude)
+	}
+}
+
+/// Roughly where the National Weather Service issues alerts: the contiguous states, Alaska, Hawaii, and Puerto Rico with the US Virgin Islands. The rectangles spill over into neighbouring countries, where the service just has no alerts.
+const NWS_AREAS: [Area; 4] = [
+	Area {
+		south: 24.4,
+		north: 49.4,
+		west: -125.0,
+		east: -66.9,
+	},
+	Area {
+		south: 51.2,
+		north: 71.5,
+		west: -180.0,
+		east: -129.9,
+	},
+	Area {
+		south: 18.9,
+		north: 22.3,
+		west: -160.3,
+		east: -154.8,
+	},
+	Area {
+		south: 17.6,
+		north: 18.6,
+		west: -67.3,
+		east: -64.5,
+	},
+];
+
+/// https://www.weather.gov/documentation/services-web-api
+#[derive(Debug, Deserialize)]
+struct NwsAlerts {
+	features: Vec<NwsFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NwsFeature {
+	properties: NwsAlert,
+}
+
+#[derive(Debug, Deserialize)]
+struct NwsAlert {
+	event: String,
+	headline: Option<String>,
+	severity: String,
+	expires: Option<DateTime<FixedOffset>>,
+	ends: Option<DateTime<FixedOffset>>,
+}
+
+impl NwsAlerts {
+	fn into_alerts(self) -> Vec<Alert> {
+		self.features
+			.into_iter()
+			.map(|feature| {
+				let alert = feature.properties;
+				Alert {
+					headline: alert.headline.unwrap_or(alert.event),
+					severity: alert.severity,
+					until: alert.ends.or(alert.expires),
+				}
+			})
+			.collect()
+	}
+}
+
+pub async fn handle_alerts(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let Some(provider) = AlertProvider::for_coordinates(location.coordinates()) else {
+		return Err(Error::friendly(
+			"No alerts are available for this location. So far they only come from the US National Weather Service.",
+		));
+	};
+	interaction.defer_reply(&context.http, false).await?;
+
+	let alerts = provider.get(location.coordinates(), &client).await?;
+	let place = location.short_description();
+	let message = if alerts.is_empty() {
+		CreateInteractionResponseFollowup::new().content(format!("No active alerts for {place}."))
+	} else {
+		CreateInteractionResponseFollowup::new().embed(alerts_embed(&alerts, &place, provider))
+	};
+	interaction.create_followup(context, message).await?;
+	Ok(())
+}
+
+fn alerts_embed(alerts: &[Alert], place: &str, provider: AlertProvider) -> CreateEmbed {
+	let mut embed = CreateEmbed::new().title(format!("Weather alerts for {place}"));
+	for alert in alerts.iter().take(MAX_ALERTS) {
+		let headline: String = alert.headline.chars().take(MAX_FIELD_NAME_LENGTH).collect();
+		embed = embed.field(headline, alert.describe(), false);
+	}
+	if alerts.len() > MAX_ALERTS {
+		embed = embed.description(format!("And {} more.", alerts.len() - MAX_ALERTS));
+	}
+	embed.footer(CreateEmbedFooter::new(format!(
+		"From the {}",
+		provider.name()
+	)))
+}
+
+pub fn create_alerts() -> CreateCommand {
+	i18n::command("alerts", "Active severe weather warnings")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather alerts of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn provider_by_coordinates() {
+		let provider = |latitude, longitude| {
+			AlertProvider::for_coordinates(Coordinates::new(latitude, longitude))
+		};
+		// Kansas City, Anchorage, Honolulu and San Juan.
+		assert_eq!(provider(39.1, -94.58), Some(AlertProvider::Nws));
+		assert_eq!(provider(61.22, -149.9), Some(AlertProvider::Nws));
+		assert_eq!(provider(21.31, -157.86), Some(AlertProvider::Nws));
+		assert_eq!(provider(18.47, -66.11), Some(AlertProvider::Nws));
+		// Amsterdam and Mexico City.
+		assert_eq!(provider(52.37, 4.9), None);
+		assert_eq!(provider(19.43, -99.13), None);
+	}
+	#[test]
+	fn nws_alerts_parsed() {
+		let alerts: NwsAlerts = serde_json::from_str(
+			r#"{
+				"type": "FeatureCollection",
+				"features": [
+					{
+						"properties": {
+							"event": "Tornado Warning",
+							"headline": "Tornado Warning issued June 5 at 4:12PM CDT",
+							"severity": "Extreme",
+							"expires": "2024-06-05T16:45:00-05:00",
+							"ends": null
+						}
+					},
+					{
+						"properties": {
+							"event": "Heat Advisory",
+							"headline": null,
+							"severity": "Moderate",
+							"expires": "2024-06-05T20:00:00-05:00",
+							"ends": "2024-06-06T20:00:00-05:00"
+						}
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+		let alerts = alerts.into_alerts();
+		assert_eq!(
+			alerts[0].headline,
+			"Tornado Warning issued June 5 at 4:12PM CDT"
+		);
+		assert_eq!(alerts[0].describe(), "Extreme, until <t:1717623900:f>");
+		// Without a headline, the event stands in, and the end of the hazard counts rather than of the message.
+		assert_eq!(alerts[1].headline, "Heat Advisory");
+		assert_eq!(alerts[1].describe(), "Moderate, until <t:1717722000:f>");
+	}
+}
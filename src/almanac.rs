@@ -0,0 +1,208 @@
+use std::{
+	sync::{Arc, LazyLock},
+	time::Duration,
+};
+
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+	CreateEmbed, CreateEmbedFooter, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	cache::{coordinates_key, TtlCache},
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	reply_shortcuts::ReplyShortcuts,
+};
+
+/// How many complete past years of archive data to base the almanac on.
+const ARCHIVE_YEARS: i32 = 30;
+
+#[derive(Debug, Deserialize)]
+struct ArchiveDaily {
+	time: Vec<i64>,
+	temperature_2m_max: Vec<Option<f32>>,
+	temperature_2m_min: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	daily: ArchiveDaily,
+}
+
+/// Rounded coordinates and the current year.
+type ArchiveKey = ((i32, i32), i32);
+
+/// The archive only changes when a new year completes, so a day-long cache is plenty fresh.
+static ARCHIVE_CACHE: LazyLock<TtlCache<ArchiveKey, Arc<ArchiveResult>>> =
+	LazyLock::new(|| TtlCache::new(Duration::from_secs(24 * 60 * 60)));
+
+impl ArchiveResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Arc<Self>, Error> {
+		let current_year = Utc::now().year();
+		let key = (coordinates_key(coordinates), current_year);
+		if let Some(result) = ARCHIVE_CACHE.get(&key) {
+			return Ok(result);
+		}
+		let start_date = format!("{}-01-01", current_year - ARCHIVE_YEARS);
+		let end_date = format!("{}-12-31", current_year - 1);
+		let result = client
+			.get("https://archive-api.open-meteo.com/v1/archive")
+			.query(&[
+				("daily", "temperature_2m_max"),
+				("daily", "temperature_2m_min"),
+				("timeformat", "unixtime"),
+				("timezone", "auto"),
+			])
+			.query(&[
+				("start_date", start_date.as_str()),
+				("end_date", end_date.as_str()),
+			])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo::<ArchiveResult>()
+			.await?;
+		let result = Arc::new(result);
+		ARCHIVE_CACHE.insert(key, result.clone());
+		Ok(result)
+	}
+}
+
+/// A temperature and the year it occurred in.
+struct Record {
+	temperature: f32,
+	year: i32,
+}
+
+/// What a calendar date has historically been like at a location.
+struct Almanac {
+	average_high: f32,
+	average_low: f32,
+	record_high: Record,
+	record_low: Record,
+	/// The number of years that had data for the date.
+	sample_size: usize,
+}
+
+impl Almanac {
+	fn for_date(archive: &ArchiveResult, month: u32, day: u32) -> Option<Self> {
+		let offset = FixedOffset::east_opt(archive.utc_offset_seconds)?;
+		let samples: Vec<_> = archive
+			.daily
+			.time
+			.iter()
+			.zip(&archive.daily.temperature_2m_max)
+			.zip(&archive.daily.temperature_2m_min)
+			.filter_map(|((&time, &max), &min)| {
+				let date = DateTime::from_timestamp(time, 0)?.with_timezone(&offset);
+				(date.month() == month && date.day() == day).then_some((date.year(), max?, min?))
+			})
+			.collect();
+		let sample_size = samples.len();
+		let (&(high_year, high, _), &(low_year, _, low)) = (
+			samples
+				.iter()
+				.max_by(|(_, a, _), (_, b, _)| a.total_cmp(b))?,
+			samples
+				.iter()
+				.min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))?,
+		);
+		Some(Self {
+			average_high: samples.iter().map(|(_, max, _)| max).sum::<f32>() / sample_size as f32,
+			average_low: samples.iter().map(|(_, _, min)| min).sum::<f32>() / sample_size as f32,
+			record_high: Record {
+				temperature: high,
+				year: high_year,
+			},
+			record_low: Record {
+				temperature: low,
+				year: low_year,
+			},
+			sample_size,
+		})
+	}
+}
+
+pub async fn handle_almanac(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	// Thirty years of archive data can take a while to arrive.
+	interaction.defer_reply(&context.http, false).await?;
+
+	let archive = ArchiveResult::get(location.coordinates(), &client).await?;
+	let offset = FixedOffset::east_opt(archive.utc_offset_seconds)
+		.ok_or_else(|| Error::custom_unfriendly("Invalid UTC offset"))?;
+	let today = Utc::now().with_timezone(&offset);
+	let almanac = Almanac::for_date(&archive, today.month(), today.day())
+		.ok_or_else(|| Error::friendly("No archive data for this location"))?;
+
+	let embed = CreateEmbed::new()
+		.title(format!("Almanac for {}", today.format("%B %-d")))
+		.description(location.to_string())
+		.field(
+			"Average high",
+			format!("{:.1}°C", almanac.average_high),
+			true,
+		)
+		.field("Average low", format!("{:.1}°C", almanac.average_low), true)
+		.field("\u{200B}", "\u{200B}", true)
+		.field(
+			"Record high",
+			format!(
+				"{:.1}°C ({})",
+				almanac.record_high.temperature, almanac.record_high.year
+			),
+			true,
+		)
+		.field(
+			"Record low",
+			format!(
+				"{:.1}°C ({})",
+				almanac.record_low.temperature, almanac.record_low.year
+			),
+			true,
+		)
+		.footer(CreateEmbedFooter::new(format!(
+			"Based on {} of the last {} years of archive data",
+			almanac.sample_size, ARCHIVE_YEARS
+		)));
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new().embed(embed),
+		)
+		.await?;
+	Ok(())
+}
+
+pub fn create_almanac() -> CreateCommand {
+	i18n::command("almanac", "What today is historically like")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the almanac of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
@@ -0,0 +1,49 @@
+use std::{
+	collections::HashMap,
+	hash::Hash,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crate::location::Coordinates;
+
+/// A simple in-memory cache whose entries expire a fixed amount of time after insertion.
+pub struct TtlCache<K, V> {
+	time_to_live: Duration,
+	entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+	K: Eq + Hash,
+	V: Clone,
+{
+	pub fn new(time_to_live: Duration) -> Self {
+		Self {
+			time_to_live,
+			entries: Mutex::new(HashMap::new()),
+		}
+	}
+	/// Get a copy of the cached value, if there is one and it has not expired yet.
+	pub fn get(&self, key: &K) -> Option<V> {
+		let entries = self.entries.lock().unwrap();
+		entries
+			.get(key)
+			.filter(|(inserted, _)| inserted.elapsed() < self.time_to_live)
+			.map(|(_, value)| value.clone())
+	}
+	/// Insert a value, evicting any expired entries along the way so the cache doesn't grow forever.
+	pub fn insert(&self, key: K, value: V) {
+		let mut entries = self.entries.lock().unwrap();
+		entries.retain(|_, (inserted, _)| inserted.elapsed() < self.time_to_live);
+		entries.insert(key, (Instant::now(), value));
+	}
+}
+
+/// Coordinates rounded to two decimals (roughly a kilometre), for use in cache keys.
+pub fn coordinates_key(coordinates: Coordinates) -> (i32, i32) {
+	(
+		(coordinates.latitude * 100.0).round() as i32,
+		(coordinates.longitude * 100.0).round() as i32,
+	)
+}
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommandOption};
+
+use crate::util::{CommandInteractionExt, ForecastTimezone};
+
+/// Whether the command's "data" option asks for the forecast data as a CSV attachment too.
+pub fn data_option(interaction: &CommandInteraction) -> bool {
+	interaction.bool_option("data").unwrap_or(false)
+}
+
+pub fn create_data_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::Boolean,
+		"data",
+		"Also attach the forecast data as a CSV file, for spreadsheets.",
+	)
+	.required(false)
+}
+
+/// A column of forecast data, with its values already formatted.
+pub struct Column {
+	header: String,
+	values: Vec<String>,
+}
+
+impl Column {
+	pub fn new<T>(header: impl Into<String>, values: &[T]) -> Self
+	where
+		T: Display,
+	{
+		Self {
+			header: header.into(),
+			values: values.iter().map(ToString::to_string).collect(),
+		}
+	}
+}
+
+/// Forecast data as CSV: one row per time step, with the time in the location's local time in `time_format`, followed by the columns.
+///
+/// Rows stop at the shortest column, except that a column with no values at all, for a variable Open-Meteo left out, is left blank. None of the headers or values need quoting.
+pub fn to_csv(
+	times: &[i64],
+	timezone: ForecastTimezone,
+	time_format: &str,
+	columns: &[Column],
+) -> String {
+	let mut csv = std::iter::once("time")
+		.chain(columns.iter().map(|column| column.header.as_str()))
+		.collect::<Vec<_>>()
+		.join(",");
+	csv.push('\n');
+	for (index, &time) in times.iter().enumerate() {
+		let Some(values) = columns
+			.iter()
+			.map(|column| {
+				if column.values.is_empty() {
+					Some("")
+				} else {
+					column.values.get(index).map(String::as_str)
+				}
+			})
+			.collect::<Option<Vec<_>>>()
+		else {
+			break;
+		};
+		let time = timezone.local(time).format(time_format).to_string();
+		csv += &std::iter::once(time.as_str())
+			.chain(values)
+			.collect::<Vec<_>>()
+			.join(",");
+		csv.push('\n');
+	}
+	csv
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn csv_in_local_time() {
+		let csv = to_csv(
+			&[1_700_000_000, 1_700_003_600],
+			ForecastTimezone::new(None, 3600),
+			"%Y-%m-%d %H:%M",
+			&[
+				Column::new("temperature_2m (°C)", &[11.5, 10.25]),
+				Column::new("precipitation_probability (%)", &[5, 20]),
+			],
+		);
+		assert_eq!(
+			csv,
+			"time,temperature_2m (°C),precipitation_probability (%)\n\
+			2023-11-14 23:13,11.5,5\n\
+			2023-11-15 00:13,10.25,20\n"
+		);
+	}
+	#[test]
+	fn csv_stops_at_shortest_column() {
+		let csv = to_csv(
+			&[0, 86_400, 172_800],
+			ForecastTimezone::new(None, 0),
+			"%Y-%m-%d",
+			&[Column::new("uv_index_max", &[1.0, 2.0])],
+		);
+		assert_eq!(csv, "time,uv_index_max\n1970-01-01,1\n1970-01-02,2\n");
+	}
+	#[test]
+	fn csv_blank_for_missing_column() {
+		let csv = to_csv(
+			&[0, 86_400],
+			ForecastTimezone::new(None, 0),
+			"%Y-%m-%d",
+			&[
+				Column::new("uv_index_max", &[1.0, 2.0]),
+				Column::new("uv_index_clear_sky_max", &[] as &[f32]),
+			],
+		);
+		assert_eq!(
+			csv,
+			"time,uv_index_max,uv_index_clear_sky_max\n1970-01-01,1,\n1970-01-02,2,\n"
+		);
+	}
+}
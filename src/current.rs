@@ -1,19 +1,39 @@
 use std::sync::LazyLock;
 
-use ab_glyph::FontRef;
+use ab_glyph::{FontRef, PxScale};
 use chrono::Duration;
+use graph::{
+	drawing::{Padding, Spacing},
+	generic_graph::{Chart, Rgb},
+	text_box::TextSegment,
+	util::{composite, make_png},
+};
 use reqwest::Client;
 use serde::Deserialize;
 use serenity::all::{
-	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-	CreateInteractionResponse, CreateInteractionResponseMessage,
+	Colour, CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+	CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage, Message,
+	UserId,
 };
 use sqlx::{Pool, Sqlite};
 
 use crate::{
 	error::Error,
-	location::{Coordinates, Location},
-	util::weather_code_to_str,
+	geocoding::GeocodingResult,
+	i18n,
+	layout::text_chart,
+	location::{create_label_option, label_option, Coordinates, Location},
+	open_meteo::{self, complete_series, RequestBuilderExt},
+	palette::Theme,
+	random_place,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	sunrise_sunset::SunResult,
+	units::{celsius_to_fahrenheit, cm_to_inches, kmh_to_mph, mm_to_inches, UnitSystem},
+	util::{
+		capitalize, compass_point, heat_index, temperature_colour, weather_code_to_emoji,
+		weather_code_to_str, wind_chill, CommandInteractionExt,
+	},
 };
 
 #[derive(Debug, Deserialize)]
@@ -48,8 +68,12 @@ struct CurrentResult {
 }
 
 impl CurrentResult {
-	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
-		Ok(client
+	async fn get(
+		coordinates: Coordinates,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		client
 			.get("https://api.open-meteo.com/v1/forecast")
 			.query(&[("current", "temperature_2m")])
 			.query(&[("current", "relative_humidity_2m")])
@@ -65,30 +89,339 @@ impl CurrentResult {
 			.query(&[("current", "wind_gusts_10m")])
 			.query(&[("current", "uv_index")])
 			.query(&[("current", "uv_index_clear_sky")])
+			.query(&[("wind_speed_unit", units.wind_speed_unit())])
+			.query(&units.query())
 			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
 			.query(&[
 				("latitude", coordinates.latitude),
 				("longitude", coordinates.longitude),
 			])
-			.send()
-			.await?
-			.json::<Self>()
-			.await?)
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// Just the daily readings the today card needs, for the first day only.
+#[derive(Debug, Deserialize)]
+struct TodayWeather {
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m_min: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation_probability_max: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TodayResult {
+	daily: TodayWeather,
+}
+
+impl TodayResult {
+	async fn get(
+		coordinates: Coordinates,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[
+				("daily", "temperature_2m_min"),
+				("daily", "temperature_2m_max"),
+				("daily", "precipitation_probability_max"),
+				("timeformat", "unixtime"),
+				("timezone", "auto"),
+			])
+			.query(&units.query())
+			.query(&[("forecast_days", 1)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// The wind chill or the heat index of the current weather in the user's units, whichever applies, with its name.
+fn chill_or_heat_index(current: &CurrentWeather, units: UnitSystem) -> Option<(&'static str, f32)> {
+	let temperature = units.temperature_to_celsius(current.temperature_2m);
+	let (name, index) = wind_chill(temperature, units.wind_speed_to_kmh(current.wind_speed_10m))
+		.map(|chill| ("wind chill", chill))
+		.or_else(|| {
+			heat_index(temperature, current.relative_humidity_2m).map(|index| ("heat index", index))
+		})?;
+	Some((name, units.temperature_from_celsius(index)))
+}
+
+const CARD_HEADER_SIZE: PxScale = PxScale { x: 24.0, y: 24.0 };
+const CARD_TEXT_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+/// Width of the text on the current weather card, in pixels.
+const CARD_WIDTH: u32 = 360;
+
+/// One line of the current weather card.
+fn card_line(segments: &[TextSegment], font: &FontRef<'static>, scale: PxScale) -> Chart {
+	let spacing = Spacing {
+		horizontal: CARD_WIDTH,
+		vertical: 1,
+	};
+	let padding = Padding {
+		above: 6,
+		below: 0,
+		left: 12,
+		right: 12,
+	};
+	text_chart(segments, font, scale, 2, spacing, padding)
+}
+
+/// Draw the current weather as a card, with the place as its header and then one line per kind of reading.
+fn draw_current_card(
+	place: &str,
+	current: &CurrentWeather,
+	units: UnitSystem,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let palette = Theme::Default.palette();
+	let temperature = units.temperature_symbol();
+	let precipitation = units.precipitation_symbol();
+	let wind_speed = units.wind_speed_symbol();
+
+	let description =
+		capitalize(weather_code_to_str(current.weather_code).unwrap_or("unknown weather"));
+	let temperatures = [
+		format!("{:.1}{temperature}", current.temperature_2m),
+		format!("{:.1}{temperature}", current.apparent_temperature),
+	];
+	let precipitation_text = if current.snowfall > 0.0 {
+		format!(
+			"{:.1} {precipitation}, of which {:.1} {} snow",
+			current.precipitation,
+			current.snowfall,
+			units.snowfall_symbol()
+		)
+	} else {
+		format!("{:.1} {precipitation}", current.precipitation)
+	};
+	let wind = format!(
+		"{:.0} {wind_speed} from {:.0}°",
+		current.wind_speed_10m, current.wind_direction_10m
+	);
+	let gusts = format!("{:.0} {wind_speed}", current.wind_gusts_10m);
+	let humidity = format!(
+		"Humidity {:.0}%, cloud cover {:.0}%",
+		current.relative_humidity_2m, current.cloud_cover
+	);
+	let uv = format!(
+		"{:.1} (clear sky {:.1})",
+		current.uv_index, current.uv_index_clear_sky
+	);
+	let index = chill_or_heat_index(current, units)
+		.map(|(name, index)| (capitalize(name) + " ", format!("{index:.1}{temperature}")));
+
+	let mut lines = vec![
+		card_line(&[TextSegment::white(place)], header_font, CARD_HEADER_SIZE),
+		card_line(&[TextSegment::white(&description)], font, CARD_TEXT_SIZE),
+		card_line(
+			&[
+				TextSegment::white("Temperature "),
+				TextSegment::new(&temperatures[0], Rgb(palette.temperature)),
+				TextSegment::white(", feels like "),
+				TextSegment::new(&temperatures[1], Rgb(palette.apparent_temperature)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+	];
+	if let Some((name, index)) = &index {
+		lines.push(card_line(
+			&[
+				TextSegment::white(name),
+				TextSegment::new(index, Rgb(palette.apparent_temperature)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		));
 	}
+	lines.extend([
+		card_line(&[TextSegment::white(&humidity)], font, CARD_TEXT_SIZE),
+		card_line(
+			&[
+				TextSegment::white("Wind "),
+				TextSegment::new(&wind, Rgb(palette.wind_gradient[0])),
+				TextSegment::white(", gusts "),
+				TextSegment::new(&gusts, Rgb(palette.gust_gradient[0])),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+		card_line(
+			&[
+				TextSegment::white("Precipitation "),
+				TextSegment::new(&precipitation_text, Rgb(palette.precipitation)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+		card_line(
+			&[
+				TextSegment::white("UV index "),
+				TextSegment::new(&uv, Rgb(palette.uv_colour(current.uv_index))),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+	]);
+	let lines: Vec<_> = lines.into_iter().map(Chart::into_canvas).collect();
+	make_png(composite(&lines))
+}
+
+/// Draw the current weather together with today's range, chance of precipitation and sunrise and sunset, as a card like the current weather one.
+fn draw_today_card(
+	place: &str,
+	current: &CurrentWeather,
+	today: &TodayWeather,
+	sun: &str,
+	units: UnitSystem,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let palette = Theme::Default.palette();
+	let temperature = units.temperature_symbol();
+	let (Some(min), Some(max), Some(probability)) = (
+		today.temperature_2m_min.first(),
+		today.temperature_2m_max.first(),
+		today.precipitation_probability_max.first(),
+	) else {
+		return Err(Error::friendly("No forecast for today at this location"));
+	};
+
+	let description =
+		capitalize(weather_code_to_str(current.weather_code).unwrap_or("unknown weather"));
+	let now = [
+		format!("{:.1}{temperature}", current.temperature_2m),
+		format!("{:.1}{temperature}", current.apparent_temperature),
+	];
+	let range = [
+		format!("{min:.1}{temperature}"),
+		format!("{max:.1}{temperature}"),
+	];
+	let probability = format!("{probability}%");
+
+	let lines = [
+		card_line(&[TextSegment::white(place)], header_font, CARD_HEADER_SIZE),
+		card_line(&[TextSegment::white(&description)], font, CARD_TEXT_SIZE),
+		card_line(
+			&[
+				TextSegment::white("Now "),
+				TextSegment::new(&now[0], Rgb(palette.temperature)),
+				TextSegment::white(", feels like "),
+				TextSegment::new(&now[1], Rgb(palette.apparent_temperature)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+		card_line(
+			&[
+				TextSegment::white("Today "),
+				TextSegment::new(&range[0], Rgb(palette.temperature)),
+				TextSegment::white(" to "),
+				TextSegment::new(&range[1], Rgb(palette.temperature)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+		card_line(
+			&[
+				TextSegment::white("Chance of precipitation "),
+				TextSegment::new(&probability, Rgb(palette.precipitation_probability)),
+			],
+			font,
+			CARD_TEXT_SIZE,
+		),
+		card_line(&[TextSegment::white(sun)], font, CARD_TEXT_SIZE),
+	];
+	Ok(make_png(composite(&lines.map(Chart::into_canvas))))
+}
+
+/// The current weather and the rest of today at a glance, fetching the current, daily and sun forecasts at the same time.
+pub async fn handle_today(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+	let coordinates = location.coordinates();
+
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let (current, today, sun) = tokio::join!(
+		CurrentResult::get(coordinates, units, &client),
+		TodayResult::get(coordinates, units, &client),
+		SunResult::get(coordinates, 1, &client),
+	);
+	let current = current?.current;
+	let image = draw_today_card(
+		location.name(),
+		&current,
+		&today?.daily,
+		&sun?.describe_today()?,
+		units,
+		font,
+		header_font,
+	)?;
+
+	let content = format!(
+		"{} {} in {}.",
+		weather_code_to_emoji(current.weather_code).unwrap_or("❔"),
+		capitalize(weather_code_to_str(current.weather_code).unwrap_or("unknown weather")),
+		location.short_description()
+	);
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(content)
+				.add_file(CreateAttachment::bytes(image, "today.png")),
+		)
+		.await?;
+	Ok(())
 }
 
 pub async fn handle_current(
 	context: &Context,
 	interaction: &CommandInteraction,
 	database: &Pool<Sqlite>,
-	_font: &FontRef<'static>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
 ) -> Result<(), Error> {
-	let client = Client::new();
+	let client = open_meteo::client();
 	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
 
-	let weather = CurrentResult::get(location.coordinates(), &client).await?;
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let weather = CurrentResult::get(location.coordinates(), units, &client).await?;
 	let current = weather.current;
 
+	let as_text = interaction.bool_option("text").unwrap_or(false);
+	if !as_text {
+		let image = draw_current_card(location.name(), &current, units, font, header_font);
+		interaction
+			.create_response(
+				context,
+				CreateInteractionResponse::Message(
+					CreateInteractionResponseMessage::new()
+						.add_file(CreateAttachment::bytes(image, "current.png")),
+				),
+			)
+			.await?;
+		return Ok(());
+	}
+
 	let interval_text = current
 		.interval
 		.and_then(|interval| {
@@ -99,13 +432,382 @@ pub async fn handle_current(
 			static TEXT: LazyLock<stringify_interval::Text> =
 				LazyLock::new(stringify_interval::Text::default);
 			stringify_interval::without_date(Duration::seconds(interval as i64), &CONFIG, &TEXT)
-				.inspect_err(|error| eprintln!("{error}"))
+				.inspect_err(|error| tracing::error!(%error, "Failed to stringify interval"))
 				.ok()
 		})
 		.unwrap_or(String::from("unknown"));
 
-	let content = format!("Temperature: {}°C, apparent temperature: {}°C, relative humidity: {}%, precipitation: {}mm, rain: {}mm, showers: {}mm, snowfall: {}cm, weather code: {}, cloud cover: {}%, wind speed: {}km/h, wind direction: {}°, wind gusts: {}km/h, UVI: {}, clear-sky UVI: {}, interval: {}", current.temperature_2m, current.apparent_temperature, current.relative_humidity_2m, current.precipitation, current.rain, current.showers, current.snowfall, weather_code_to_str(current.weather_code).unwrap_or("?"), current.cloud_cover, current.wind_speed_10m, current.wind_direction_10m, current.wind_gusts_10m, current.uv_index, current.uv_index_clear_sky, interval_text);
+	let (temperature, precipitation, snowfall, wind_speed) = (
+		units.temperature_symbol(),
+		units.precipitation_symbol(),
+		units.snowfall_symbol(),
+		units.wind_speed_symbol(),
+	);
+	let [red, green, blue] =
+		temperature_colour(units.temperature_to_celsius(current.temperature_2m));
+	let mut embed = CreateEmbed::new()
+		.title(location.name())
+		.description(format!(
+			"{} {}",
+			weather_code_to_emoji(current.weather_code).unwrap_or("❔"),
+			capitalize(weather_code_to_str(current.weather_code).unwrap_or("unknown weather"))
+		))
+		.colour(Colour::from_rgb(red, green, blue))
+		.field(
+			"Temperature",
+			format!("{}{temperature}", current.temperature_2m),
+			true,
+		)
+		.field(
+			"Apparent temperature",
+			format!("{}{temperature}", current.apparent_temperature),
+			true,
+		);
+	if let Some((name, index)) = chill_or_heat_index(&current, units) {
+		embed = embed.field(capitalize(name), format!("{index:.1}{temperature}"), true);
+	}
+	let embed = embed
+		.field(
+			"Relative humidity",
+			format!("{}%", current.relative_humidity_2m),
+			true,
+		)
+		.field("Cloud cover", format!("{}%", current.cloud_cover), true)
+		.field(
+			"Precipitation",
+			format!("{}{precipitation}", current.precipitation),
+			true,
+		)
+		.field("Rain", format!("{}{precipitation}", current.rain), true)
+		.field(
+			"Showers",
+			format!("{}{precipitation}", current.showers),
+			true,
+		)
+		.field("Snowfall", format!("{}{snowfall}", current.snowfall), true)
+		.field(
+			"Wind",
+			format!(
+				"{}{wind_speed} from {} ({}°)",
+				current.wind_speed_10m,
+				compass_point(current.wind_direction_10m as f32),
+				current.wind_direction_10m
+			),
+			true,
+		)
+		.field(
+			"Wind gusts",
+			format!("{}{wind_speed}", current.wind_gusts_10m),
+			true,
+		)
+		.field(
+			"UV index",
+			format!(
+				"{} ({} with a clear sky)",
+				current.uv_index, current.uv_index_clear_sky
+			),
+			true,
+		)
+		.footer(CreateEmbedFooter::new(format!("Interval: {interval_text}")));
+
+	interaction.reply_embed(&context.http, embed, false).await?;
+	Ok(())
+}
+
+/// The current weather as a single line, like "⛅ 22°C, feels 21°C, 💨 12 km/h".
+fn summary_line(current: &CurrentWeather, units: UnitSystem) -> String {
+	let temperature = units.temperature_symbol();
+	format!(
+		"{} {:.0}{temperature}, feels {:.0}{temperature}, 💨 {:.0} {}",
+		weather_code_to_emoji(current.weather_code).unwrap_or("❔"),
+		current.temperature_2m,
+		current.apparent_temperature,
+		current.wind_speed_10m,
+		units.wind_speed_symbol(),
+	)
+}
+
+pub async fn handle_weather(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let current = CurrentResult::get(location.coordinates(), units, &client)
+		.await?
+		.current;
+	interaction
+		.public_reply(&context.http, summary_line(&current, units))
+		.await?;
+	Ok(())
+}
+
+/// How the apparent temperature comes about, in °C.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FeelsLike {
+	temperature: f32,
+	/// How much colder the wind makes it feel, as a negative number, or 0 where wind chill isn't defined.
+	wind: f32,
+	/// How much warmer humid air makes it feel, or 0 where the heat index isn't defined.
+	humidity: f32,
+	/// Whatever else Open-Meteo's apparent temperature accounts for, like the sun, which the wind chill and heat index formulas leave out.
+	other: f32,
+	apparent: f32,
+}
+
+impl FeelsLike {
+	fn new(temperature: f32, humidity: f32, wind_kmh: f32, apparent: f32) -> Self {
+		let wind = wind_chill(temperature, wind_kmh).map_or(0.0, |chill| chill - temperature);
+		let humidity = heat_index(temperature, humidity).map_or(0.0, |index| index - temperature);
+		Self {
+			temperature,
+			wind,
+			humidity,
+			other: apparent - temperature - wind - humidity,
+			apparent,
+		}
+	}
+	/// One sentence on what makes the biggest difference.
+	fn explanation(&self) -> &'static str {
+		let biggest = [self.wind, self.humidity, self.other]
+			.into_iter()
+			.max_by(|a, b| a.abs().total_cmp(&b.abs()))
+			.unwrap_or(0.0);
+		if (self.apparent - self.temperature).abs() < 1.0 {
+			"It feels about as warm as the thermometer says."
+		} else if biggest == self.wind {
+			"The wind carries heat away from your skin, so it feels colder than it is."
+		} else if biggest == self.humidity {
+			"Humid air keeps sweat from evaporating, so it feels hotter than it is."
+		} else if self.other > 0.0 {
+			"Sunshine and calm air make it feel warmer than it is."
+		} else {
+			"Damp air and a lack of sun make it feel colder than it is."
+		}
+	}
+}
+
+/// Break the current apparent temperature down into what makes it differ from the actual temperature.
+pub async fn handle_feels_like(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	// In metric, as the wind chill and heat index formulas take it.
+	let current = CurrentResult::get(location.coordinates(), UnitSystem::Metric, &client)
+		.await?
+		.current;
+	let feels_like = FeelsLike::new(
+		current.temperature_2m,
+		current.relative_humidity_2m,
+		current.wind_speed_10m,
+		current.apparent_temperature,
+	);
+
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let symbol = units.temperature_symbol();
+	let temperature =
+		|celsius: f32| format!("{:.1}{symbol}", units.temperature_from_celsius(celsius));
+	let difference = |celsius: f32| {
+		let difference =
+			units.temperature_from_celsius(celsius) - units.temperature_from_celsius(0.0);
+		format!("{difference:+.1}{symbol}")
+	};
+	let wind = if feels_like.wind == 0.0 {
+		String::from("none, only from 10 °C and with some wind")
+	} else {
+		difference(feels_like.wind)
+	};
+	let humidity = if feels_like.humidity == 0.0 {
+		String::from("none, only from 27 °C")
+	} else {
+		difference(feels_like.humidity)
+	};
+	let content = format!(
+		"**Feels like in {}**\nThermometer: {}\nWind chill: {wind}\nHumidity: {humidity}\nSun and everything else: {}\nFeels like: {}\n{}",
+		location.short_description(),
+		temperature(feels_like.temperature),
+		difference(feels_like.other),
+		temperature(feels_like.apparent),
+		feels_like.explanation(),
+	);
+	interaction.public_reply(&context.http, content).await?;
+	Ok(())
+}
+
+/// Show a single current reading in both metric and imperial units, side by side.
+pub async fn handle_units_demo(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let current = CurrentResult::get(location.coordinates(), UnitSystem::Metric, &client)
+		.await?
+		.current;
+
+	let metric = format!(
+		"Temperature: {:.1}°C\nApparent: {:.1}°C\nPrecipitation: {:.1} mm\nSnowfall: {:.1} cm\nWind: {:.0} km/h\nGusts: {:.0} km/h",
+		current.temperature_2m,
+		current.apparent_temperature,
+		current.precipitation,
+		current.snowfall,
+		current.wind_speed_10m,
+		current.wind_gusts_10m,
+	);
+	let imperial = format!(
+		"Temperature: {:.1}°F\nApparent: {:.1}°F\nPrecipitation: {:.2} in\nSnowfall: {:.1} in\nWind: {:.0} mph\nGusts: {:.0} mph",
+		celsius_to_fahrenheit(current.temperature_2m),
+		celsius_to_fahrenheit(current.apparent_temperature),
+		mm_to_inches(current.precipitation),
+		cm_to_inches(current.snowfall),
+		kmh_to_mph(current.wind_speed_10m),
+		kmh_to_mph(current.wind_gusts_10m),
+	);
+	let embed = CreateEmbed::new()
+		.title("The same reading in both unit systems")
+		.description(location.to_string())
+		.field("Metric", metric, true)
+		.field("Imperial", imperial, true);
+
+	interaction
+		.create_response(
+			context,
+			CreateInteractionResponse::Message(
+				CreateInteractionResponseMessage::new().embed(embed),
+			),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Current weather at a place picked at random, for wandering the world's weather without a place in mind.
+pub async fn handle_random_place(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let place = random_place::pick(&mut rand::thread_rng());
 
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let current = CurrentResult::get(place.coordinates(), units, &client)
+		.await?
+		.current;
+	let image = draw_current_card(place.name, &current, units, font, header_font);
+	interaction
+		.create_response(
+			context,
+			CreateInteractionResponse::Message(
+				CreateInteractionResponseMessage::new()
+					.content(format!("You wandered to {}.", place.name))
+					.add_file(CreateAttachment::bytes(image, "current.png")),
+			),
+		)
+		.await?;
+	Ok(())
+}
+
+/// The place named in a message that starts by mentioning the bot, which is empty if nothing comes after the mention. `None` if the message doesn't start with a mention of the bot.
+pub fn mentioned_place(content: &str, bot: UserId) -> Option<&str> {
+	let content = content.trim_start();
+	[format!("<@{bot}>"), format!("<@!{bot}>")]
+		.iter()
+		.find_map(|mention| content.strip_prefix(mention.as_str()))
+		.map(str::trim)
+}
+
+/// Reply to a message mentioning the bot with the current weather card for the place named after the mention, or else for the author's saved location.
+pub async fn reply_to_mention(
+	context: &Context,
+	message: &Message,
+	place: &str,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = if place.is_empty() {
+		let location =
+			Location::get_for_user(database, message.author.id, message.guild_id, None).await?;
+		let location = match (location, message.guild_id) {
+			(None, Some(guild)) => Location::get_for_guild(database, guild).await?,
+			(location, _) => location,
+		};
+		location.ok_or_else(|| Error::friendly("No location set, and no location provided"))?
+	} else {
+		Location::try_from_arg(place, &client, database).await?
+	};
+
+	let units = UnitSystem::get_for_user(database, message.author.id).await?;
+	let current = CurrentResult::get(location.coordinates(), units, &client)
+		.await?
+		.current;
+	let image = draw_current_card(location.name(), &current, units, font, header_font);
+	message
+		.channel_id
+		.send_message(
+			&context.http,
+			CreateMessage::new()
+				.reference_message(message)
+				.add_file(CreateAttachment::bytes(image, "current.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Current weather at whichever match for a place name is nearest to a reference point, rather than at the most prominent match. The reference is the "near" option, or else the user's saved location.
+pub async fn handle_nearest_weather(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let place = interaction
+		.string_option("place")
+		.ok_or_else(|| Error::custom_unfriendly("Missing place option"))?;
+
+	let client = open_meteo::client();
+	let reference = match interaction.string_option("near") {
+		Some(arg) => Location::try_from_arg(arg, &client, database).await?,
+		None => Location::get_for_user(
+			database,
+			interaction.user.id,
+			interaction.guild_id,
+			label_option(interaction)?,
+		)
+		.await?
+		.ok_or_else(|| {
+			Error::friendly("No location set, and no location to search near provided")
+		})?,
+	};
+	let location = Location::from_geocoding_result(
+		GeocodingResult::get_nearest(place, reference.coordinates(), &client, database).await?,
+	);
+	let distance = location.coordinates().distance_km(reference.coordinates());
+
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let current = CurrentResult::get(location.coordinates(), units, &client)
+		.await?
+		.current;
+
+	let content = format!(
+		"{location}, {:.0} km from {}: {:.1}{}, {}",
+		distance,
+		reference.short_description(),
+		current.temperature_2m,
+		units.temperature_symbol(),
+		weather_code_to_str(current.weather_code).unwrap_or("unknown weather"),
+	);
 	interaction
 		.create_response(
 			context,
@@ -118,14 +820,159 @@ pub async fn handle_current(
 }
 
 pub fn create_current() -> CreateCommand {
-	CreateCommand::new("current")
-		.description("Current weather")
+	i18n::command("current", "Current weather")
 		.add_option(
 			CreateCommandOption::new(
 				CommandOptionType::String,
 				"place",
 				"The place to get the weather of.",
 			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::Boolean,
+				"text",
+				"Reply in plain text instead of an image, for example for screen readers.",
+			)
 			.required(false),
 		)
 }
+
+pub fn create_today() -> CreateCommand {
+	i18n::command("today", "What it's like now and for the rest of today")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_private_option())
+}
+
+pub fn create_weather() -> CreateCommand {
+	i18n::command("weather", "Current weather in a single line")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+pub fn create_feels_like() -> CreateCommand {
+	i18n::command(
+		"feels_like",
+		"Why it feels warmer or colder than the thermometer says",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+}
+
+pub fn create_units_demo() -> CreateCommand {
+	i18n::command(
+		"units_demo",
+		"Current weather in both metric and imperial units",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+}
+
+pub fn create_nearest_weather() -> CreateCommand {
+	i18n::command(
+		"nearest_weather",
+		"Current weather at the match for a place name that is nearest to you",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather of.",
+		)
+		.required(true)
+		.set_autocomplete(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"near",
+			"Where to look for the nearest match. Defaults to your saved location.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+}
+
+pub fn create_random_place() -> CreateCommand {
+	i18n::command("random_place", "Current weather somewhere in the world")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn feels_like_broken_down() {
+		// Cold and windy: the wind chill is most of it.
+		let windy = FeelsLike::new(0.0, 80.0, 30.0, -6.0);
+		assert!((windy.wind - -6.47).abs() < 0.01, "{windy:?}");
+		assert_eq!(windy.humidity, 0.0);
+		assert!((windy.other - 0.47).abs() < 0.01, "{windy:?}");
+		assert!(windy.explanation().contains("wind"));
+		// Hot and humid: the heat index is.
+		let humid = FeelsLike::new(32.0, 70.0, 5.0, 40.0);
+		assert_eq!(humid.wind, 0.0);
+		assert!((humid.humidity - 8.41).abs() < 0.01, "{humid:?}");
+		assert!(humid.explanation().contains("Humid"));
+		// The parts add up to the apparent temperature.
+		for feels_like in [windy, humid] {
+			let total =
+				feels_like.temperature + feels_like.wind + feels_like.humidity + feels_like.other;
+			assert!((total - feels_like.apparent).abs() < 0.001);
+		}
+		let mild = FeelsLike::new(18.0, 50.0, 3.0, 17.6);
+		assert_eq!(
+			mild.explanation(),
+			"It feels about as warm as the thermometer says."
+		);
+	}
+	#[test]
+	fn place_after_mention() {
+		let bot = UserId::new(1234);
+		assert_eq!(mentioned_place("<@1234> Amsterdam", bot), Some("Amsterdam"));
+		assert_eq!(
+			mentioned_place("  <@!1234>   New York ", bot),
+			Some("New York")
+		);
+		assert_eq!(mentioned_place("<@1234>", bot), Some(""));
+		assert_eq!(mentioned_place("<@5678> Amsterdam", bot), None);
+		assert_eq!(mentioned_place("Weather in Amsterdam, <@1234>?", bot), None);
+	}
+}
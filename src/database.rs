@@ -7,6 +7,7 @@ use sqlx::{
 };
 
 pub async fn init_database<P: AsRef<Path>>(path: P) -> Pool<Sqlite> {
+	let path = path.as_ref();
 	let pool = SqlitePoolOptions::new()
 		.max_connections(4)
 		.connect_with(
@@ -15,7 +16,12 @@ pub async fn init_database<P: AsRef<Path>>(path: P) -> Pool<Sqlite> {
 				.create_if_missing(true),
 		)
 		.await
-		.unwrap();
+		.unwrap_or_else(|error| {
+			panic!(
+				"Could not open database {} ({error}); set WEATHERBOT_DB_PATH to change its path",
+				path.display()
+			)
+		});
 
 	migrate!("./migrations")
 		.run(&pool)
@@ -24,3 +30,15 @@ pub async fn init_database<P: AsRef<Path>>(path: P) -> Pool<Sqlite> {
 
 	pool
 }
+
+/// A fresh database that only lives as long as the pool, for tests.
+#[cfg(test)]
+pub async fn in_memory() -> Pool<Sqlite> {
+	let pool = SqlitePoolOptions::new()
+		.max_connections(1)
+		.connect("sqlite::memory:")
+		.await
+		.unwrap();
+	migrate!("./migrations").run(&pool).await.unwrap();
+	pool
+}
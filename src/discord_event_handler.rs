@@ -1,25 +1,64 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use itertools::Itertools;
 use serenity::{
-	all::{Context, EventHandler, Interaction, Ready},
+	all::{
+		Command, CommandInteraction, Context, CreateCommand, CreateInteractionResponseFollowup,
+		EditInteractionResponse, EventHandler, InstallationContext, Interaction,
+		InteractionContext, Message, Ready,
+	},
 	async_trait,
 };
 use sqlx::{Pool, Sqlite};
 
 use crate::{
-	current::{self, handle_current},
-	daily_forecast::{self, handle_daily},
+	alerts::{self, handle_alerts},
+	almanac::{self, handle_almanac},
+	current::{
+		self, handle_current, handle_feels_like, handle_nearest_weather, handle_random_place,
+		handle_today, handle_units_demo, handle_weather, mentioned_place, reply_to_mention,
+	},
 	error::Error,
-	geocoding::{self, handle_find_coordinates},
-	hourly_forecast::{self, handle_hourly},
-	reply_shortcuts::ReplyShortcuts,
+	event::{self, handle_weather_for_event},
+	fog::{self, handle_visibility, handle_visibility_now},
+	forecasts::{
+		air_quality::{self, handle_air_quality, handle_pollen},
+		daily::{self, handle_daily, handle_forecast_text},
+		history::{self, handle_history},
+		hourly::{
+			self, handle_compare, handle_extreme_hour, handle_hourly, handle_precip_accumulation,
+			TemperatureExtreme,
+		},
+		marine::{self, handle_marine},
+		pressure::{self, handle_pressure},
+		seasonal::{self, handle_seasonal},
+		snow::{self, handle_snow},
+		soil::{self, handle_soil},
+		temp_histogram::{self, handle_temperature_histogram},
+	},
+	geocoding::{self, handle_find_coordinates, handle_place_autocomplete, is_place_pick},
+	i18n,
+	moon::{self, handle_moon, handle_moonrise},
+	pin::{self, handle_pin, update_pins_periodically},
+	refresh::{handle_refresh, is_refresh},
+	reply_shortcuts::{response_state, track_response, ReplyShortcuts, ResponseState},
+	status::{self, handle_status},
+	subscriptions::{self, handle_subscribe, handle_unsubscribe, send_subscriptions_periodically},
 	sunrise_sunset::{self, handle_sun},
-	user_locations::{self, handle_set_location, handle_unset_location},
+	user_locations::{
+		self, handle_forget_me, handle_list_locations, handle_set_coords,
+		handle_set_guild_location, handle_set_home_timezone, handle_set_location, handle_set_units,
+		handle_unset_location,
+	},
 };
 
 pub struct DiscordEventHandler {
 	database: Pool<Sqlite>,
 	font: ab_glyph::FontRef<'static>,
 	header_font: ab_glyph::FontRef<'static>,
+	background_tasks_started: AtomicBool,
+	/// Whether to answer messages that start by mentioning the bot, which needs the privileged message content intent.
+	answer_mentions: bool,
 }
 
 impl DiscordEventHandler {
@@ -27,88 +66,500 @@ impl DiscordEventHandler {
 		database: Pool<Sqlite>,
 		font: ab_glyph::FontRef<'static>,
 		header_font: ab_glyph::FontRef<'static>,
+		answer_mentions: bool,
 	) -> Self {
 		Self {
 			database,
 			font,
 			header_font,
+			background_tasks_started: AtomicBool::new(false),
+			answer_mentions,
 		}
 	}
+	/// Run the command, and tell the user if it failed.
+	async fn command(&self, context: &Context, interaction: &CommandInteraction) {
+		// Discord only waits three seconds for a response. Fetching a forecast and drawing it can take longer than that, so those commands defer first and then reply with a follow-up, while the quick text replies respond right away.
+		let result = match interaction.data.name.as_str() {
+			"find_coordinates" => {
+				handle_find_coordinates(context, interaction, &self.database).await
+			}
+			"current" => {
+				handle_current(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"feels_like" => handle_feels_like(context, interaction, &self.database).await,
+			"random_place" => {
+				handle_random_place(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"today" => {
+				handle_today(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"weather" => handle_weather(context, interaction, &self.database).await,
+			"units_demo" => handle_units_demo(context, interaction, &self.database).await,
+			"nearest_weather" => handle_nearest_weather(context, interaction, &self.database).await,
+			"hourly" => {
+				handle_hourly(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"compare" => {
+				handle_compare(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"precip_accumulation" => {
+				handle_precip_accumulation(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"snow" => {
+				handle_snow(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"soil" => {
+				handle_soil(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"hottest_hour" => {
+				handle_extreme_hour(
+					context,
+					interaction,
+					&self.database,
+					TemperatureExtreme::Hottest,
+				)
+				.await
+			}
+			"coldest_hour" => {
+				handle_extreme_hour(
+					context,
+					interaction,
+					&self.database,
+					TemperatureExtreme::Coldest,
+				)
+				.await
+			}
+			"daily" => {
+				handle_daily(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"forecast_text" => handle_forecast_text(context, interaction, &self.database).await,
+			"seasonal" => {
+				handle_seasonal(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"temperature_histogram" => {
+				handle_temperature_histogram(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"air_quality" => {
+				handle_air_quality(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"history" => {
+				handle_history(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"pressure" => {
+				handle_pressure(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"pollen" => {
+				handle_pollen(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"marine" => {
+				handle_marine(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"weather_for_event" => {
+				handle_weather_for_event(context, interaction, &self.database).await
+			}
+			"status" => handle_status(context, interaction).await,
+			"almanac" => handle_almanac(context, interaction, &self.database).await,
+			"alerts" => handle_alerts(context, interaction, &self.database).await,
+			"pin" => {
+				handle_pin(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"sun" => {
+				handle_sun(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"moon" => handle_moon(context, interaction, &self.database).await,
+			"moonrise" => handle_moonrise(context, interaction, &self.database).await,
+			"visibility" => {
+				handle_visibility(
+					context,
+					interaction,
+					&self.database,
+					&self.font,
+					&self.header_font,
+				)
+				.await
+			}
+			"visibility_now" => handle_visibility_now(context, interaction, &self.database).await,
+			"set_location" => handle_set_location(context, interaction, &self.database).await,
+			"set_coords" => handle_set_coords(context, interaction, &self.database).await,
+			"set_guild_location" => {
+				handle_set_guild_location(context, interaction, &self.database).await
+			}
+			"unset_location" => handle_unset_location(context, interaction, &self.database).await,
+			"list_locations" => handle_list_locations(context, interaction, &self.database).await,
+			"set_units" => handle_set_units(context, interaction, &self.database).await,
+			"set_home_timezone" => {
+				handle_set_home_timezone(context, interaction, &self.database).await
+			}
+			"forget_me" => handle_forget_me(context, interaction, &self.database).await,
+			"subscribe" => handle_subscribe(context, interaction, &self.database).await,
+			"unsubscribe" => handle_unsubscribe(context, interaction, &self.database).await,
+			name => return tracing::warn!(command = name, "Unknown command"),
+		};
+		let text = match result {
+			Err(Error::Friendly(text)) => text,
+			Err(error @ Error::Unfriendly(_)) => {
+				tracing::error!(
+					command = interaction.data.name,
+					guild = ?interaction.guild_id,
+					user = %interaction.user.id,
+					error = %error.chain(),
+					"Command failed"
+				);
+				String::from("Error")
+			}
+			Ok(_) => return,
+		};
+		let text = String::from(i18n::translate(&text, &interaction.locale));
+		let _ = match response_state() {
+			ResponseState::Pending => interaction.ephemeral_reply(&context.http, text).await,
+			// A follow-up would take on the deferred response's visibility anyway, so the error takes its place, which keeps it private if the command was.
+			ResponseState::Deferred => interaction
+				.edit_response(&context.http, EditInteractionResponse::new().content(text))
+				.await
+				.map(drop),
+			ResponseState::Sent => interaction
+				.create_followup(
+					&context.http,
+					CreateInteractionResponseFollowup::new()
+						.content(text)
+						.ephemeral(true),
+				)
+				.await
+				.map(drop),
+		};
+	}
 }
 
 #[async_trait]
 impl EventHandler for DiscordEventHandler {
 	async fn interaction_create(&self, context: Context, interaction: Interaction) {
 		if let Interaction::Command(interaction) = interaction {
-			let result = match interaction.data.name.as_str() {
-				"find_coordinates" => handle_find_coordinates(&context, &interaction).await,
-				"current" => {
-					handle_current(&context, &interaction, &self.database, &self.font).await
-				}
-				"hourly" => {
-					handle_hourly(
-						&context,
-						&interaction,
-						&self.database,
-						&self.font,
-						&self.header_font,
-					)
-					.await
-				}
-				"daily" => {
-					handle_daily(
-						&context,
-						&interaction,
-						&self.database,
-						&self.font,
-						&self.header_font,
-					)
-					.await
-				}
-				"sun" => handle_sun(&context, &interaction, &self.database).await,
-				"set_location" => handle_set_location(&context, &interaction, &self.database).await,
-				"unset_location" => {
-					handle_unset_location(&context, &interaction, &self.database).await
-				}
-				name => return println!("Unknown command: {name}"),
-			};
-			match result {
-				Err(Error::Friendly(text)) => {
-					let _ = interaction.ephemeral_reply(&context.http, text).await;
-				}
-				Err(Error::Unfriendly(error)) => {
-					println!("{}", error);
-					let _ = interaction.ephemeral_reply(&context.http, "Error").await;
+			track_response(self.command(&context, &interaction)).await;
+		} else if let Interaction::Autocomplete(interaction) = interaction {
+			// Suggestions are only a convenience, so failures are just logged.
+			if let Err(error) = handle_place_autocomplete(&context, &interaction).await {
+				tracing::warn!(
+					command = interaction.data.name,
+					user = %interaction.user.id,
+					error = %error.chain(),
+					"Autocomplete failed"
+				);
+			}
+		} else if let Interaction::Component(interaction) = interaction {
+			// Place picks are collected by the command that offered them.
+			if is_place_pick(&interaction) {
+				return;
+			}
+			if !is_refresh(&interaction) {
+				return tracing::warn!(custom_id = interaction.data.custom_id, "Unknown component");
+			}
+			let result = handle_refresh(
+				&context,
+				&interaction,
+				&self.database,
+				&self.font,
+				&self.header_font,
+			)
+			.await;
+			let text = match result {
+				Err(Error::Friendly(text)) => text,
+				Err(error @ Error::Unfriendly(_)) => {
+					tracing::error!(
+						custom_id = interaction.data.custom_id,
+						guild = ?interaction.guild_id,
+						user = %interaction.user.id,
+						error = %error.chain(),
+						"Refresh failed"
+					);
+					String::from("Error")
 				}
-				Ok(_) => (),
+				Ok(_) => return,
 			};
+			let text = String::from(i18n::translate(&text, &interaction.locale));
+			let _ = interaction
+				.create_followup(
+					&context.http,
+					CreateInteractionResponseFollowup::new()
+						.content(text)
+						.ephemeral(true),
+				)
+				.await;
 		}
 	}
+	async fn message(&self, context: Context, message: Message) {
+		if !self.answer_mentions || message.author.bot {
+			return;
+		}
+		let Some(place) = mentioned_place(&message.content, context.cache.current_user().id) else {
+			return;
+		};
+		let result = reply_to_mention(
+			&context,
+			&message,
+			place,
+			&self.database,
+			&self.font,
+			&self.header_font,
+		)
+		.await;
+		let text = match result {
+			Err(Error::Friendly(text)) => text,
+			Err(error @ Error::Unfriendly(_)) => {
+				tracing::error!(
+					guild = ?message.guild_id,
+					user = %message.author.id,
+					error = %error.chain(),
+					"Answering mention failed"
+				);
+				String::from("Error")
+			}
+			Ok(_) => return,
+		};
+		let _ = message.reply(&context.http, text).await;
+	}
 	async fn ready(&self, context: Context, _ready: Ready) {
-		println!("Ready");
+		tracing::info!("Ready");
+		if !self.background_tasks_started.swap(true, Ordering::SeqCst) {
+			tokio::spawn(update_pins_periodically(
+				context.http.clone(),
+				self.database.clone(),
+				self.font.clone(),
+				self.header_font.clone(),
+			));
+			tokio::spawn(send_subscriptions_periodically(
+				context.http.clone(),
+				self.database.clone(),
+				self.font.clone(),
+				self.header_font.clone(),
+			));
+		}
+		// `register` sets the commands in every guild the bot is in, which takes effect right away. `register global` or `register-global` sets them once for everywhere, including direct messages and guilds joined later.
 		let arg = std::env::args().nth(1);
-		if Some("register") == arg.as_deref() {
+		if matches!(arg.as_deref(), Some("register" | "register-global")) {
 			let commands = Vec::from([
 				geocoding::create_find_coordinates(),
 				current::create_current(),
-				hourly_forecast::create_hourly(),
-				daily_forecast::create_daily(),
+				current::create_today(),
+				current::create_weather(),
+				current::create_units_demo(),
+				current::create_nearest_weather(),
+				current::create_random_place(),
+				current::create_feels_like(),
+				hourly::create_hourly(),
+				hourly::create_hottest_hour(),
+				hourly::create_coldest_hour(),
+				hourly::create_compare(),
+				hourly::create_precip_accumulation(),
+				daily::create_daily(),
+				daily::create_forecast_text(),
+				history::create_history(),
+				seasonal::create_seasonal(),
+				temp_histogram::create_temperature_histogram(),
+				air_quality::create_air_quality(),
+				air_quality::create_pollen(),
+				pressure::create_pressure(),
+				marine::create_marine(),
+				snow::create_snow(),
+				soil::create_soil(),
+				event::create_weather_for_event(),
 				user_locations::create_set_location(),
+				user_locations::create_set_coords(),
 				user_locations::create_unset_location(),
+				user_locations::create_list_locations(),
+				user_locations::create_set_units(),
+				user_locations::create_set_home_timezone(),
+				user_locations::create_forget_me(),
+				subscriptions::create_subscribe(),
+				subscriptions::create_unsubscribe(),
 				sunrise_sunset::create_sun(),
+				moon::create_moon(),
+				moon::create_moonrise(),
+				fog::create_visibility(),
+				fog::create_visibility_now(),
+				almanac::create_almanac(),
+				alerts::create_alerts(),
+				status::create_status(),
 			]);
+			// Pins are edited through the channel later, which only works where the bot itself is, and a server's default location only means something in that server.
+			let guild_only = [
+				pin::create_pin(),
+				user_locations::create_set_guild_location(),
+			];
+			let global = arg.as_deref() == Some("register-global")
+				|| std::env::args().nth(2).as_deref() == Some("global");
+			if global {
+				let commands = commands
+					.into_iter()
+					.map(user_installable)
+					.chain(guild_only.map(guild_installed))
+					.collect::<Vec<_>>();
+				let commands = Command::set_global_commands(&context.http, commands)
+					.await
+					.unwrap();
+				let command_names = commands.into_iter().map(|command| command.name).join(", ");
+				tracing::info!(
+					commands = command_names,
+					"Registered global slash commands. Discord can take a while to show changes to global commands everywhere; use `register` for per-guild commands that update right away while testing."
+				);
+				return;
+			}
+			let commands = commands.into_iter().chain(guild_only).collect::<Vec<_>>();
 			for guild in context.cache.guilds() {
 				let commands = guild
 					.set_commands(&context.http, commands.clone())
 					.await
 					.unwrap();
 				let command_names = commands.into_iter().map(|command| command.name).join(", ");
-				println!(
-					"I now have the following guild slash commands in guild {}: {}",
-					guild.get(),
-					command_names
+				tracing::info!(
+					guild = guild.get(),
+					commands = command_names,
+					"Registered guild slash commands"
 				);
 			}
 		}
 	}
 }
+
+/// Let a global command be installed to a user's account as well as to a server, and be used in direct messages and in servers the bot is not in.
+fn user_installable(command: CreateCommand) -> CreateCommand {
+	command
+		.integration_types(vec![InstallationContext::Guild, InstallationContext::User])
+		.contexts(vec![
+			InteractionContext::Guild,
+			InteractionContext::BotDm,
+			InteractionContext::PrivateChannel,
+		])
+}
+
+/// Keep a global command to servers the bot is installed to, out of direct messages and user installs.
+fn guild_installed(command: CreateCommand) -> CreateCommand {
+	command
+		.integration_types(vec![InstallationContext::Guild])
+		.contexts(vec![InteractionContext::Guild])
+}
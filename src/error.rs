@@ -28,6 +28,24 @@ impl Error {
 	}
 }
 
+impl Error {
+	/// The error and every error that caused it, outermost first, for logging.
+	pub fn chain(&self) -> String {
+		match self {
+			Self::Friendly(text) => text.clone(),
+			Self::Unfriendly(error) => {
+				let mut chain = error.to_string();
+				let mut source = error.source();
+				while let Some(error) = source {
+					chain += &format!(": {error}");
+					source = error.source();
+				}
+				chain
+			}
+		}
+	}
+}
+
 impl Display for Error {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -59,3 +77,30 @@ impl Display for CustomError {
 }
 
 impl std::error::Error for CustomError {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug)]
+	struct Outer(CustomError);
+
+	impl Display for Outer {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			f.write_str("request failed")
+		}
+	}
+
+	impl std::error::Error for Outer {
+		fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+			Some(&self.0)
+		}
+	}
+
+	#[test]
+	fn chain_includes_sources() {
+		let error = Error::from(Outer(CustomError(String::from("connection reset"))));
+		assert_eq!(error.to_string(), "request failed");
+		assert_eq!(error.chain(), "request failed: connection reset");
+	}
+}
@@ -0,0 +1,328 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, complete_series, utc_offset, RequestBuilderExt},
+	reply_shortcuts::ReplyShortcuts,
+	util::CommandInteractionExt,
+};
+
+/// How far ahead Open-Meteo forecasts, in days.
+const FORECAST_HORIZON_DAYS: i64 = 16;
+/// The longest event window, in hours.
+const MAX_EVENT_HOURS: i64 = 24;
+/// Gusts above this many km/h make an event windy.
+const WINDY_GUSTS: f32 = 50.0;
+
+#[derive(Debug, Deserialize)]
+struct EventHourly {
+	/// Empty if Open-Meteo has no complete series for it, as are the others. Without temperatures there is no summary.
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation_probability: Vec<u8>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_gusts_10m: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventResult {
+	hourly: EventHourly,
+}
+
+impl EventResult {
+	/// Get the hours from `start` through `end`, both in the location's local time.
+	async fn get(
+		coordinates: Coordinates,
+		start: NaiveDateTime,
+		end: NaiveDateTime,
+		client: &Client,
+	) -> Result<Self, Error> {
+		let start = start.format("%Y-%m-%dT%H:%M").to_string();
+		let end = end.format("%Y-%m-%dT%H:%M").to_string();
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[
+				("hourly", "temperature_2m"),
+				("hourly", "precipitation_probability"),
+				("hourly", "precipitation"),
+				("hourly", "wind_gusts_10m"),
+				("timezone", "auto"),
+			])
+			.query(&[("start_hour", start), ("end_hour", end)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// Parse when an event starts, relative to the current local time.
+///
+/// Accepts a time (`14:00`), optionally preceded by `today`, `tomorrow`, a weekday (`saturday` or `sat`) or a date (`2024-07-20`). A bare time or weekday means its next occurrence.
+fn parse_start(input: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+	let (day, time) = match input.split_whitespace().collect_tuple() {
+		Some((day, time)) => (Some(day), time),
+		None => (None, input.trim()),
+	};
+	let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+	let today = now.date();
+	let date = match day.map(str::to_lowercase).as_deref() {
+		None => {
+			if today.and_time(time) >= now {
+				today
+			} else {
+				today.succ_opt()?
+			}
+		}
+		Some("today") => today,
+		Some("tomorrow") => today.succ_opt()?,
+		Some(day) => match day.parse::<Weekday>() {
+			Ok(weekday) => {
+				let days_ahead = (weekday.num_days_from_monday() + 7
+					- today.weekday().num_days_from_monday())
+					% 7;
+				let date = today + Duration::days(days_ahead as i64);
+				if date.and_time(time) >= now {
+					date
+				} else {
+					date + Duration::days(7)
+				}
+			}
+			Err(_) => NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?,
+		},
+	};
+	Some(date.and_time(time))
+}
+
+/// Conditions over the whole event window.
+struct EventSummary {
+	min_temperature: f32,
+	max_temperature: f32,
+	max_precipitation_probability: u8,
+	total_precipitation: f32,
+	max_gusts: f32,
+}
+
+impl EventSummary {
+	fn new(hourly: &EventHourly) -> Option<Self> {
+		let (min_temperature, max_temperature) = hourly
+			.temperature_2m
+			.iter()
+			.copied()
+			.minmax_by(f32::total_cmp)
+			.into_option()?;
+		Some(Self {
+			min_temperature,
+			max_temperature,
+			max_precipitation_probability: hourly
+				.precipitation_probability
+				.iter()
+				.copied()
+				.max()
+				.unwrap_or(0),
+			total_precipitation: hourly.precipitation.iter().sum(),
+			max_gusts: hourly.wind_gusts_10m.iter().copied().fold(0.0, f32::max),
+		})
+	}
+	/// A one-line verdict like "Looks dry and mild for your event."
+	fn verdict(&self) -> String {
+		let rain = if self.total_precipitation >= 0.5 || self.max_precipitation_probability >= 60 {
+			"Rain is likely, and it'll be"
+		} else if self.max_precipitation_probability >= 30 {
+			"There's a chance of rain, and it'll be"
+		} else {
+			"Looks dry and"
+		};
+		let average = (self.min_temperature + self.max_temperature) / 2.0;
+		let temperature = match average {
+			average if average < 5.0 => "cold",
+			average if average < 12.0 => "cool",
+			average if average < 22.0 => "mild",
+			average if average < 28.0 => "warm",
+			_ => "hot",
+		};
+		let wind = if self.max_gusts > WINDY_GUSTS {
+			" and windy"
+		} else {
+			""
+		};
+		format!("{rain} {temperature}{wind} for your event.")
+	}
+}
+
+pub async fn handle_weather_for_event(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let start = interaction
+		.string_option("start")
+		.ok_or_else(|| Error::custom_unfriendly("Missing start option"))?;
+	let hours = interaction
+		.integer_option("hours")
+		.ok_or_else(|| Error::custom_unfriendly("Missing hours option"))?
+		.clamp(1, MAX_EVENT_HOURS);
+
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let offset = utc_offset(location.coordinates(), &client).await?;
+	let now = Utc::now().with_timezone(&offset).naive_local();
+
+	let start = parse_start(start, now).ok_or_else(|| {
+		Error::friendly(
+			"Could not understand the start time. Try something like \"14:00\", \"saturday 14:00\" or \"2024-07-20 14:00\".",
+		)
+	})?;
+	let start = start.with_minute(0).unwrap_or(start);
+	let end = start + Duration::hours(hours - 1);
+	let current_hour = now
+		.with_minute(0)
+		.unwrap_or(now)
+		.with_second(0)
+		.unwrap_or(now);
+	if start < current_hour {
+		return Err(Error::friendly("That event is in the past."));
+	}
+	if end >= current_hour + Duration::days(FORECAST_HORIZON_DAYS) {
+		return Err(Error::friendly(format!(
+			"The forecast only goes {FORECAST_HORIZON_DAYS} days ahead."
+		)));
+	}
+
+	let result = EventResult::get(location.coordinates(), start, end, &client).await?;
+	let summary = EventSummary::new(&result.hourly)
+		.ok_or_else(|| Error::friendly("No forecast available for that window."))?;
+
+	let message = format!(
+		"{} from {} for {} hour{}: {:.0} to {:.0}°C, up to {}% chance of precipitation ({:.1} mm total), gusts up to {:.0} km/h. {}",
+		location.short_description(),
+		start.format("%A %H:%M"),
+		hours,
+		if hours == 1 { "" } else { "s" },
+		summary.min_temperature,
+		summary.max_temperature,
+		summary.max_precipitation_probability,
+		summary.total_precipitation,
+		summary.max_gusts,
+		summary.verdict()
+	);
+	interaction.public_reply(&context.http, message).await?;
+	Ok(())
+}
+
+pub fn create_weather_for_event() -> CreateCommand {
+	i18n::command(
+		"weather_for_event",
+		"Forecast summary for a specific window of time",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"start",
+			"When the event starts, like \"14:00\", \"saturday 14:00\" or \"2024-07-20 14:00\".",
+		)
+		.required(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::Integer,
+			"hours",
+			"How many hours the event lasts.",
+		)
+		.min_int_value(1)
+		.max_int_value(MAX_EVENT_HOURS as u64)
+		.required(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place of the event.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Friday 2024-07-19 at 10:00.
+	fn now() -> NaiveDateTime {
+		NaiveDate::from_ymd_opt(2024, 7, 19)
+			.unwrap()
+			.and_hms_opt(10, 0, 0)
+			.unwrap()
+	}
+
+	fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+		NaiveDate::from_ymd_opt(2024, 7, day)
+			.unwrap()
+			.and_hms_opt(hour, minute, 0)
+			.unwrap()
+	}
+
+	#[test]
+	fn parse_bare_time() {
+		assert_eq!(parse_start("14:00", now()), Some(at(19, 14, 0)));
+		assert_eq!(parse_start("9:30", now()), Some(at(20, 9, 30)));
+	}
+	#[test]
+	fn parse_relative_days() {
+		assert_eq!(parse_start("today 18:00", now()), Some(at(19, 18, 0)));
+		assert_eq!(parse_start("Tomorrow 7:00", now()), Some(at(20, 7, 0)));
+	}
+	#[test]
+	fn parse_weekdays() {
+		assert_eq!(parse_start("saturday 14:00", now()), Some(at(20, 14, 0)));
+		assert_eq!(parse_start("sun 14:00", now()), Some(at(21, 14, 0)));
+		assert_eq!(parse_start("friday 12:00", now()), Some(at(19, 12, 0)));
+		assert_eq!(parse_start("friday 9:00", now()), Some(at(26, 9, 0)));
+	}
+	#[test]
+	fn parse_date() {
+		assert_eq!(parse_start("2024-07-25 08:15", now()), Some(at(25, 8, 15)));
+	}
+	#[test]
+	fn parse_rejects_nonsense() {
+		assert_eq!(parse_start("soon", now()), None);
+		assert_eq!(parse_start("someday 14:00", now()), None);
+		assert_eq!(parse_start("saturday 25:00", now()), None);
+	}
+	#[test]
+	fn verdicts() {
+		let summary = EventSummary {
+			min_temperature: 15.0,
+			max_temperature: 21.0,
+			max_precipitation_probability: 10,
+			total_precipitation: 0.0,
+			max_gusts: 20.0,
+		};
+		assert_eq!(summary.verdict(), "Looks dry and mild for your event.");
+		let summary = EventSummary {
+			total_precipitation: 3.0,
+			max_gusts: 60.0,
+			..summary
+		};
+		assert_eq!(
+			summary.verdict(),
+			"Rain is likely, and it'll be mild and windy for your event."
+		);
+	}
+}
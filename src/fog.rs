@@ -0,0 +1,430 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb},
+	text_box::TextSegment,
+	util::{make_png, next_multiple},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	layout::{create_layout_option, header_height},
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	palette::create_theme_option,
+	render::{check_forecast_data, create_scale_option, Style},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	units::UnitSystem,
+	util::{convert_num, ForecastTimezone},
+};
+
+/// Below this visibility in metres, fog is dense (the threshold used for dense fog advisories).
+const DENSE_FOG_VISIBILITY: f32 = 200.0;
+/// Below this visibility in metres, it is fog by the meteorological definition.
+const FOG_VISIBILITY: f32 = 1_000.0;
+/// Below this visibility in metres, it is mist or haze.
+const HAZE_VISIBILITY: f32 = 5_000.0;
+/// At or below this temperature–dew point spread in °C the air is close to saturated, and fog can form or thicken quickly.
+const SATURATED_SPREAD: f32 = 1.0;
+/// At or below this spread in °C, fog is possible, mostly overnight and around dawn.
+const NEAR_SATURATED_SPREAD: f32 = 2.5;
+
+#[derive(Debug, Deserialize)]
+struct CurrentFog {
+	temperature_2m: f32,
+	dew_point_2m: f32,
+	/// Not every weather model provides visibility.
+	visibility: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FogResult {
+	current: CurrentFog,
+}
+
+impl FogResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[
+				("current", "temperature_2m"),
+				("current", "dew_point_2m"),
+				("current", "visibility"),
+			])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// How many hours the visibility graph covers.
+const VISIBILITY_HOURS: u32 = 48;
+/// Up to this many kilometres or miles, the visibility graph is drawn on a detailed scale, so fog stands out.
+const SHORT_VISIBILITY: i32 = 5;
+
+#[derive(Debug, Deserialize)]
+struct VisibilityHourly {
+	time: Vec<i64>,
+	/// In metres.
+	visibility: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs
+#[derive(Debug, Deserialize)]
+struct VisibilityResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: VisibilityHourly,
+}
+
+impl VisibilityResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[("hourly", "visibility")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", VISIBILITY_HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl VisibilityHourly {
+	/// The visibility of every hour in kilometres or miles, if every hour has one.
+	fn distances(&self, units: UnitSystem) -> Option<Vec<f32>> {
+		self.visibility
+			.iter()
+			.map(|visibility| visibility.map(|visibility| units.visibility_from_metres(visibility)))
+			.collect()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FogCondition {
+	Clear,
+	Hazy,
+	Foggy,
+	DenseFog,
+}
+
+impl FogCondition {
+	/// Classify by visibility, bumping clear air up to hazy when it is close to saturated, since fog is then likely to form soon.
+	fn classify(visibility: f32, spread: f32) -> Self {
+		let by_visibility = if visibility < DENSE_FOG_VISIBILITY {
+			Self::DenseFog
+		} else if visibility < FOG_VISIBILITY {
+			Self::Foggy
+		} else if visibility < HAZE_VISIBILITY {
+			Self::Hazy
+		} else {
+			Self::Clear
+		};
+		if spread <= SATURATED_SPREAD {
+			by_visibility.max(Self::Hazy)
+		} else {
+			by_visibility
+		}
+	}
+	fn name(self) -> &'static str {
+		match self {
+			Self::Clear => "Clear",
+			Self::Hazy => "Hazy",
+			Self::Foggy => "Foggy",
+			Self::DenseFog => "Dense fog",
+		}
+	}
+	fn driving_advice(self) -> &'static str {
+		match self {
+			Self::Clear => "No visibility concerns for driving.",
+			Self::Hazy => "Visibility is somewhat reduced, and fog could form. Keep some extra distance.",
+			Self::Foggy => "Use dipped headlights, slow down and keep a large following distance.",
+			Self::DenseFog => "Avoid driving if you can. Otherwise use fog lights, drive slowly and don't overtake.",
+		}
+	}
+}
+
+/// A rough fog risk from the temperature–dew point spread alone, for when there is no visibility data.
+fn fog_risk_from_spread(spread: f32) -> &'static str {
+	if spread <= SATURATED_SPREAD {
+		"fog is likely"
+	} else if spread <= NEAR_SATURATED_SPREAD {
+		"fog is possible"
+	} else {
+		"fog is unlikely"
+	}
+}
+
+pub async fn handle_visibility_now(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let current = FogResult::get(location.coordinates(), &client)
+		.await?
+		.current;
+	let spread = (current.temperature_2m - current.dew_point_2m).max(0.0);
+	let message = match current.visibility {
+		Some(visibility) => {
+			let condition = FogCondition::classify(visibility, spread);
+			format!(
+				"{}: visibility {}, temperature–dew point spread {:.1}°C. {}",
+				condition.name(),
+				format_visibility(visibility),
+				spread,
+				condition.driving_advice()
+			)
+		}
+		None => format!(
+			"Visibility data isn't available for this location. The temperature–dew point spread is {:.1}°C, so {}.",
+			spread,
+			fog_risk_from_spread(spread)
+		),
+	};
+	interaction.public_reply(&context.http, message).await?;
+	Ok(())
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_visibility(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let style = Style::from_interaction(interaction, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = VisibilityResult::get(location.coordinates(), &client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	let place = location.short_description();
+	let timezone = style.timezone(ForecastTimezone::new(
+		Some(&result.timezone),
+		result.utc_offset_seconds,
+	));
+	let image = visibility_graph(result, &place, style, font, header_font)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!(
+					"Visibility forecast for {place}. Times are in {timezone}."
+				))
+				.add_file(CreateAttachment::bytes(image, "visibility.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Pixels per unit of distance before scaling, the marks, and the multiple to round the top of the axis up to, by the longest visibility on the graph in hundredths of a kilometre or mile. Short visibilities get a more detailed scale.
+fn visibility_scale(max: i32) -> (u32, MarkIntervals, i32) {
+	if max <= SHORT_VISIBILITY * 100 {
+		(30, MarkIntervals::new(1, 1), 1)
+	} else {
+		(8, MarkIntervals::new(1, 5), 5)
+	}
+}
+
+/// Draw the visibility over the next two days as a line, in kilometres or miles.
+fn visibility_graph(
+	result: VisibilityResult,
+	place: &str,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let Some(visibilities) = result.hourly.distances(style.units) else {
+		return Err(Error::friendly(
+			"Visibility data isn't available for this location",
+		));
+	};
+	let palette = style.theme.palette();
+	let timezone = style.timezone(ForecastTimezone::new(
+		Some(&result.timezone),
+		result.utc_offset_seconds,
+	));
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let visibilities: Vec<_> = visibilities.into_iter().map(convert_num).collect();
+
+	let max = visibilities.iter().copied().max().unwrap_or(0);
+	let (vertical_spacing, vertical_intervals, multiple) = visibility_scale(max);
+	// At least one unit high, so dense fog all along still gets a chart with some height.
+	let chart_range = Range::new(0, next_multiple(max, multiple).max(100));
+
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	});
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: vertical_spacing,
+	});
+	let label = style.layout.header(
+		&[
+			TextSegment::new("Visibility", Rgb(palette.visibility)),
+			TextSegment::white(&format!(
+				" in {place}, in {}",
+				style.units.visibility_label()
+			)),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		(times.len() - 1) as u32 * spacing.horizontal,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		chart_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals,
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: chart_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.visibility),
+		data: visibilities.into_iter(),
+		max: chart_range.end(),
+	});
+	Ok(make_png(chart.into_canvas()))
+}
+
+fn format_visibility(metres: f32) -> String {
+	if metres < 1_000.0 {
+		format!("{:.0} m", metres)
+	} else {
+		format!("{:.1} km", metres / 1_000.0)
+	}
+}
+
+pub fn create_visibility_now() -> CreateCommand {
+	i18n::command("visibility_now", "Whether it's foggy right now")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to check the visibility of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+pub fn create_visibility() -> CreateCommand {
+	i18n::command(
+		"visibility",
+		"Visibility for the next two days, for spotting fog and haze",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the visibility forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_theme_option())
+	.add_option(create_layout_option())
+	.add_option(create_scale_option())
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn classify_by_visibility() {
+		assert_eq!(FogCondition::classify(150.0, 0.5), FogCondition::DenseFog);
+		assert_eq!(FogCondition::classify(600.0, 0.5), FogCondition::Foggy);
+		assert_eq!(FogCondition::classify(3_000.0, 4.0), FogCondition::Hazy);
+		assert_eq!(FogCondition::classify(20_000.0, 8.0), FogCondition::Clear);
+	}
+	#[test]
+	fn saturated_air_is_at_least_hazy() {
+		assert_eq!(FogCondition::classify(20_000.0, 0.5), FogCondition::Hazy);
+		assert_eq!(FogCondition::classify(20_000.0, 1.5), FogCondition::Clear);
+	}
+	#[test]
+	fn visibility_in_chosen_units() {
+		let hourly = VisibilityHourly {
+			time: vec![0, 3600],
+			visibility: vec![Some(24_140.0), Some(80.0)],
+		};
+		assert_eq!(
+			hourly.distances(UnitSystem::Metric),
+			Some(vec![24.14, 0.08])
+		);
+		let miles = hourly.distances(UnitSystem::Imperial).unwrap();
+		assert!((miles[0] - 15.0).abs() < 1e-2);
+		let patchy = VisibilityHourly {
+			visibility: vec![Some(24_140.0), None],
+			..hourly
+		};
+		assert_eq!(patchy.distances(UnitSystem::Metric), None);
+	}
+	#[test]
+	fn short_visibility_gets_a_detailed_scale() {
+		assert_eq!(visibility_scale(80).2, 1);
+		assert_eq!(visibility_scale(500).2, 1);
+		assert_eq!(visibility_scale(2_414).2, 5);
+	}
+	#[test]
+	fn fog_risk_without_visibility() {
+		assert_eq!(fog_risk_from_spread(0.3), "fog is likely");
+		assert_eq!(fog_risk_from_spread(2.0), "fog is possible");
+		assert_eq!(fog_risk_from_spread(6.0), "fog is unlikely");
+	}
+}
@@ -0,0 +1,10 @@
+pub mod air_quality;
+pub mod daily;
+pub mod history;
+pub mod hourly;
+pub mod marine;
+pub mod pressure;
+pub mod seasonal;
+pub mod snow;
+pub mod soil;
+pub mod temp_histogram;
@@ -0,0 +1,552 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	layout::text_chart,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	util::{capitalize, convert_num, ForecastTimezone},
+};
+
+const PM10: Rgb = Rgb([214, 158, 46]);
+const PM2_5: Rgb = Rgb([255, 0, 0]);
+const OZONE: Rgb = Rgb([0, 148, 255]);
+const NITROGEN_DIOXIDE: Rgb = Rgb([178, 102, 255]);
+const AQI: Rgb = Rgb([0, 200, 83]);
+const ALDER: Rgb = Rgb([214, 158, 46]);
+const BIRCH: Rgb = Rgb([255, 255, 33]);
+const GRASS: Rgb = Rgb([0, 200, 83]);
+const MUGWORT: Rgb = Rgb([178, 102, 255]);
+const OLIVE: Rgb = Rgb([118, 215, 234]);
+const RAGWEED: Rgb = Rgb([255, 0, 0]);
+
+/// How far ahead to chart, matching the default of the hourly forecast.
+const HOURS: u32 = 48;
+
+#[derive(Debug, Deserialize)]
+struct AirQualityHourly {
+	time: Vec<i64>,
+	pm10: Vec<Option<f32>>,
+	pm2_5: Vec<Option<f32>>,
+	ozone: Vec<Option<f32>>,
+	nitrogen_dioxide: Vec<Option<f32>>,
+	european_aqi: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs/air-quality-api
+#[derive(Debug, Deserialize)]
+struct AirQualityResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: AirQualityHourly,
+}
+
+impl AirQualityResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://air-quality-api.open-meteo.com/v1/air-quality")
+			.query(&[("hourly", "pm10")])
+			.query(&[("hourly", "pm2_5")])
+			.query(&[("hourly", "ozone")])
+			.query(&[("hourly", "nitrogen_dioxide")])
+			.query(&[("hourly", "european_aqi")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl AirQualityHourly {
+	/// How many hours from the start have every reading, since the models covering some pollutants stop earlier than others.
+	fn complete_hours(&self) -> usize {
+		let series = [
+			&self.pm10,
+			&self.pm2_5,
+			&self.ozone,
+			&self.nitrogen_dioxide,
+			&self.european_aqi,
+		];
+		(0..self.time.len())
+			.take_while(|&hour| {
+				series
+					.iter()
+					.all(|values| values.get(hour).is_some_and(Option::is_some))
+			})
+			.count()
+	}
+}
+
+/// In grains per cubic metre. The pollen forecast only covers Europe, and elsewhere every value is null.
+#[derive(Debug, Deserialize)]
+struct PollenHourly {
+	time: Vec<i64>,
+	alder_pollen: Vec<Option<f32>>,
+	birch_pollen: Vec<Option<f32>>,
+	grass_pollen: Vec<Option<f32>>,
+	mugwort_pollen: Vec<Option<f32>>,
+	olive_pollen: Vec<Option<f32>>,
+	ragweed_pollen: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PollenResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: PollenHourly,
+}
+
+impl PollenResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://air-quality-api.open-meteo.com/v1/air-quality")
+			.query(&[("hourly", "alder_pollen")])
+			.query(&[("hourly", "birch_pollen")])
+			.query(&[("hourly", "grass_pollen")])
+			.query(&[("hourly", "mugwort_pollen")])
+			.query(&[("hourly", "olive_pollen")])
+			.query(&[("hourly", "ragweed_pollen")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl PollenHourly {
+	/// The name, colour and readings of every species with any readings at all.
+	fn species(&self) -> Vec<(&'static str, Rgb, &[Option<f32>])> {
+		[
+			("alder", ALDER, &self.alder_pollen),
+			("birch", BIRCH, &self.birch_pollen),
+			("grass", GRASS, &self.grass_pollen),
+			("mugwort", MUGWORT, &self.mugwort_pollen),
+			("olive", OLIVE, &self.olive_pollen),
+			("ragweed", RAGWEED, &self.ragweed_pollen),
+		]
+		.into_iter()
+		.filter(|(_, _, values)| values.iter().any(Option::is_some))
+		.map(|(name, colour, values)| (name, colour, values.as_slice()))
+		.collect()
+	}
+}
+
+/// What to divide pollen counts by to keep the chart at most a hundred units high, and the unit that makes.
+fn pollen_scale(max: f32) -> (f32, &'static str) {
+	if max <= 100.0 {
+		(1.0, "grains/m³")
+	} else if max <= 1000.0 {
+		(10.0, "tens of grains/m³")
+	} else {
+		(100.0, "hundreds of grains/m³")
+	}
+}
+
+const HEADER_SIZE: PxScale = PxScale { x: 22.0, y: 22.0 };
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_air_quality(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = AirQualityResult::get(location.coordinates(), &client).await?;
+	let image = draw_air_quality(result, &location.short_description(), font, header_font)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.add_file(CreateAttachment::bytes(image, "air_quality.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the particulate matter, the gases and the overall index each as a panel of lines, under a header naming the place.
+fn draw_air_quality(
+	result: AirQualityResult,
+	place: &str,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let hours = result.hourly.complete_hours();
+	if hours < 2 {
+		return Err(Error::friendly(
+			"No air quality forecast is available for this location.",
+		));
+	}
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result.hourly.time[..hours]
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let readings = |values: &[Option<f32>]| -> Vec<i32> {
+		values[..hours]
+			.iter()
+			.flatten()
+			.copied()
+			.map(convert_num)
+			.collect()
+	};
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	};
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 1,
+	};
+
+	let header = text_chart(
+		&[TextSegment::white(&format!("Air quality in {place}"))],
+		header_font,
+		HEADER_SIZE,
+		hours,
+		spacing,
+		padding,
+	);
+	let particulates = line_panel(
+		&[
+			TextSegment::new("PM10", PM10),
+			TextSegment::white(" and "),
+			TextSegment::new("PM2.5", PM2_5),
+			TextSegment::white(" (μg/m³)"),
+		],
+		&[
+			(PM10, readings(&result.hourly.pm10)),
+			(PM2_5, readings(&result.hourly.pm2_5)),
+		],
+		&times,
+		spacing,
+		padding,
+		font,
+		header_font,
+	);
+	let gases = line_panel(
+		&[
+			TextSegment::new("Ozone", OZONE),
+			TextSegment::white(" and "),
+			TextSegment::new("nitrogen dioxide", NITROGEN_DIOXIDE),
+			TextSegment::white(" (μg/m³)"),
+		],
+		&[
+			(OZONE, readings(&result.hourly.ozone)),
+			(NITROGEN_DIOXIDE, readings(&result.hourly.nitrogen_dioxide)),
+		],
+		&times,
+		spacing,
+		padding,
+		font,
+		header_font,
+	);
+	let index = line_panel(
+		&[
+			TextSegment::new("European AQI", AQI),
+			TextSegment::white(" (above 60 is poor, above 100 extremely poor)"),
+		],
+		&[(AQI, readings(&result.hourly.european_aqi))],
+		&times,
+		spacing,
+		padding,
+		font,
+		header_font,
+	);
+
+	Ok(make_png(composite(&[
+		header.into_canvas(),
+		particulates.into_canvas(),
+		gases.into_canvas(),
+		index.into_canvas(),
+	])))
+}
+
+/// A panel with a line for every series, on a scale from zero up to the highest reading of any of them.
+fn line_panel(
+	label: &[TextSegment],
+	series: &[(Rgb, Vec<i32>)],
+	times: &[u8],
+	spacing: Spacing,
+	padding: Padding,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let max = series
+		.iter()
+		.flat_map(|(_, values)| values)
+		.copied()
+		.max()
+		.unwrap_or(0);
+	let range = Range::new(0, next_multiple(max, 10));
+
+	let label = TextBox::new(
+		label,
+		header_font.clone(),
+		LABEL_SIZE,
+		(times.len() - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(10, 20),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	for (colour, values) in series {
+		chart.draw(Line {
+			colour: *colour,
+			data: values.iter().copied(),
+			max: range.end(),
+		});
+	}
+	chart
+}
+
+pub async fn handle_pollen(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = PollenResult::get(location.coordinates(), &client).await?;
+	let image = draw_pollen(result, &location.short_description(), font, header_font)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.add_file(CreateAttachment::bytes(image, "pollen.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw a line for every pollen species with readings, under a header naming the place. Missing hours are drawn as zero.
+fn draw_pollen(
+	result: PollenResult,
+	place: &str,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let species = result.hourly.species();
+	let hours = result.hourly.time.len();
+	if species.is_empty() || hours < 2 {
+		return Err(Error::friendly(
+			"No pollen forecast is available for this location. Pollen is only forecast for Europe.",
+		));
+	}
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let max = species
+		.iter()
+		.flat_map(|(_, _, values)| values.iter().flatten())
+		.copied()
+		.fold(0.0, f32::max);
+	let (divisor, unit) = pollen_scale(max);
+	let series: Vec<_> = species
+		.iter()
+		.map(|&(_, colour, values)| {
+			let values = values
+				.iter()
+				.map(|value| convert_num(value.unwrap_or(0.0) / divisor))
+				.collect();
+			(colour, values)
+		})
+		.collect();
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	};
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 2,
+	};
+	let unit = format!(" ({unit})");
+	let first_name = capitalize(species[0].0);
+	let label: Vec<_> = species
+		.iter()
+		.enumerate()
+		.flat_map(|(index, &(name, colour, _))| {
+			let (separator, name) = match index {
+				0 => (None, first_name.as_str()),
+				_ => (Some(TextSegment::white(", ")), name),
+			};
+			separator
+				.into_iter()
+				.chain([TextSegment::new(name, colour)])
+		})
+		.chain([TextSegment::white(&unit)])
+		.collect();
+
+	let header = text_chart(
+		&[TextSegment::white(&format!("Pollen in {place}"))],
+		header_font,
+		HEADER_SIZE,
+		hours,
+		spacing,
+		padding,
+	);
+	let pollen = line_panel(&label, &series, &times, spacing, padding, font, header_font);
+	Ok(make_png(composite(&[
+		header.into_canvas(),
+		pollen.into_canvas(),
+	])))
+}
+
+pub fn create_air_quality() -> CreateCommand {
+	i18n::command("air_quality", "Air quality forecast for the next two days")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the air quality forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_private_option())
+}
+
+pub fn create_pollen() -> CreateCommand {
+	i18n::command("pollen", "Pollen forecast for the next two days, in Europe")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the pollen forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn complete_hours_stop_at_first_gap() {
+		let hourly = AirQualityHourly {
+			time: vec![0, 3600, 7200, 10800],
+			pm10: vec![Some(10.0), Some(12.0), Some(11.0), Some(9.0)],
+			pm2_5: vec![Some(5.0), Some(6.0), Some(6.0), Some(4.0)],
+			ozone: vec![Some(60.0), Some(65.0), None, Some(70.0)],
+			nitrogen_dioxide: vec![Some(20.0), Some(18.0), Some(17.0), Some(15.0)],
+			european_aqi: vec![Some(30.0), Some(32.0), Some(31.0), Some(29.0)],
+		};
+		assert_eq!(hourly.complete_hours(), 2);
+	}
+	#[test]
+	fn complete_hours_with_short_series() {
+		let hourly = AirQualityHourly {
+			time: vec![0, 3600],
+			pm10: vec![Some(10.0), Some(12.0)],
+			pm2_5: vec![Some(5.0), Some(6.0)],
+			ozone: vec![Some(60.0), Some(65.0)],
+			nitrogen_dioxide: vec![Some(20.0), Some(18.0)],
+			european_aqi: vec![Some(30.0)],
+		};
+		assert_eq!(hourly.complete_hours(), 1);
+	}
+	#[test]
+	fn pollen_species_without_readings_left_out() {
+		let hourly = PollenHourly {
+			time: vec![0, 3600],
+			alder_pollen: vec![None, None],
+			birch_pollen: vec![Some(120.0), Some(80.0)],
+			grass_pollen: vec![Some(3.0), None],
+			mugwort_pollen: vec![None, None],
+			olive_pollen: vec![None, None],
+			ragweed_pollen: vec![None, None],
+		};
+		let names: Vec<_> = hourly
+			.species()
+			.into_iter()
+			.map(|(name, _, _)| name)
+			.collect();
+		assert_eq!(names, ["birch", "grass"]);
+	}
+	#[test]
+	fn pollen_scaled_to_fit() {
+		assert_eq!(pollen_scale(0.0), (1.0, "grains/m³"));
+		assert_eq!(pollen_scale(100.0), (1.0, "grains/m³"));
+		assert_eq!(pollen_scale(450.0), (10.0, "tens of grains/m³"));
+		assert_eq!(pollen_scale(3000.0), (100.0, "hundreds of grains/m³"));
+	}
+}
@@ -0,0 +1,1057 @@
+use ab_glyph::{FontRef, PxScale};
+use chrono::Datelike;
+use graph::{
+	common_types::{GradientPoint, MultiPointGradient, Range},
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, GradientBars, HorizontalLines, Line, Rgb, SolidBars},
+	text_box::TextSegment,
+	util::{composite, make_png, next_multiple, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	csv::{create_data_option, data_option, to_csv, Column},
+	error::Error,
+	i18n,
+	layout::{create_layout_option, header_height},
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, complete_series, RequestBuilderExt},
+	palette::{create_theme_option, UV_PROTECTION_THRESHOLD},
+	refresh::refresh_button,
+	render::{
+		check_forecast_data, create_horizon_option, create_scale_option, horizon_option,
+		ForecastKind, Style,
+	},
+	render_archive::archive_render,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	units::UnitSystem,
+	util::{convert_num, convert_tenths, notable_gust_bars, ForecastTimezone},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DailyWeather {
+	time: Vec<i64>,
+	/// Empty if Open-Meteo has no complete series for it, as are the other variables read with `complete_series`. The charts of empty variables are left out.
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m_min: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	apparent_temperature_min: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	apparent_temperature_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation_sum: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation_probability_max: Vec<u8>,
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_speed_10m_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_gusts_10m_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	uv_index_max: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	uv_index_clear_sky_max: Vec<f32>,
+	/// Missing from renders archived before humidity was requested.
+	#[serde(default, deserialize_with = "complete_series")]
+	relative_humidity_2m_mean: Vec<f32>,
+}
+
+/// Open-Meteo only gives the freezing level by the hour, so the daily lowest and highest are worked out from these.
+#[derive(Debug, Deserialize, Serialize)]
+struct FreezingLevelHourly {
+	time: Vec<i64>,
+	/// In metres. Missing where no model covers it.
+	freezing_level_height: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DailyResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	/// Missing from renders archived before the timezone was kept.
+	#[serde(default)]
+	timezone: Option<String>,
+	daily: DailyWeather,
+	/// Missing from renders archived before the freezing level was requested.
+	#[serde(default)]
+	hourly: Option<FreezingLevelHourly>,
+}
+
+impl DailyResult {
+	async fn get(
+		coordinates: Coordinates,
+		days: u32,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[
+				("daily", "temperature_2m_min"),
+				("daily", "temperature_2m_max"),
+				("daily", "apparent_temperature_min"),
+				("daily", "apparent_temperature_max"),
+				("daily", "precipitation_sum"),
+				("daily", "precipitation_probability_max"),
+				("daily", "wind_speed_10m_max"),
+				("daily", "wind_gusts_10m_max"),
+				("daily", "uv_index_max"),
+				("daily", "uv_index_clear_sky_max"),
+				("daily", "relative_humidity_2m_mean"),
+				("hourly", "freezing_level_height"),
+				("timeformat", "unixtime"),
+				("timezone", "auto"),
+			])
+			.query(&[("wind_speed_unit", units.chart_wind_speed_unit())])
+			.query(&units.query())
+			.query(&[("forecast_days", days)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl DailyResult {
+	fn timezone(&self) -> ForecastTimezone {
+		ForecastTimezone::new(self.timezone.as_deref(), self.utc_offset_seconds)
+	}
+	/// The forecast data as CSV, with one row per day.
+	fn to_csv(&self, units: UnitSystem) -> String {
+		let daily = &self.daily;
+		let temperature = units.temperature_symbol();
+		let wind_speed = units.chart_wind_speed_symbol();
+		to_csv(
+			&daily.time,
+			self.timezone(),
+			"%Y-%m-%d",
+			&[
+				Column::new(
+					format!("temperature_2m_min ({temperature})"),
+					&daily.temperature_2m_min,
+				),
+				Column::new(
+					format!("temperature_2m_max ({temperature})"),
+					&daily.temperature_2m_max,
+				),
+				Column::new(
+					format!("apparent_temperature_min ({temperature})"),
+					&daily.apparent_temperature_min,
+				),
+				Column::new(
+					format!("apparent_temperature_max ({temperature})"),
+					&daily.apparent_temperature_max,
+				),
+				Column::new(
+					format!("precipitation_sum ({})", units.precipitation_symbol()),
+					&daily.precipitation_sum,
+				),
+				Column::new(
+					"precipitation_probability_max (%)",
+					&daily.precipitation_probability_max,
+				),
+				Column::new(
+					format!("wind_speed_10m_max ({wind_speed})"),
+					&daily.wind_speed_10m_max,
+				),
+				Column::new(
+					format!("wind_gusts_10m_max ({wind_speed})"),
+					&daily.wind_gusts_10m_max,
+				),
+				Column::new("uv_index_max", &daily.uv_index_max),
+				Column::new("uv_index_clear_sky_max", &daily.uv_index_clear_sky_max),
+				Column::new(
+					"relative_humidity_2m_mean (%)",
+					&daily.relative_humidity_2m_mean,
+				),
+			],
+		)
+	}
+}
+
+impl FreezingLevelHourly {
+	/// The lowest and highest freezing level of every local day, in metres. `None` if any day has no freezing level at all, as in tropical lowlands where it's often missing.
+	fn daily_range(&self, timezone: ForecastTimezone) -> Option<Vec<(f32, f32)>> {
+		self.time
+			.iter()
+			.zip(&self.freezing_level_height)
+			.chunk_by(|(&time, _)| day_from_timestamp(time, timezone))
+			.into_iter()
+			.map(|(_, hours)| {
+				hours
+					.filter_map(|(_, &height)| height)
+					.minmax_by(f32::total_cmp)
+					.into_option()
+			})
+			.collect()
+	}
+}
+
+/// Get the day of the month (from 1 to 31) for a given Unix timestamp in a timezone.
+pub fn day_from_timestamp(timestamp: i64, timezone: ForecastTimezone) -> u8 {
+	timezone.local(timestamp).day() as u8
+}
+
+const DEFAULT_FORECAST_DAYS: u32 = 7;
+/// The furthest ahead Open-Meteo forecasts.
+const MAX_FORECAST_DAYS: u32 = 16;
+
+const LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_daily(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let style = Style {
+		horizon: horizon_option(interaction, "days", MAX_FORECAST_DAYS)?,
+		..Style::from_interaction(interaction, database).await?
+	};
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+	let result = fetch_daily(location.coordinates(), style, &client, database).await?;
+	let data = data_option(interaction).then(|| result.to_csv(style.units));
+	let image = draw_daily(result, style, font, header_font);
+
+	let mut message = CreateInteractionResponseFollowup::new()
+		.ephemeral(private)
+		.content(format!("Daily forecast for {location}."))
+		.add_file(CreateAttachment::bytes(image, "daily.png"))
+		.components(
+			refresh_button(ForecastKind::Daily, style, location.coordinates())
+				.into_iter()
+				.collect(),
+		);
+	if let Some(data) = data {
+		message = message.add_file(CreateAttachment::bytes(data, "daily.csv"));
+	}
+	interaction.create_followup(context, message).await?;
+	Ok(())
+}
+
+/// Fetch the daily forecast for the coordinates and draw it as a PNG.
+pub async fn render_daily(
+	coordinates: Coordinates,
+	style: Style,
+	client: &Client,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let result = fetch_daily(coordinates, style, client, database).await?;
+	Ok(draw_daily(result, style, font, header_font))
+}
+
+/// Fetch the daily forecast for the coordinates, archiving it if the archive is enabled.
+async fn fetch_daily(
+	coordinates: Coordinates,
+	style: Style,
+	client: &Client,
+	database: &Pool<Sqlite>,
+) -> Result<DailyResult, Error> {
+	let days = style.horizon.unwrap_or(DEFAULT_FORECAST_DAYS);
+	let result = DailyResult::get(coordinates, days, style.units, client).await?;
+	check_forecast_data(&result.daily.time)?;
+	archive_render(database, ForecastKind::Daily, style, coordinates, &result).await;
+	Ok(result)
+}
+
+/// Draw the daily forecast again from archived data.
+pub fn draw_archived_daily(
+	input: &str,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	Ok(draw_daily(
+		serde_json::from_str(input)?,
+		style,
+		font,
+		header_font,
+	))
+}
+
+fn draw_daily(
+	result: DailyResult,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let palette = style.theme.palette();
+	let layout = style.layout;
+	let units = style.units;
+	let timezone = result.timezone();
+	let times = result
+		.daily
+		.time
+		.into_iter()
+		.map(|time| day_from_timestamp(time, timezone))
+		.collect::<Vec<_>>();
+
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 9,
+	});
+
+	let temp_image = (result.daily.temperature_2m_min.len() == times.len()
+		&& result.daily.temperature_2m_max.len() == times.len())
+	.then(|| {
+		let (&min, &max) = result
+			.daily
+			.apparent_temperature_max
+			.iter()
+			.chain(&result.daily.apparent_temperature_min)
+			.chain(&result.daily.temperature_2m_max)
+			.chain(&result.daily.temperature_2m_min)
+			.minmax()
+			.into_option()
+			.unwrap_or((&0.0, &0.0));
+		let temp_range = Range::new(convert_num(min), convert_num(max));
+		let chart_temp_range =
+			previous_and_next_multiple(Range::new(temp_range.start(), temp_range.end()), 4);
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 25,
+			vertical: 3,
+		});
+		let label = layout.header(
+			&[
+				TextSegment::new("Minimum", Rgb(palette.low_temperature)),
+				TextSegment::white(", "),
+				TextSegment::new("maximum", Rgb(palette.temperature)),
+				TextSegment::white(" and "),
+				TextSegment::new(
+					"apparent minimum and maximum",
+					Rgb(palette.daily_apparent_temperature),
+				),
+				TextSegment::white(&format!(" temperatures ({})", units.temperature_symbol())),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			(times.len() as u32 - 1) * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len(),
+			chart_temp_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				left: padding.left + spacing.horizontal / 2,
+				right: padding.right + spacing.horizontal / 2,
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(2, 4),
+			horizontal_intervals: MarkIntervals::new(1, 1),
+			vertical_label_range: chart_temp_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.daily_apparent_temperature),
+			data: result
+				.daily
+				.apparent_temperature_min
+				.into_iter()
+				.map(convert_num),
+			max: chart_temp_range.end(),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.daily_apparent_temperature),
+			data: result
+				.daily
+				.apparent_temperature_max
+				.into_iter()
+				.map(convert_num),
+			max: chart_temp_range.end(),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.low_temperature),
+			data: result.daily.temperature_2m_min.into_iter().map(convert_num),
+			max: chart_temp_range.end(),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.temperature),
+			data: result.daily.temperature_2m_max.into_iter().map(convert_num),
+			max: chart_temp_range.end(),
+		});
+		chart.into_canvas()
+	});
+
+	let freezing_levels = result
+		.hourly
+		.as_ref()
+		.and_then(|hourly| hourly.daily_range(timezone))
+		.filter(|levels| levels.len() == times.len());
+	let freezing_level_image = freezing_levels.map(|levels| {
+		// In hundredths of kilometres, to have the axis marked in whole kilometres.
+		let lowest: Vec<_> = levels
+			.iter()
+			.map(|&(low, _)| convert_num(low / 1000.0))
+			.collect();
+		let highest: Vec<_> = levels
+			.iter()
+			.map(|&(_, high)| convert_num(high / 1000.0))
+			.collect();
+		let (&min, &max) = lowest
+			.iter()
+			.chain(&highest)
+			.minmax()
+			.into_option()
+			.unwrap_or((&0, &0));
+		let chart_range = previous_and_next_multiple(Range::new(min, max), 1);
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 25,
+			vertical: 30,
+		});
+		let label = layout.header(
+			&[
+				TextSegment::new("Lowest", Rgb(palette.low_temperature)),
+				TextSegment::white(" and "),
+				TextSegment::new("highest", Rgb(palette.temperature)),
+				TextSegment::white(" freezing level (km)"),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			(times.len() as u32 - 1) * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len(),
+			chart_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				left: padding.left + spacing.horizontal / 2,
+				right: padding.right + spacing.horizontal / 2,
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 1),
+			horizontal_intervals: MarkIntervals::new(1, 1),
+			vertical_label_range: chart_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.low_temperature),
+			data: lowest.into_iter(),
+			max: chart_range.end(),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.temperature),
+			data: highest.into_iter(),
+			max: chart_range.end(),
+		});
+		chart.into_canvas()
+	});
+
+	let humidity_image = (result.daily.relative_humidity_2m_mean.len() == times.len()).then(|| {
+		humidity_graph(
+			&result.daily.relative_humidity_2m_mean,
+			&times,
+			style,
+			padding,
+			font,
+			header_font,
+		)
+		.into_canvas()
+	});
+
+	let precipitation_image = (result.daily.precipitation_sum.len() == times.len()).then(|| {
+		let max_precipitation = result
+			.daily
+			.precipitation_sum
+			.iter()
+			.fold(0.0f32, |acc, num| acc.max(*num));
+		let precipitation_range = Range::new(
+			0,
+			next_multiple(convert_num(units.chart_precipitation(max_precipitation)), 5),
+		);
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 25,
+			vertical: 1,
+		});
+		let label = layout.header(
+			&[
+				TextSegment::white("Total "),
+				TextSegment::new("precipitation", Rgb(palette.precipitation)),
+				TextSegment::white(&format!(" ({})", units.chart_precipitation_label())),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			times.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len() + 1,
+			precipitation_range.end() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(25, 25),
+			horizontal_intervals: MarkIntervals::new(1, 1),
+			vertical_label_range: precipitation_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.precipitation),
+			data: result
+				.daily
+				.precipitation_sum
+				.iter()
+				.map(|&amount| convert_num(units.chart_precipitation(amount))),
+		});
+		chart.into_canvas()
+	});
+
+	let wind_image = (result.daily.wind_speed_10m_max.len() == times.len()).then(|| {
+		let max_wind = result
+			.daily
+			.wind_gusts_10m_max
+			.iter()
+			.chain(&result.daily.wind_speed_10m_max)
+			.fold(0.0f32, |acc, num| acc.max(*num));
+		let wind_range = Range::new(0, next_multiple(convert_num(max_wind), 5));
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 25,
+			vertical: units.chart_wind_speed_spacing(),
+		});
+		let wind_height = |speed: f32| {
+			padding.below
+				+ (spacing.vertical as f32 * units.wind_speed_from_metres_per_second(speed)) as u32
+		};
+		let label = layout.header(
+			&[
+				TextSegment::white("Maximum "),
+				TextSegment::new("wind", Rgb(palette.wind_gradient[0])),
+				TextSegment::white(" and "),
+				TextSegment::new("gust", Rgb(palette.gust_gradient[0])),
+				TextSegment::white(&format!(" speeds ({}), ", units.chart_wind_speed_symbol())),
+				TextSegment::new("strong gusts", Rgb(palette.strong_gust)),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			times.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len() + 1,
+			wind_range.end() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(5, 5),
+			horizontal_intervals: MarkIntervals::new(1, 1),
+			vertical_label_range: wind_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(GradientBars {
+			gradient: MultiPointGradient::new(vec![
+				GradientPoint::from_rgb(padding.below, palette.gust_gradient[0]),
+				GradientPoint::from_rgb(wind_height(7.0), palette.gust_gradient[1]),
+				GradientPoint::from_rgb(wind_height(14.0), palette.gust_gradient[2]),
+				GradientPoint::from_rgb(wind_height(21.0), palette.gust_gradient[3]),
+			]),
+			data: result
+				.daily
+				.wind_gusts_10m_max
+				.iter()
+				.copied()
+				.map(convert_num),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.strong_gust),
+			data: notable_gust_bars(
+				units,
+				&result.daily.wind_speed_10m_max,
+				&result.daily.wind_gusts_10m_max,
+			)
+			.into_iter(),
+		});
+		chart.draw(GradientBars {
+			gradient: MultiPointGradient::new(vec![
+				GradientPoint::from_rgb(padding.below, palette.wind_gradient[0]),
+				GradientPoint::from_rgb(wind_height(7.0), palette.wind_gradient[1]),
+				GradientPoint::from_rgb(wind_height(14.0), palette.wind_gradient[2]),
+				GradientPoint::from_rgb(wind_height(21.0), palette.wind_gradient[3]),
+			]),
+			data: result
+				.daily
+				.wind_speed_10m_max
+				.iter()
+				.copied()
+				.map(convert_num),
+		});
+		chart.into_canvas()
+	});
+
+	let uvi_image = (!result.daily.uv_index_max.is_empty()
+		|| !result.daily.uv_index_clear_sky_max.is_empty())
+	.then(|| {
+		let max_uv = result
+			.daily
+			.uv_index_max
+			.iter()
+			.chain(&result.daily.uv_index_clear_sky_max)
+			.fold(0.0f32, |acc, num| acc.max(*num));
+		let uv_range = Range::new(0, next_multiple(convert_num(max_uv), 1));
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 25,
+			vertical: 10,
+		});
+
+		let label = layout.header(
+			&[TextSegment::white("UV index: ")]
+				.into_iter()
+				.chain(palette.uv_legend())
+				.chain([
+					TextSegment::white(" (and "),
+					TextSegment::new("clear sky UV", Rgb(palette.uv_clear_sky)),
+					TextSegment::white(", "),
+					TextSegment::new("protection needed", Rgb(palette.uv_protection)),
+					TextSegment::white(")"),
+				])
+				.collect::<Vec<_>>(),
+			header_font,
+			style.font_size(LABEL_SIZE),
+			(times.len() as u32 - 1) * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len() + 1,
+			uv_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 1),
+			horizontal_intervals: MarkIntervals::new(1, 1),
+			vertical_label_range: uv_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(HorizontalLines {
+			colour: Rgb(palette.uv_clear_sky),
+			data: result
+				.daily
+				.uv_index_clear_sky_max
+				.into_iter()
+				.map(convert_tenths),
+		});
+		chart.draw(GradientBars {
+			gradient: palette.uv_gradient(padding.below, spacing.vertical),
+			data: result.daily.uv_index_max.into_iter().map(convert_tenths),
+		});
+		// Over the bars, so it shows which hours need protection.
+		if convert_num(UV_PROTECTION_THRESHOLD) <= uv_range.end() {
+			chart.draw(HorizontalLines {
+				colour: Rgb(palette.uv_protection),
+				data: std::iter::repeat_n(convert_num(UV_PROTECTION_THRESHOLD), times.len()),
+			});
+		}
+		chart.into_canvas()
+	});
+
+	// As wide as the bar charts, whichever of them are drawn.
+	let spacing = style.spacing(Spacing {
+		horizontal: 25,
+		vertical: 10,
+	});
+	let legend = layout.legend(
+		&[
+			TextSegment::new("Minimum", Rgb(palette.low_temperature)),
+			TextSegment::white(", "),
+			TextSegment::new("maximum", Rgb(palette.temperature)),
+			TextSegment::white(", "),
+			TextSegment::new("apparent", Rgb(palette.daily_apparent_temperature)),
+			TextSegment::white(&format!(" ({}) · ", units.temperature_symbol())),
+			TextSegment::white(if freezing_level_image.is_some() {
+				"freezing level (km) · "
+			} else {
+				""
+			}),
+			TextSegment::new(
+				if humidity_image.is_some() {
+					"humidity"
+				} else {
+					""
+				},
+				Rgb(palette.dew_point),
+			),
+			TextSegment::white(if humidity_image.is_some() {
+				" (%) · "
+			} else {
+				""
+			}),
+			TextSegment::new("precipitation", Rgb(palette.precipitation)),
+			TextSegment::white(&format!(" ({}) · ", units.chart_precipitation_label())),
+			TextSegment::new("wind", Rgb(palette.wind_gradient[0])),
+			TextSegment::white(", "),
+			TextSegment::new("gusts", Rgb(palette.gust_gradient[0])),
+			TextSegment::white(&format!(" ({}) · ", units.chart_wind_speed_symbol())),
+			TextSegment::new("UV", Rgb(palette.uv_bands[0])),
+			TextSegment::white(", "),
+			TextSegment::new("clear sky UV", Rgb(palette.uv_clear_sky)),
+			TextSegment::white(", "),
+			TextSegment::new("protection needed", Rgb(palette.uv_protection)),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		times.len() + 1,
+		spacing,
+		padding,
+	);
+	let composite = composite(
+		&legend
+			.map(Chart::into_canvas)
+			.into_iter()
+			.chain(temp_image)
+			.chain(freezing_level_image)
+			.chain(humidity_image)
+			.chain(precipitation_image)
+			.chain(wind_image)
+			.chain(uvi_image)
+			.collect::<Vec<_>>(),
+	);
+	make_png(composite)
+}
+
+/// Draw the mean relative humidity of each day as a line on a scale from 0 to 100 %.
+fn humidity_graph(
+	humidities: &[f32],
+	times: &[u8],
+	style: Style,
+	padding: Padding,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let range = Range::new(0, 100 * 100);
+	let spacing = style.spacing(Spacing {
+		horizontal: 25,
+		vertical: 1,
+	});
+	let label = style.layout.header(
+		&[
+			TextSegment::white("Mean relative "),
+			TextSegment::new("humidity", Rgb(palette.dew_point)),
+			TextSegment::white(" (%)"),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		(times.len() as u32 - 1) * spacing.horizontal,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			left: padding.left + spacing.horizontal / 2,
+			right: padding.right + spacing.horizontal / 2,
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(10, 20),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.dew_point),
+		data: humidities.iter().copied().map(convert_num),
+		max: range.end(),
+	});
+	chart
+}
+
+/// From this daily highest wind speed, in km/h, the day is called breezy, windy or stormy, roughly from Beaufort 4, 6 and 9.
+const WIND_WORDS: [(f32, &str); 3] = [(62.0, "stormy"), (39.0, "windy"), (20.0, "breezy")];
+
+pub async fn handle_forecast_text(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let days =
+		horizon_option(interaction, "days", MAX_FORECAST_DAYS)?.unwrap_or(DEFAULT_FORECAST_DAYS);
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = DailyResult::get(location.coordinates(), days, units, &client).await?;
+	check_forecast_data(&result.daily.time)?;
+	let summary = describe_days(&result, units).join("\n");
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!("Daily forecast for {location}.\n{summary}")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// One sentence per day, like "Mon: 12–19°C, 40% chance of rain, breezy.", naming the days in the location's timezone. Anything Open-Meteo left out is left out of the sentences too.
+fn describe_days(result: &DailyResult, units: UnitSystem) -> Vec<String> {
+	let daily = &result.daily;
+	let timezone = result.timezone();
+	daily
+		.time
+		.iter()
+		.enumerate()
+		.map(|(index, &time)| {
+			// Adding zero turns a rounded -0 into 0.
+			let whole = |temperature: f32| temperature.round() + 0.0;
+			let mut parts = Vec::new();
+			if let (Some(&min), Some(&max)) = (
+				daily.temperature_2m_min.get(index),
+				daily.temperature_2m_max.get(index),
+			) {
+				parts.push(format!(
+					"{}–{}{}",
+					whole(min),
+					whole(max),
+					units.temperature_symbol()
+				));
+			}
+			match daily.precipitation_probability_max.get(index) {
+				Some(0) => parts.push(String::from("dry")),
+				Some(probability) => parts.push(format!("{probability}% chance of rain")),
+				None => (),
+			}
+			let wind = daily
+				.wind_speed_10m_max
+				.get(index)
+				.map(|&speed| units.chart_wind_speed_to_kmh(speed));
+			if let Some(&(_, word)) =
+				wind.and_then(|wind| WIND_WORDS.iter().find(|(threshold, _)| wind >= *threshold))
+			{
+				parts.push(String::from(word));
+			}
+			if parts.is_empty() {
+				parts.push(String::from("no forecast"));
+			}
+			format!(
+				"{}: {}.",
+				timezone.local(time).format("%a"),
+				parts.join(", ")
+			)
+		})
+		.collect()
+}
+
+pub fn create_daily() -> CreateCommand {
+	i18n::command("daily", "Daily weather forecast")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_theme_option())
+		.add_option(create_layout_option())
+		.add_option(create_data_option())
+		.add_option(create_horizon_option(
+			"days",
+			"How many days ahead to forecast (7 by default).",
+			MAX_FORECAST_DAYS,
+		))
+		.add_option(create_scale_option())
+		.add_option(create_private_option())
+}
+
+pub fn create_forecast_text() -> CreateCommand {
+	i18n::command(
+		"forecast_text",
+		"Daily weather forecast in words, one sentence per day",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_horizon_option(
+		"days",
+		"How many days ahead to forecast (7 by default).",
+		MAX_FORECAST_DAYS,
+	))
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn freezing_level_daily_range() {
+		let hourly = FreezingLevelHourly {
+			time: vec![0, 3600, 86_400, 90_000],
+			freezing_level_height: vec![Some(2400.0), Some(2100.0), None, Some(1800.0)],
+		};
+		assert_eq!(
+			hourly.daily_range(ForecastTimezone::new(None, 0)),
+			Some(vec![(2100.0, 2400.0), (1800.0, 1800.0)])
+		);
+		let tropical = FreezingLevelHourly {
+			freezing_level_height: vec![Some(4800.0), Some(4900.0), None, None],
+			..hourly
+		};
+		assert_eq!(tropical.daily_range(ForecastTimezone::new(None, 0)), None);
+	}
+	#[test]
+	fn days_described() {
+		let result: DailyResult = serde_json::from_str(
+			r#"{
+				"latitude": 52.37,
+				"longitude": 4.9,
+				"utc_offset_seconds": 7200,
+				"daily": {
+					"time": [1717365600, 1717452000, 1717538400],
+					"temperature_2m_min": [12.2, 9.6, -0.4],
+					"temperature_2m_max": [18.7, 14.0, 3.1],
+					"precipitation_sum": [1.2, 0.0, 0.0],
+					"precipitation_probability_max": [40, 0, 5],
+					"wind_speed_10m_max": [6.0, 12.0, 2.0]
+				}
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(
+			describe_days(&result, UnitSystem::Metric),
+			[
+				"Mon: 12–19°C, 40% chance of rain, breezy.",
+				"Tue: 10–14°C, dry, windy.",
+				"Wed: 0–3°C, 5% chance of rain.",
+			]
+		);
+	}
+	#[test]
+	fn missing_series_left_empty() {
+		let daily: DailyWeather = serde_json::from_str(
+			r#"{
+				"time": [0, 86400],
+				"temperature_2m_min": [4.0, 5.5],
+				"temperature_2m_max": [11.0, null],
+				"apparent_temperature_min": [2.0, 3.5],
+				"apparent_temperature_max": [9.0, 10.5],
+				"precipitation_sum": [0.0, 3.2],
+				"precipitation_probability_max": [10, null],
+				"wind_speed_10m_max": [5.0, 7.0],
+				"wind_gusts_10m_max": null,
+				"uv_index_max": [2.5, 3.0],
+				"relative_humidity_2m_mean": [81.0, 74.0]
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(daily.uv_index_max, vec![2.5, 3.0]);
+		assert_eq!(daily.relative_humidity_2m_mean, vec![81.0, 74.0]);
+		// Left out, sent as null, or with a gap, the series is dropped rather than the forecast.
+		assert!(daily.uv_index_clear_sky_max.is_empty());
+		assert!(daily.wind_gusts_10m_max.is_empty());
+		assert!(daily.precipitation_probability_max.is_empty());
+		assert!(daily.temperature_2m_max.is_empty());
+	}
+	#[test]
+	fn days_described_without_temperatures() {
+		let result: DailyResult = serde_json::from_str(
+			r#"{
+				"latitude": 52.37,
+				"longitude": 4.9,
+				"utc_offset_seconds": 7200,
+				"daily": {
+					"time": [1717365600, 1717452000],
+					"temperature_2m_min": [12.2, null],
+					"temperature_2m_max": [18.7, 14.0],
+					"precipitation_probability_max": [40, 0]
+				}
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(
+			describe_days(&result, UnitSystem::Metric),
+			["Mon: 40% chance of rain.", "Tue: dry."]
+		);
+	}
+}
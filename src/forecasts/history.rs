@@ -0,0 +1,413 @@
+use ab_glyph::{FontRef, PxScale};
+use chrono::{NaiveDate, Utc};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb, SolidBars},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::daily::day_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	palette::create_theme_option,
+	render::{create_scale_option, Style, MIN_HORIZON},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	units::UnitSystem,
+	util::{convert_num, CommandInteractionExt, ForecastTimezone},
+};
+
+/// The most days a history chart covers, so the days stay readable.
+const MAX_HISTORY_DAYS: i64 = 31;
+
+#[derive(Debug, Deserialize)]
+struct HistoryDaily {
+	time: Vec<i64>,
+	/// The archive lags a few days behind, and has no data for those days yet.
+	temperature_2m_min: Vec<Option<f32>>,
+	temperature_2m_max: Vec<Option<f32>>,
+	precipitation_sum: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs/historical-weather-api
+#[derive(Debug, Deserialize)]
+struct HistoryResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	daily: HistoryDaily,
+}
+
+impl HistoryResult {
+	async fn get(
+		coordinates: Coordinates,
+		start: NaiveDate,
+		end: NaiveDate,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		client
+			.get("https://archive-api.open-meteo.com/v1/archive")
+			.query(&[
+				("daily", "temperature_2m_min"),
+				("daily", "temperature_2m_max"),
+				("daily", "precipitation_sum"),
+				("timeformat", "unixtime"),
+				("timezone", "auto"),
+			])
+			.query(&units.query())
+			.query(&[
+				("start_date", start.to_string()),
+				("end_date", end.to_string()),
+			])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// One day of past weather, with every reading known.
+struct HistoryDay {
+	time: i64,
+	min: f32,
+	max: f32,
+	precipitation: f32,
+}
+
+impl HistoryDaily {
+	/// The days up to the first one the archive doesn't have every reading for yet.
+	fn complete_days(&self) -> Vec<HistoryDay> {
+		self.time
+			.iter()
+			.zip(&self.temperature_2m_min)
+			.zip(&self.temperature_2m_max)
+			.zip(&self.precipitation_sum)
+			.map_while(|(((&time, min), max), precipitation)| {
+				Some(HistoryDay {
+					time,
+					min: (*min)?,
+					max: (*max)?,
+					precipitation: (*precipitation)?,
+				})
+			})
+			.collect()
+	}
+}
+
+/// The date in the command's option of this name, which has to be written as YYYY-MM-DD.
+fn date_option(interaction: &CommandInteraction, name: &str) -> Result<NaiveDate, Error> {
+	let value = interaction
+		.string_option(name)
+		.ok_or_else(|| Error::custom_unfriendly("Missing argument"))?;
+	parse_date(value, name)
+}
+
+fn parse_date(value: &str, name: &str) -> Result<NaiveDate, Error> {
+	NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+		Error::friendly(format!(
+			"Could not read \"{value}\" as the {name}. Write it as YYYY-MM-DD, like 2024-07-21."
+		))
+	})
+}
+
+/// Check that the dates are in order, in the past and not too far apart.
+fn check_date_range(start: NaiveDate, end: NaiveDate, today: NaiveDate) -> Result<(), Error> {
+	if start > end {
+		return Err(Error::friendly(
+			"The start date has to be before the end date",
+		));
+	}
+	if end >= today {
+		return Err(Error::friendly(
+			"The history only goes up to yesterday. Use /daily for the forecast.",
+		));
+	}
+	let days = (end - start).num_days() + 1;
+	if days < MIN_HORIZON as i64 {
+		return Err(Error::friendly(format!(
+			"The history needs at least {MIN_HORIZON} days to chart"
+		)));
+	}
+	if days > MAX_HISTORY_DAYS {
+		return Err(Error::friendly(format!(
+			"The history can cover at most {MAX_HISTORY_DAYS} days at a time"
+		)));
+	}
+	Ok(())
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_history(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let start = date_option(interaction, "start_date")?;
+	let end = date_option(interaction, "end_date")?;
+	check_date_range(start, end, Utc::now().date_naive())?;
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let style = Style::from_interaction(interaction, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result =
+		HistoryResult::get(location.coordinates(), start, end, style.units, &client).await?;
+	let days = result.daily.complete_days();
+	if days.len() < MIN_HORIZON as usize {
+		return Err(Error::friendly(
+			"No past weather for those dates yet. The archive runs a few days behind.",
+		));
+	}
+	let mut content = format!(
+		"Weather in {} from {start} to {end}.",
+		location.short_description()
+	);
+	if days.len() < result.daily.time.len() {
+		content += " The archive doesn't have the last few days yet.";
+	}
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let image = draw_history(&days, timezone, style, font, header_font);
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(content)
+				.add_file(CreateAttachment::bytes(image, "history.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the daily minimum and maximum temperatures as lines and the precipitation as bars, like the daily forecast.
+fn draw_history(
+	days: &[HistoryDay],
+	timezone: ForecastTimezone,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let palette = style.theme.palette();
+	let units = style.units;
+	let times: Vec<_> = days
+		.iter()
+		.map(|day| day_from_timestamp(day.time, timezone))
+		.collect();
+
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 9,
+	});
+
+	let (min, max) = days
+		.iter()
+		.flat_map(|day| [day.min, day.max])
+		.map(convert_num)
+		.minmax()
+		.into_option()
+		.unwrap_or((0, 0));
+	let chart_temp_range = previous_and_next_multiple(Range::new(min, max), 4);
+
+	let spacing = style.spacing(Spacing {
+		horizontal: 25,
+		vertical: 3,
+	});
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Minimum", Rgb(palette.low_temperature)),
+			TextSegment::white(" and "),
+			TextSegment::new("maximum", Rgb(palette.temperature)),
+			TextSegment::white(&format!(" temperatures ({})", units.temperature_symbol())),
+		],
+		header_font.clone(),
+		style.font_size(LABEL_SIZE),
+		(days.len() as u32 - 1) * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		days.len(),
+		chart_temp_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			left: padding.left + spacing.horizontal / 2,
+			right: padding.right + spacing.horizontal / 2,
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(2, 4),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: chart_temp_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.low_temperature),
+		data: days.iter().map(|day| convert_num(day.min)),
+		max: chart_temp_range.end(),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.temperature),
+		data: days.iter().map(|day| convert_num(day.max)),
+		max: chart_temp_range.end(),
+	});
+	let temp_image = chart.into_canvas();
+
+	let max_precipitation = days
+		.iter()
+		.fold(0.0f32, |acc, day| acc.max(day.precipitation));
+	let precipitation_range = Range::new(
+		0,
+		next_multiple(convert_num(units.chart_precipitation(max_precipitation)), 5),
+	);
+
+	let spacing = style.spacing(Spacing {
+		horizontal: 25,
+		vertical: 1,
+	});
+	let label = TextBox::new(
+		&[
+			TextSegment::white("Total "),
+			TextSegment::new("precipitation", Rgb(palette.precipitation)),
+			TextSegment::white(&format!(" ({})", units.chart_precipitation_label())),
+		],
+		header_font.clone(),
+		style.font_size(LABEL_SIZE),
+		days.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		days.len() + 1,
+		precipitation_range.end() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(25, 25),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: precipitation_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(SolidBars {
+		colour: Rgb(palette.precipitation),
+		data: days
+			.iter()
+			.map(|day| convert_num(units.chart_precipitation(day.precipitation))),
+	});
+	let precipitation_image = chart.into_canvas();
+
+	make_png(composite(&[temp_image, precipitation_image]))
+}
+
+pub fn create_history() -> CreateCommand {
+	i18n::command("history", "Past daily temperatures and precipitation")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"start_date",
+				"The first day, as YYYY-MM-DD.",
+			)
+			.required(true),
+		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"end_date",
+				"The last day, as YYYY-MM-DD.",
+			)
+			.required(true),
+		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the past weather of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_theme_option())
+		.add_option(create_scale_option())
+		.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(year, month, day).unwrap()
+	}
+
+	#[test]
+	fn dates_parsed() {
+		assert_eq!(
+			parse_date(" 2024-07-21", "start_date").unwrap(),
+			date(2024, 7, 21)
+		);
+		assert!(matches!(
+			parse_date("21/07/2024", "start_date"),
+			Err(Error::Friendly(_))
+		));
+		assert!(parse_date("2024-02-30", "end_date").is_err());
+	}
+	#[test]
+	fn date_ranges_checked() {
+		let today = date(2024, 8, 1);
+		assert!(check_date_range(date(2024, 7, 1), date(2024, 7, 31), today).is_ok());
+		assert!(check_date_range(date(2024, 7, 10), date(2024, 7, 3), today).is_err());
+		assert!(check_date_range(date(2024, 7, 25), date(2024, 8, 1), today).is_err());
+		assert!(check_date_range(date(2024, 7, 3), date(2024, 7, 3), today).is_err());
+		assert!(check_date_range(date(2024, 6, 1), date(2024, 7, 31), today).is_err());
+	}
+	#[test]
+	fn missing_days_cut_off() {
+		let daily = HistoryDaily {
+			time: vec![0, 86_400, 172_800],
+			temperature_2m_min: vec![Some(12.0), Some(13.5), None],
+			temperature_2m_max: vec![Some(24.0), Some(26.1), None],
+			precipitation_sum: vec![Some(0.0), Some(3.2), Some(0.0)],
+		};
+		let days = daily.complete_days();
+		assert_eq!(days.len(), 2);
+		assert_eq!(days[1].max, 26.1);
+	}
+}
@@ -0,0 +1,1902 @@
+use ab_glyph::{FontRef, PxScale};
+use chrono::Timelike;
+use graph::{
+	common_types::{GradientPoint, MultiPointGradient, Range},
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, GradientBars, HorizontalLines, Line, Rgb, SolidBars},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	csv::{create_data_option, data_option, to_csv, Column},
+	error::Error,
+	home_timezone, i18n,
+	layout::{create_layout_option, header_height},
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, complete_series, RequestBuilderExt},
+	palette::{create_theme_option, Palette, UV_PROTECTION_THRESHOLD},
+	refresh::refresh_button,
+	render::{
+		check_forecast_data, create_apparent_option, create_horizon_option, create_scale_option,
+		create_start_option, create_wet_bulb_option, horizon_option, start_option, ForecastKind,
+		Style, TemperatureLines, MIN_HORIZON,
+	},
+	render_archive::archive_render,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	units::UnitSystem,
+	util::{
+		absolute_humidity, compass_point, convert_num, convert_tenths, convert_whole,
+		dew_point_temp, is_thunderstorm, notable_gust_bars, precip_intensity, wet_bulb_temp,
+		Comfort, CommandInteractionExt, ForecastTimezone,
+	},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+struct HourlyWeather {
+	time: Vec<i64>,
+	/// Empty if Open-Meteo has no complete series for it, like the clear sky UV index, as are the other variables read with `complete_series`. The charts of empty variables are left out.
+	#[serde(default, deserialize_with = "complete_series")]
+	uv_index: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	uv_index_clear_sky: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	temperature_2m: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	apparent_temperature: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	relative_humidity_2m: Vec<i32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation_probability: Vec<u8>,
+	#[serde(default, deserialize_with = "complete_series")]
+	precipitation: Vec<f32>,
+	/// Missing from renders archived before precipitation was split by type, like the ones below.
+	#[serde(default, deserialize_with = "complete_series")]
+	rain: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	showers: Vec<f32>,
+	/// In centimetres or inches of snow, rather than of water like the other precipitation.
+	#[serde(default, deserialize_with = "complete_series")]
+	snowfall: Vec<f32>,
+	/// Missing from renders archived before cloud cover was requested, like the layers below.
+	#[serde(default, deserialize_with = "complete_series")]
+	cloud_cover: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	cloud_cover_low: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	cloud_cover_mid: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	cloud_cover_high: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_speed_10m: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_gusts_10m: Vec<f32>,
+	/// Missing from renders archived before the direction was requested.
+	#[serde(default, deserialize_with = "complete_series")]
+	wind_direction_10m: Vec<f32>,
+	/// WMO weather codes. Missing from renders archived before thunderstorms were marked.
+	#[serde(default, deserialize_with = "complete_series")]
+	weather_code: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct HourlyResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	/// Missing from renders archived before the timezone was kept.
+	#[serde(default)]
+	timezone: Option<String>,
+	hourly: HourlyWeather,
+}
+
+impl HourlyResult {
+	/// The forecast for `hours` hours, starting `start` hours from now.
+	async fn get(
+		coordinates: Coordinates,
+		start: u32,
+		hours: u32,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		let mut result = client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[("hourly", "uv_index")])
+			.query(&[("hourly", "uv_index_clear_sky")])
+			.query(&[("hourly", "temperature_2m")])
+			.query(&[("hourly", "relative_humidity_2m")])
+			.query(&[("hourly", "apparent_temperature")])
+			.query(&[("hourly", "precipitation_probability")])
+			.query(&[("hourly", "precipitation")])
+			.query(&[("hourly", "rain")])
+			.query(&[("hourly", "showers")])
+			.query(&[("hourly", "snowfall")])
+			.query(&[("hourly", "cloud_cover")])
+			.query(&[("hourly", "cloud_cover_low")])
+			.query(&[("hourly", "cloud_cover_mid")])
+			.query(&[("hourly", "cloud_cover_high")])
+			.query(&[("hourly", "wind_speed_10m")])
+			.query(&[("hourly", "wind_gusts_10m")])
+			.query(&[("hourly", "wind_direction_10m")])
+			.query(&[("hourly", "weather_code")])
+			.query(&[("wind_speed_unit", units.chart_wind_speed_unit())])
+			.query(&units.query())
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", start + hours)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo::<HourlyResult>()
+			.await?;
+		result.hourly.skip(start as usize);
+		Ok(result)
+	}
+}
+
+impl HourlyWeather {
+	/// Drop the first `hours` hours, since Open-Meteo's hourly forecast always starts now.
+	fn skip(&mut self, hours: usize) {
+		fn skip<T>(values: &mut Vec<T>, hours: usize) {
+			values.drain(..hours.min(values.len()));
+		}
+		skip(&mut self.time, hours);
+		skip(&mut self.uv_index, hours);
+		skip(&mut self.uv_index_clear_sky, hours);
+		skip(&mut self.temperature_2m, hours);
+		skip(&mut self.apparent_temperature, hours);
+		skip(&mut self.relative_humidity_2m, hours);
+		skip(&mut self.precipitation_probability, hours);
+		skip(&mut self.precipitation, hours);
+		skip(&mut self.rain, hours);
+		skip(&mut self.showers, hours);
+		skip(&mut self.snowfall, hours);
+		skip(&mut self.cloud_cover, hours);
+		skip(&mut self.cloud_cover_low, hours);
+		skip(&mut self.cloud_cover_mid, hours);
+		skip(&mut self.cloud_cover_high, hours);
+		skip(&mut self.wind_speed_10m, hours);
+		skip(&mut self.wind_gusts_10m, hours);
+		skip(&mut self.wind_direction_10m, hours);
+		skip(&mut self.weather_code, hours);
+	}
+}
+
+impl HourlyResult {
+	fn timezone(&self) -> ForecastTimezone {
+		ForecastTimezone::new(self.timezone.as_deref(), self.utc_offset_seconds)
+	}
+	/// The forecast data as CSV, with one row per hour.
+	fn to_csv(&self, units: UnitSystem) -> String {
+		let hourly = &self.hourly;
+		let temperature = units.temperature_symbol();
+		let wind_speed = units.chart_wind_speed_symbol();
+		to_csv(
+			&hourly.time,
+			self.timezone(),
+			"%Y-%m-%d %H:%M",
+			&[
+				Column::new(
+					format!("temperature_2m ({temperature})"),
+					&hourly.temperature_2m,
+				),
+				Column::new(
+					format!("apparent_temperature ({temperature})"),
+					&hourly.apparent_temperature,
+				),
+				Column::new("relative_humidity_2m (%)", &hourly.relative_humidity_2m),
+				Column::new(
+					"precipitation_probability (%)",
+					&hourly.precipitation_probability,
+				),
+				Column::new(
+					format!("precipitation ({})", units.precipitation_symbol()),
+					&hourly.precipitation,
+				),
+				Column::new(
+					format!("rain ({})", units.precipitation_symbol()),
+					&hourly.rain,
+				),
+				Column::new(
+					format!("showers ({})", units.precipitation_symbol()),
+					&hourly.showers,
+				),
+				Column::new(
+					format!("snowfall ({})", units.snowfall_symbol()),
+					&hourly.snowfall,
+				),
+				Column::new("cloud_cover (%)", &hourly.cloud_cover),
+				Column::new("cloud_cover_low (%)", &hourly.cloud_cover_low),
+				Column::new("cloud_cover_mid (%)", &hourly.cloud_cover_mid),
+				Column::new("cloud_cover_high (%)", &hourly.cloud_cover_high),
+				Column::new(
+					format!("wind_speed_10m ({wind_speed})"),
+					&hourly.wind_speed_10m,
+				),
+				Column::new(
+					format!("wind_gusts_10m ({wind_speed})"),
+					&hourly.wind_gusts_10m,
+				),
+				Column::new("wind_direction_10m (°)", &hourly.wind_direction_10m),
+				Column::new("weather_code (WMO code)", &hourly.weather_code),
+				Column::new("uv_index", &hourly.uv_index),
+				Column::new("uv_index_clear_sky", &hourly.uv_index_clear_sky),
+			],
+		)
+	}
+}
+
+/// Get the hour of the day (from 0 to 23) for a given Unix timestamp in a timezone.
+pub fn hour_from_timestamp(timestamp: i64, timezone: ForecastTimezone) -> u8 {
+	timezone.local(timestamp).hour() as u8
+}
+
+const DEFAULT_FORECAST_HOURS: u32 = 48;
+/// The furthest ahead Open-Meteo forecasts.
+const MAX_FORECAST_HOURS: u32 = 384;
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_hourly(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let horizon = horizon_option(interaction, "hours", MAX_FORECAST_HOURS)?;
+	let style = Style {
+		horizon,
+		start: start_option(
+			interaction,
+			"start",
+			horizon.unwrap_or(DEFAULT_FORECAST_HOURS),
+			MAX_FORECAST_HOURS,
+		)?,
+		..Style::from_interaction(interaction, database).await?
+	};
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+	let result = fetch_hourly(location.coordinates(), style, &client, database).await?;
+	let data = data_option(interaction).then(|| result.to_csv(style.units));
+	let timezone = style.timezone(result.timezone());
+	let image = draw_hourly(result, style, font, header_font);
+
+	let mut message = CreateInteractionResponseFollowup::new()
+		.ephemeral(private)
+		.content(format!(
+			"Hourly forecast for {location}. Times are in {timezone}."
+		))
+		.add_file(CreateAttachment::bytes(image, "hourly.png"))
+		.components(
+			refresh_button(ForecastKind::Hourly, style, location.coordinates())
+				.into_iter()
+				.collect(),
+		);
+	if let Some(data) = data {
+		message = message.add_file(CreateAttachment::bytes(data, "hourly.csv"));
+	}
+	interaction.create_followup(context, message).await?;
+	Ok(())
+}
+
+/// Fetch the hourly forecast for the coordinates and draw it as a PNG.
+pub async fn render_hourly(
+	coordinates: Coordinates,
+	style: Style,
+	client: &Client,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let result = fetch_hourly(coordinates, style, client, database).await?;
+	Ok(draw_hourly(result, style, font, header_font))
+}
+
+/// Fetch the hourly forecast for the coordinates, archiving it if the archive is enabled.
+async fn fetch_hourly(
+	coordinates: Coordinates,
+	style: Style,
+	client: &Client,
+	database: &Pool<Sqlite>,
+) -> Result<HourlyResult, Error> {
+	let hours = style.horizon.unwrap_or(DEFAULT_FORECAST_HOURS);
+	let result = HourlyResult::get(coordinates, style.start, hours, style.units, client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	archive_render(database, ForecastKind::Hourly, style, coordinates, &result).await;
+	Ok(result)
+}
+
+/// Draw the hourly forecast again from archived data.
+pub fn draw_archived_hourly(
+	input: &str,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	Ok(draw_hourly(
+		serde_json::from_str(input)?,
+		style,
+		font,
+		header_font,
+	))
+}
+
+fn draw_hourly(
+	result: HourlyResult,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let palette = style.theme.palette();
+	let layout = style.layout;
+	let units = style.units;
+	let timezone = style.timezone(result.timezone());
+	let boundaries = day_boundaries(&result.hourly.time, timezone);
+	let day_image =
+		day_graph(&result.hourly.time, &boundaries, timezone, style, font).into_canvas();
+	let separators = |height: i32| day_separators(&boundaries, result.hourly.time.len(), height);
+	let times = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect::<Vec<_>>();
+
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	});
+
+	let complete = |len: usize| len == times.len();
+	let has_temperature = complete(result.hourly.temperature_2m.len());
+	let has_humidity = has_temperature && complete(result.hourly.relative_humidity_2m.len());
+	// Lines without data to draw them from are left out.
+	let lines = TemperatureLines {
+		apparent: style.temperature_lines.apparent
+			&& complete(result.hourly.apparent_temperature.len()),
+		wet_bulb: style.temperature_lines.wet_bulb && has_humidity,
+	};
+
+	// In °C, as the humidity formulas take it.
+	let humidities: Vec<_> = result
+		.hourly
+		.temperature_2m
+		.iter()
+		.zip(&result.hourly.relative_humidity_2m)
+		.map(|(&temp, &humidity)| (units.temperature_to_celsius(temp), humidity as f32))
+		.collect();
+
+	let dew_points: Vec<_> = humidities
+		.iter()
+		.map(|&(temp, humidity)| {
+			convert_num(units.temperature_from_celsius(dew_point_temp(temp, humidity)))
+		})
+		.collect();
+
+	// Lines that are left out get the dry bulb temperature as a stand-in.
+	let temps: Vec<_> = result
+		.hourly
+		.temperature_2m
+		.iter()
+		.enumerate()
+		.map(|(index, &temp)| {
+			let apparent = result
+				.hourly
+				.apparent_temperature
+				.get(index)
+				.copied()
+				.unwrap_or(temp);
+			let wet_bulb = humidities
+				.get(index)
+				.map(|&(celsius, humidity)| {
+					units.temperature_from_celsius(wet_bulb_temp(celsius, humidity))
+				})
+				.unwrap_or(temp);
+			[temp, apparent, wet_bulb].map(convert_num)
+		})
+		.collect();
+
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 3,
+	});
+	let extra_lines = extra_temperature_lines(lines, palette);
+
+	let temp_image = has_temperature.then(|| {
+		let temp_range = temps
+			.iter()
+			.flat_map(|&[temp, apparent, wet_bulb]| {
+				[
+					Some(temp),
+					lines.apparent.then_some(apparent),
+					lines.wet_bulb.then_some(wet_bulb),
+				]
+			})
+			.flatten()
+			.minmax()
+			.into_option()
+			.unwrap_or((0, 0));
+		let chart_temp_range =
+			previous_and_next_multiple(Range::new(temp_range.0, temp_range.1), 4);
+
+		let mut segments = vec![TextSegment::new("Dry bulb", Rgb(palette.temperature))];
+		for (index, &(name, colour)) in extra_lines.iter().enumerate() {
+			segments.push(TextSegment::white(if index + 1 == extra_lines.len() {
+				" and "
+			} else {
+				", "
+			}));
+			segments.push(TextSegment::new(name, colour));
+		}
+		let unit = format!(
+			" {} ({})",
+			if extra_lines.is_empty() {
+				"temperature"
+			} else {
+				"temperatures"
+			},
+			units.temperature_symbol()
+		);
+		segments.push(TextSegment::white(&unit));
+		let label = layout.header(
+			&segments,
+			header_font,
+			style.font_size(LABEL_SIZE),
+			(temps.len() - 1) as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			temps.len(),
+			chart_temp_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(2, 4),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: chart_temp_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		if lines.apparent {
+			chart.draw(Line {
+				colour: Rgb(palette.apparent_temperature),
+				data: temps.iter().map(|[_, apparent, _]| apparent).copied(),
+				max: chart_temp_range.end(),
+			});
+		}
+		if lines.wet_bulb {
+			chart.draw(Line {
+				colour: Rgb(palette.low_temperature),
+				data: temps.iter().map(|[_, _, wet_bulb]| wet_bulb).copied(),
+				max: chart_temp_range.end(),
+			});
+		}
+		chart.draw(Line {
+			colour: Rgb(palette.temperature),
+			data: temps.iter().map(|[temp, _, _]| temp).copied(),
+			max: chart_temp_range.end(),
+		});
+
+		chart.into_canvas()
+	});
+
+	let thunderstorm_image = complete(result.hourly.weather_code.len())
+		.then(|| thunderstorm_hours(&result.hourly.weather_code))
+		.flatten()
+		.map(|hours| thunderstorm_graph(&hours, style, padding, header_font).into_canvas());
+
+	let dew_point_image = has_humidity.then(|| {
+		let dew_point_range = dew_points
+			.iter()
+			.copied()
+			.minmax()
+			.into_option()
+			.unwrap_or((0, 0));
+		let chart_dew_point_range =
+			previous_and_next_multiple(Range::new(dew_point_range.0, dew_point_range.1), 4);
+
+		let label = layout.header(
+			&[
+				TextSegment::new("Dew point", Rgb(palette.dew_point)),
+				TextSegment::white(&format!(" ({})", units.temperature_symbol())),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			(dew_points.len() - 1) as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			dew_points.len(),
+			chart_dew_point_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(2, 4),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: chart_dew_point_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(Line {
+			colour: Rgb(palette.dew_point),
+			data: dew_points.into_iter(),
+			max: chart_dew_point_range.end(),
+		});
+
+		chart.into_canvas()
+	});
+
+	let comfort_image = has_humidity.then(|| {
+		comfort_graph(&humidities, &times, style, padding, font, header_font).into_canvas()
+	});
+
+	let uvi_image = (!result.hourly.uv_index.is_empty()
+		|| !result.hourly.uv_index_clear_sky.is_empty())
+	.then(|| {
+		let max_uv = result
+			.hourly
+			.uv_index
+			.iter()
+			.chain(&result.hourly.uv_index_clear_sky)
+			.fold(0.0f32, |acc, num| acc.max(*num));
+		let uv_range = Range::new(0, next_multiple(convert_num(max_uv), 1));
+
+		let spacing = style.spacing(Spacing {
+			horizontal: 8,
+			vertical: 10,
+		});
+
+		let label = layout.header(
+			&[TextSegment::white("UV index: ")]
+				.into_iter()
+				.chain(palette.uv_legend())
+				.chain([
+					TextSegment::white(" (and "),
+					TextSegment::new("clear sky UVI", Rgb(palette.uv_clear_sky)),
+					TextSegment::white(", "),
+					TextSegment::new("protection needed", Rgb(palette.uv_protection)),
+					TextSegment::white(")"),
+				])
+				.collect::<Vec<_>>(),
+			header_font,
+			style.font_size(LABEL_SIZE),
+			times.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			times.len() + 1,
+			uv_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 1),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: uv_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.day_separator),
+			data: separators(uv_range.end()),
+		});
+		chart.draw(HorizontalLines {
+			colour: Rgb(palette.uv_clear_sky),
+			data: result
+				.hourly
+				.uv_index_clear_sky
+				.into_iter()
+				.map(convert_tenths),
+		});
+		chart.draw(GradientBars {
+			gradient: palette.uv_gradient(padding.below, spacing.vertical),
+			data: result.hourly.uv_index.into_iter().map(convert_tenths),
+		});
+		// Over the bars, so it shows which hours need protection.
+		if convert_num(UV_PROTECTION_THRESHOLD) <= uv_range.end() {
+			chart.draw(HorizontalLines {
+				colour: Rgb(palette.uv_protection),
+				data: std::iter::repeat_n(convert_num(UV_PROTECTION_THRESHOLD), times.len()),
+			});
+		}
+
+		chart.into_canvas()
+	});
+
+	let pop_image = complete(result.hourly.precipitation_probability.len()).then(|| {
+		let spacing = style.spacing(Spacing {
+			horizontal: 8,
+			vertical: 1,
+		});
+		let probability_range = Range::new(0, 100 * 100);
+
+		let label = layout.header(
+			&[
+				TextSegment::white("Probability of "),
+				TextSegment::new("precipitation", Rgb(palette.precipitation_probability)),
+				TextSegment::white(" (%)"),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			result.hourly.precipitation_probability.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			result.hourly.precipitation_probability.len() + 1,
+			probability_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(10, 20),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: probability_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.day_separator),
+			data: separators(probability_range.end()),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.precipitation_probability),
+			data: result
+				.hourly
+				.precipitation_probability
+				.into_iter()
+				.map(|n| n as i32 * 100),
+		});
+
+		chart.into_canvas()
+	});
+
+	let cloud_image = [
+		&result.hourly.cloud_cover,
+		&result.hourly.cloud_cover_low,
+		&result.hourly.cloud_cover_mid,
+		&result.hourly.cloud_cover_high,
+	]
+	.iter()
+	.all(|series| complete(series.len()))
+	.then(|| {
+		cloud_graph(
+			&result.hourly.cloud_cover,
+			[
+				&result.hourly.cloud_cover_low,
+				&result.hourly.cloud_cover_mid,
+				&result.hourly.cloud_cover_high,
+			],
+			&times,
+			style,
+			padding,
+			font,
+			header_font,
+		)
+		.into_canvas()
+	});
+
+	let precipitation_image = complete(result.hourly.precipitation.len()).then(|| {
+		let spacing = style.spacing(Spacing {
+			horizontal: 8,
+			vertical: 16,
+		});
+		let max_precipitation = result
+			.hourly
+			.precipitation
+			.iter()
+			.fold(0.0f32, |acc, num| acc.max(*num));
+		let peak_note = result
+			.hourly
+			.precipitation
+			.iter()
+			.position(|&amount| amount == max_precipitation && amount > 0.0)
+			.map(|index| {
+				format!(
+					", heaviest around {:02}:00 ({})",
+					times[index],
+					precip_intensity(units.precipitation_to_mm(max_precipitation))
+				)
+			})
+			.unwrap_or_default();
+
+		let precipitation_range = Range::new(
+			0,
+			next_multiple(convert_num(units.chart_precipitation(max_precipitation)), 1),
+		);
+
+		let label = layout.header(
+			&[
+				TextSegment::white("Amount of "),
+				TextSegment::new("rain", Rgb(palette.precipitation)),
+				TextSegment::white(", "),
+				TextSegment::new("showers", Rgb(palette.showers)),
+				TextSegment::white(" and "),
+				TextSegment::new("snow", Rgb(palette.snow)),
+				TextSegment::white(&format!(" ({})", units.chart_precipitation_label())),
+				TextSegment::white(&peak_note),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			result.hourly.precipitation.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			result.hourly.precipitation.len() + 1,
+			precipitation_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 1),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: precipitation_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		// Rain is stacked under showers under snow, by drawing each layer's top down over the one above it. Snowfall is measured as snow rather than as water, so the snow is whatever of the precipitation isn't rain or showers.
+		let precipitation = result.hourly.precipitation;
+		let (rain, showers) = if result.hourly.rain.len() == precipitation.len()
+			&& result.hourly.showers.len() == precipitation.len()
+		{
+			(result.hourly.rain, result.hourly.showers)
+		} else {
+			(precipitation.clone(), vec![0.0; precipitation.len()])
+		};
+		let chart_amount = |amount: f32| convert_num(units.chart_precipitation(amount));
+		chart.draw(SolidBars {
+			colour: Rgb(palette.day_separator),
+			data: separators(precipitation_range.end()),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.snow),
+			data: precipitation.iter().copied().map(chart_amount),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.showers),
+			data: precipitation
+				.iter()
+				.zip(rain.iter().zip(&showers))
+				.map(|(&total, (&rain, &showers))| chart_amount((rain + showers).min(total))),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.precipitation),
+			data: precipitation
+				.iter()
+				.zip(&rain)
+				.map(|(&total, &rain)| chart_amount(rain.min(total))),
+		});
+
+		chart.into_canvas()
+	});
+
+	let wind_image = complete(result.hourly.wind_speed_10m.len()).then(|| {
+		let spacing: Spacing = style.spacing(Spacing {
+			horizontal: 8,
+			vertical: units.chart_wind_speed_spacing(),
+		});
+		let wind_height = |speed: f32| {
+			padding.below
+				+ (spacing.vertical as f32 * units.wind_speed_from_metres_per_second(speed)) as u32
+		};
+
+		let max_chart_speed = next_multiple(
+			result
+				.hourly
+				.wind_speed_10m
+				.iter()
+				.chain(&result.hourly.wind_gusts_10m)
+				.copied()
+				.map(convert_num)
+				.max()
+				.unwrap_or(0),
+			5,
+		);
+
+		let data_range = Range::new(0, max_chart_speed);
+
+		let label = layout.header(
+			&[
+				TextSegment::new("Wind", Rgb(palette.wind_gradient[0])),
+				TextSegment::white(" and "),
+				TextSegment::new("gust", Rgb(palette.gust_gradient[0])),
+				TextSegment::white(&format!(" speed ({}), ", units.chart_wind_speed_symbol())),
+				TextSegment::new("strong gusts", Rgb(palette.strong_gust)),
+			],
+			header_font,
+			style.font_size(LABEL_SIZE),
+			result.hourly.wind_speed_10m.len() as u32 * spacing.horizontal,
+		);
+		let mut chart = Chart::new(
+			result.hourly.wind_speed_10m.len() + 1,
+			data_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + header_height(&label),
+				..padding
+			},
+		);
+		if let Some(label) = label {
+			chart.draw(label);
+		}
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(5, 5),
+			horizontal_intervals: MarkIntervals::new(1, 2),
+			vertical_label_range: data_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: true,
+			font: font.clone(),
+			font_scale: style.font_size(AXIS_LABEL_SIZE),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.day_separator),
+			data: separators(data_range.end()),
+		});
+		chart.draw(GradientBars {
+			gradient: MultiPointGradient::new(vec![
+				GradientPoint::from_rgb(padding.below, palette.gust_gradient[0]),
+				GradientPoint::from_rgb(wind_height(7.0), palette.gust_gradient[1]),
+				GradientPoint::from_rgb(wind_height(14.0), palette.gust_gradient[2]),
+				GradientPoint::from_rgb(wind_height(21.0), palette.gust_gradient[3]),
+			]),
+			data: result
+				.hourly
+				.wind_gusts_10m
+				.iter()
+				.copied()
+				.map(convert_num),
+		});
+		chart.draw(SolidBars {
+			colour: Rgb(palette.strong_gust),
+			data: notable_gust_bars(
+				units,
+				&result.hourly.wind_speed_10m,
+				&result.hourly.wind_gusts_10m,
+			)
+			.into_iter(),
+		});
+		chart.draw(GradientBars {
+			gradient: MultiPointGradient::new(vec![
+				GradientPoint::from_rgb(padding.below, palette.wind_gradient[0]),
+				GradientPoint::from_rgb(wind_height(7.0), palette.wind_gradient[1]),
+				GradientPoint::from_rgb(wind_height(14.0), palette.wind_gradient[2]),
+				GradientPoint::from_rgb(wind_height(21.0), palette.wind_gradient[3]),
+			]),
+			data: result.hourly.wind_speed_10m.into_iter().map(convert_num),
+		});
+
+		chart.into_canvas()
+	});
+
+	let wind_direction_image =
+		(wind_image.is_some() && complete(result.hourly.wind_direction_10m.len())).then(|| {
+			wind_direction_graph(
+				&result.hourly.wind_direction_10m,
+				style,
+				padding,
+				font,
+				header_font,
+			)
+			.into_canvas()
+		});
+
+	// As wide as the bar charts, whichever of them are drawn.
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 1,
+	});
+	let temperature_legend = extra_lines
+		.iter()
+		.flat_map(|&(name, colour)| [TextSegment::white(", "), TextSegment::new(name, colour)]);
+	let legend = layout.legend(
+		&[
+			TextSegment::new("Thunderstorms", Rgb(palette.thunderstorm)),
+			TextSegment::white(" · "),
+			TextSegment::new("dry bulb", Rgb(palette.temperature)),
+		]
+		.into_iter()
+		.chain(temperature_legend)
+		.chain([
+			TextSegment::white(&format!(" ({}) · ", units.temperature_symbol())),
+			TextSegment::new("dew point", Rgb(palette.dew_point)),
+			TextSegment::white(&format!(" ({}) · ", units.temperature_symbol())),
+			TextSegment::new("comfortable", Rgb(palette.comfort[0])),
+			TextSegment::white(", "),
+			TextSegment::new("humid", Rgb(palette.comfort[1])),
+			TextSegment::white(", "),
+			TextSegment::new("oppressive", Rgb(palette.comfort[2])),
+			TextSegment::white(" (g/m³) · "),
+			TextSegment::new(
+				"precipitation chance",
+				Rgb(palette.precipitation_probability),
+			),
+			TextSegment::white(" (%) · "),
+			TextSegment::new("cloud cover", Rgb(palette.cloud_cover)),
+			TextSegment::white(": "),
+			TextSegment::new("low", Rgb(palette.cloud_layers[0])),
+			TextSegment::white(", "),
+			TextSegment::new("mid", Rgb(palette.cloud_layers[1])),
+			TextSegment::white(", "),
+			TextSegment::new("high", Rgb(palette.cloud_layers[2])),
+			TextSegment::white(" (%) · "),
+			TextSegment::new("rain", Rgb(palette.precipitation)),
+			TextSegment::white(", "),
+			TextSegment::new("showers", Rgb(palette.showers)),
+			TextSegment::white(", "),
+			TextSegment::new("snow", Rgb(palette.snow)),
+			TextSegment::white(&format!(" ({}) · ", units.chart_precipitation_label())),
+			TextSegment::new("wind", Rgb(palette.wind_gradient[0])),
+			TextSegment::white(", "),
+			TextSegment::new("gusts", Rgb(palette.gust_gradient[0])),
+			TextSegment::white(&format!(" ({}) · ", units.chart_wind_speed_symbol())),
+			TextSegment::new("UVI", Rgb(palette.uv_bands[0])),
+			TextSegment::white(", "),
+			TextSegment::new("clear sky UVI", Rgb(palette.uv_clear_sky)),
+			TextSegment::white(", "),
+			TextSegment::new("protection needed", Rgb(palette.uv_protection)),
+		])
+		.collect::<Vec<_>>(),
+		header_font,
+		style.font_size(LABEL_SIZE),
+		times.len() + 1,
+		spacing,
+		padding,
+	);
+	let composite = composite(
+		&legend
+			.map(Chart::into_canvas)
+			.into_iter()
+			.chain([day_image])
+			.chain(thunderstorm_image)
+			.chain(temp_image)
+			.chain(dew_point_image)
+			.chain(comfort_image)
+			.chain(pop_image)
+			.chain(cloud_image)
+			.chain(precipitation_image)
+			.chain(wind_direction_image)
+			.chain(wind_image)
+			.chain(uvi_image)
+			.collect::<Vec<_>>(),
+	);
+	make_png(composite)
+}
+
+/// The positions of the first hour of every local day after the first, where the date changes from the hour before.
+fn day_boundaries(times: &[i64], timezone: ForecastTimezone) -> Vec<usize> {
+	let dates: Vec<_> = times
+		.iter()
+		.map(|&time| timezone.local(time).date())
+		.collect();
+	(1..dates.len())
+		.filter(|&index| dates[index] != dates[index - 1])
+		.collect()
+}
+
+/// Bars as high as `height` at the day boundaries and empty everywhere else, for `hours` hours.
+fn day_separators(
+	boundaries: &[usize],
+	hours: usize,
+	height: i32,
+) -> impl Iterator<Item = i32> + '_ {
+	(0..hours).map(move |index| {
+		if boundaries.contains(&index) {
+			height
+		} else {
+			0
+		}
+	})
+}
+
+/// A thin strip marking where each day starts, labelled with its weekday and date, so it's clear which day the hours below belong to.
+fn day_graph(
+	times: &[i64],
+	boundaries: &[usize],
+	timezone: ForecastTimezone,
+	style: Style,
+	font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 6,
+	});
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	});
+	let labels: Vec<String> = times
+		.iter()
+		.enumerate()
+		.map(|(index, &time)| {
+			if index == 0 || boundaries.contains(&index) {
+				timezone.local(time).format("%a %-d").to_string()
+			} else {
+				String::new()
+			}
+		})
+		.collect();
+	// One unit high, so every separator fills the strip.
+	let mut chart = Chart::new(times.len() + 1, 100, spacing, padding);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 1),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: Range::new(0, 100),
+		horizontal_labels: labels.iter(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(SolidBars {
+		colour: Rgb(palette.day_separator),
+		data: day_separators(boundaries, times.len(), 100),
+	});
+	chart
+}
+
+/// The temperature lines drawn besides the dry bulb temperature, with their colours, in the order they are named.
+fn extra_temperature_lines(lines: TemperatureLines, palette: &Palette) -> Vec<(&'static str, Rgb)> {
+	[
+		(lines.wet_bulb, "wet bulb", palette.low_temperature),
+		(lines.apparent, "apparent", palette.apparent_temperature),
+	]
+	.into_iter()
+	.filter(|&(shown, _, _)| shown)
+	.map(|(_, name, colour)| (name, Rgb(colour)))
+	.collect()
+}
+
+/// Which hours have a thunderstorm, or `None` if none do, so there is nothing to mark.
+fn thunderstorm_hours(weather_codes: &[u8]) -> Option<Vec<bool>> {
+	let hours: Vec<_> = weather_codes.iter().copied().map(is_thunderstorm).collect();
+	hours.contains(&true).then_some(hours)
+}
+
+/// Draw a thin strip with a mark over every hour with a thunderstorm, to go above the temperatures.
+fn thunderstorm_graph(
+	hours: &[bool],
+	style: Style,
+	padding: Padding,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 8,
+	});
+	let label = style.layout.header(
+		&[
+			TextSegment::new("Thunderstorms", Rgb(palette.thunderstorm)),
+			TextSegment::white(" expected"),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		hours.len() as u32 * spacing.horizontal,
+	);
+	// One unit high, so every mark fills the strip.
+	let mut chart = Chart::new(
+		hours.len() + 1,
+		100,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			// No axis labels to make room for, as the temperatures right below have them.
+			below: padding.above,
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(SolidBars {
+		colour: Rgb(palette.thunderstorm),
+		data: hours.iter().map(|&storm| if storm { 100 } else { 0 }),
+	});
+	chart
+}
+
+/// Draw a strip labelled with the compass point the wind blows from each hour, to go above the wind speeds. The fonts have no arrows, so the directions are written out.
+fn wind_direction_graph(
+	directions: &[f32],
+	style: Style,
+	padding: Padding,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 6,
+	});
+	let label = style.layout.header(
+		&[
+			TextSegment::new("Wind", Rgb(palette.wind_gradient[0])),
+			TextSegment::white(" direction, where it blows from"),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		directions.len() as u32 * spacing.horizontal,
+	);
+	let labels: Vec<_> = directions
+		.iter()
+		.map(|&direction| compass_point(direction))
+		.collect();
+	// One unit high, like the strip of days, so it takes little more room than its labels.
+	let mut chart = Chart::new(
+		directions.len() + 1,
+		100,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 1),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: Range::new(0, 100),
+		horizontal_labels: labels.iter(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart
+}
+
+/// Draw the absolute humidity of each hour, coloured by how muggy it makes the air feel. Takes the temperature in °C and the relative humidity of each hour.
+fn comfort_graph(
+	humidities: &[(f32, f32)],
+	times: &[u8],
+	style: Style,
+	padding: Padding,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let absolute: Vec<_> = humidities
+		.iter()
+		.map(|&(temp, humidity)| {
+			(
+				absolute_humidity(temp, humidity),
+				Comfort::classify(temp, humidity),
+			)
+		})
+		.collect();
+	// Always tall enough to show where it becomes oppressive.
+	let max = absolute
+		.iter()
+		.fold(0.0f32, |acc, (value, _)| acc.max(*value));
+	let range = Range::new(0, next_multiple(convert_num(max), 5).max(20 * 100));
+
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 3,
+	});
+	let label = style.layout.header(
+		&[
+			TextSegment::white("Humidity in g/m³: "),
+			TextSegment::new("comfortable", Rgb(palette.comfort[0])),
+			TextSegment::white(", "),
+			TextSegment::new("humid", Rgb(palette.comfort[1])),
+			TextSegment::white(" or "),
+			TextSegment::new("oppressive", Rgb(palette.comfort[2])),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		absolute.len() as u32 * spacing.horizontal,
+	);
+	let mut chart = Chart::new(
+		absolute.len() + 1,
+		range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(5, 5),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	// One set of bars per class, so each bar has the colour of its own class rather than of a band of heights.
+	for comfort in [Comfort::Comfortable, Comfort::Humid, Comfort::Oppressive] {
+		chart.draw(SolidBars {
+			colour: Rgb(palette.comfort[comfort.index()]),
+			data: absolute.iter().map(move |&(value, class)| {
+				if class == comfort {
+					convert_num(value)
+				} else {
+					0
+				}
+			}),
+		});
+	}
+	chart
+}
+
+/// Draw total cloud cover with the low, mid and high cloud layers over it, in percent.
+fn cloud_graph(
+	total: &[f32],
+	layers: [&[f32]; 3],
+	times: &[u8],
+	style: Style,
+	padding: Padding,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Chart {
+	let palette = style.theme.palette();
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 1,
+	});
+	let cover_range = Range::new(0, 100 * 100);
+
+	let label = style.layout.header(
+		&[
+			TextSegment::new("Cloud cover", Rgb(palette.cloud_cover)),
+			TextSegment::white(" by layer: "),
+			TextSegment::new("low", Rgb(palette.cloud_layers[0])),
+			TextSegment::white(", "),
+			TextSegment::new("mid", Rgb(palette.cloud_layers[1])),
+			TextSegment::white(" and "),
+			TextSegment::new("high", Rgb(palette.cloud_layers[2])),
+			TextSegment::white(" (%)"),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		(times.len() as u32 - 1) * spacing.horizontal,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		cover_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(10, 20),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: cover_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.cloud_cover),
+		data: total.iter().copied().map(convert_whole),
+		max: cover_range.end(),
+	});
+	for (colour, layer) in palette.cloud_layers.iter().zip(layers).rev() {
+		chart.draw(Line {
+			colour: Rgb(*colour),
+			data: layer.iter().copied().map(convert_whole),
+			max: cover_range.end(),
+		});
+	}
+	chart
+}
+
+pub fn create_hourly() -> CreateCommand {
+	i18n::command("hourly", "Hourly weather forecast")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_theme_option())
+		.add_option(create_layout_option())
+		.add_option(create_data_option())
+		.add_option(create_horizon_option(
+			"hours",
+			"How many hours ahead to forecast (48 by default).",
+			MAX_FORECAST_HOURS,
+		))
+		.add_option(create_start_option(
+			"How many hours from now to start the forecast (0 by default).",
+			MAX_FORECAST_HOURS - MIN_HORIZON,
+		))
+		.add_option(create_scale_option())
+		.add_option(create_wet_bulb_option())
+		.add_option(create_apparent_option())
+		.add_option(create_private_option())
+}
+
+/// Compare the hourly temperature of two places on one chart.
+///
+/// Both forecasts start at the current hour, so they are lined up by how many hours ahead they are, which makes every point the same moment at both places. The hours are labelled in the first place's timezone, or the user's home timezone if they set one. Where the two places' UTC offsets differ by a fraction of an hour, their hours are that fraction apart, which is close enough for a comparison.
+pub async fn handle_compare(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let place_option = |name: &str| {
+		interaction
+			.string_option(name)
+			.ok_or_else(|| Error::custom_unfriendly(format!("Missing {name} option")))
+	};
+	let client = open_meteo::client();
+	let first = Location::try_from_arg(place_option("place")?, &client, database).await?;
+	let second = Location::try_from_arg(place_option("other_place")?, &client, database).await?;
+	let style = Style::from_interaction(interaction, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let (first_result, second_result) = tokio::join!(
+		HourlyResult::get(
+			first.coordinates(),
+			0,
+			DEFAULT_FORECAST_HOURS,
+			style.units,
+			&client
+		),
+		HourlyResult::get(
+			second.coordinates(),
+			0,
+			DEFAULT_FORECAST_HOURS,
+			style.units,
+			&client
+		),
+	);
+	let first_result = first_result?;
+	let timezone = style.timezone(first_result.timezone());
+	let image = draw_comparison(
+		[
+			(first.short_description(), first_result),
+			(second.short_description(), second_result?),
+		],
+		style,
+		font,
+		header_font,
+	)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!("Times are in {timezone}."))
+				.add_file(CreateAttachment::bytes(image, "compare.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the temperatures of both places as lines on one chart, with a header naming each place in the colour of its line.
+fn draw_comparison(
+	places: [(String, HourlyResult); 2],
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let palette = style.theme.palette();
+	let colours = [Rgb(palette.temperature), Rgb(palette.low_temperature)];
+	let hours = places
+		.iter()
+		.map(|(_, result)| result.hourly.temperature_2m.len())
+		.min()
+		.unwrap_or(0);
+	if hours < 2 {
+		return Err(Error::friendly("No hourly forecast to compare"));
+	}
+	let timezone = style.timezone(places[0].1.timezone());
+	let times: Vec<_> = places[0].1.hourly.time[..hours]
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let temps = places.each_ref().map(|(_, result)| {
+		result.hourly.temperature_2m[..hours]
+			.iter()
+			.copied()
+			.map(convert_num)
+			.collect::<Vec<_>>()
+	});
+
+	let temp_range = temps
+		.iter()
+		.flatten()
+		.copied()
+		.minmax()
+		.into_option()
+		.unwrap_or((0, 0));
+	let chart_temp_range = previous_and_next_multiple(Range::new(temp_range.0, temp_range.1), 4);
+
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	});
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: 3,
+	});
+	let label = TextBox::new(
+		&[
+			TextSegment::new(&places[0].0, colours[0]),
+			TextSegment::white(" and "),
+			TextSegment::new(&places[1].0, colours[1]),
+			TextSegment::white(&format!(
+				" temperatures ({})",
+				style.units.temperature_symbol()
+			)),
+		],
+		header_font.clone(),
+		style.font_size(LABEL_SIZE),
+		(hours - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		hours,
+		chart_temp_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(2, 4),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: chart_temp_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	for (colour, temps) in colours.into_iter().zip(temps) {
+		chart.draw(Line {
+			colour,
+			data: temps.into_iter(),
+			max: chart_temp_range.end(),
+		});
+	}
+	Ok(make_png(chart.into_canvas()))
+}
+
+pub fn create_compare() -> CreateCommand {
+	i18n::command(
+		"compare",
+		"The hourly temperature of two places on one chart",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The first place to compare.",
+		)
+		.required(true)
+		.set_autocomplete(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"other_place",
+			"The place to compare it with.",
+		)
+		.required(true)
+		.set_autocomplete(true),
+	)
+	.add_option(create_theme_option())
+	.add_option(create_scale_option())
+	.add_option(create_private_option())
+}
+
+/// The precipitation so far at the end of every hour, starting from zero.
+fn running_totals(amounts: &[f32]) -> Vec<f32> {
+	amounts
+		.iter()
+		.scan(0.0, |total, &amount| {
+			*total += amount;
+			Some(*total)
+		})
+		.collect()
+}
+
+/// The height the accumulation chart aims for, in pixels, whatever the total.
+const ACCUMULATION_CHART_HEIGHT: u32 = 160;
+
+/// How much precipitation adds up to over the coming hours, as one rising line.
+pub async fn handle_precip_accumulation(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let horizon = horizon_option(interaction, "hours", MAX_FORECAST_HOURS)?;
+	let style = Style {
+		horizon,
+		..Style::from_interaction(interaction, database).await?
+	};
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let hours = horizon.unwrap_or(DEFAULT_FORECAST_HOURS);
+	let result = HourlyResult::get(location.coordinates(), 0, hours, style.units, &client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	if result.hourly.precipitation.len() != result.hourly.time.len() {
+		return Err(Error::friendly(
+			"No precipitation forecast for this location",
+		));
+	}
+	let timezone = style.timezone(result.timezone());
+	let image = draw_accumulation(result, style, font, header_font);
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!(
+					"Precipitation adding up over the next {hours} hours in {location}. Times are in {timezone}."
+				))
+				.add_file(CreateAttachment::bytes(image, "accumulation.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+fn draw_accumulation(
+	result: HourlyResult,
+	style: Style,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let palette = style.theme.palette();
+	let units = style.units;
+	let timezone = style.timezone(result.timezone());
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let totals = running_totals(&result.hourly.precipitation);
+	let total = totals.last().copied().unwrap_or(0.0);
+
+	// At least one unit high, so a dry forecast still gets a chart.
+	let range = Range::new(
+		0,
+		next_multiple(convert_num(units.chart_precipitation(total)), 1).max(100),
+	);
+	let range_units = range.len() as u32 / 100;
+	// Scaled so a downpour doesn't make the chart run off the screen, marked every tenth of the range or so.
+	let spacing = style.spacing(Spacing {
+		horizontal: 8,
+		vertical: (ACCUMULATION_CHART_HEIGHT / range_units).clamp(1, 16),
+	});
+	let mark_interval = (range_units / 10).max(1);
+	let padding = style.padding(Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	});
+
+	let total_text = format!(
+		" ({}), {:.1} {} in all",
+		units.chart_precipitation_label(),
+		total,
+		units.precipitation_symbol()
+	);
+	let label = style.layout.header(
+		&[
+			TextSegment::white("Running total of "),
+			TextSegment::new("precipitation", Rgb(palette.precipitation)),
+			TextSegment::white(&total_text),
+		],
+		header_font,
+		style.font_size(LABEL_SIZE),
+		(times.len() - 1) as u32 * spacing.horizontal,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + header_height(&label),
+			..padding
+		},
+	);
+	if let Some(label) = label {
+		chart.draw(label);
+	}
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(mark_interval, mark_interval),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: style.font_size(AXIS_LABEL_SIZE),
+	});
+	chart.draw(Line {
+		colour: Rgb(palette.precipitation),
+		data: totals
+			.into_iter()
+			.map(|total| convert_num(units.chart_precipitation(total))),
+		max: range.end(),
+	});
+	make_png(chart.into_canvas())
+}
+
+pub fn create_precip_accumulation() -> CreateCommand {
+	i18n::command(
+		"precip_accumulation",
+		"How much precipitation adds up to over the coming hours",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_theme_option())
+	.add_option(create_layout_option())
+	.add_option(create_horizon_option(
+		"hours",
+		"How many hours ahead to add up (48 by default).",
+		MAX_FORECAST_HOURS,
+	))
+	.add_option(create_scale_option())
+	.add_option(create_private_option())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TemperatureExtreme {
+	Hottest,
+	Coldest,
+}
+
+/// Positions of the lowest and the highest value. With ties, the earliest one wins.
+fn first_min_max_positions(values: &[f32]) -> Option<(usize, usize)> {
+	let mut positions = None;
+	for (index, &value) in values.iter().enumerate() {
+		let (min, max) = positions.get_or_insert((index, index));
+		if value < values[*min] {
+			*min = index;
+		}
+		if value > values[*max] {
+			*max = index;
+		}
+	}
+	positions
+}
+
+/// Reply with the hottest or coldest hour of the next 48 hours.
+pub async fn handle_extreme_hour(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	extreme: TemperatureExtreme,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let home_timezone = home_timezone::get_for_user(database, interaction.user.id).await?;
+	let result = HourlyResult::get(
+		location.coordinates(),
+		0,
+		DEFAULT_FORECAST_HOURS,
+		units,
+		&client,
+	)
+	.await?;
+	let (coldest, hottest) = first_min_max_positions(&result.hourly.temperature_2m)
+		.ok_or_else(|| Error::friendly("No hourly forecast for this location"))?;
+	let (index, description) = match extreme {
+		TemperatureExtreme::Hottest => (hottest, "Hottest"),
+		TemperatureExtreme::Coldest => (coldest, "Coldest"),
+	};
+	let time = result.hourly.time[index];
+	let timezone = home_timezone.map_or(result.timezone(), ForecastTimezone::Named);
+	let day = timezone.local(time).format("%A");
+	let feels_like = result
+		.hourly
+		.apparent_temperature
+		.get(index)
+		.map(|apparent| format!(" (feels like {apparent:.1}{})", units.temperature_symbol()))
+		.unwrap_or_default();
+	let message = format!(
+		"{} hour in the next 48 hours: {} {:02}:00 ({}), at {:.1}{}{}.",
+		description,
+		day,
+		hour_from_timestamp(time, timezone),
+		timezone,
+		result.hourly.temperature_2m[index],
+		units.temperature_symbol(),
+		feels_like
+	);
+	interaction.public_reply(&context.http, message).await?;
+	Ok(())
+}
+
+pub fn create_hottest_hour() -> CreateCommand {
+	i18n::command("hottest_hour", "The hottest hour of the next 48 hours")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+pub fn create_coldest_hour() -> CreateCommand {
+	i18n::command("coldest_hour", "The coldest hour of the next 48 hours")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn min_max_positions() {
+		assert_eq!(first_min_max_positions(&[3.0, 1.0, 5.0, 2.0]), Some((1, 2)));
+		assert_eq!(first_min_max_positions(&[]), None);
+	}
+	#[test]
+	fn min_max_ties_pick_earliest() {
+		assert_eq!(first_min_max_positions(&[4.0, 1.0, 4.0, 1.0]), Some((1, 0)));
+	}
+	#[test]
+	fn precipitation_accumulates() {
+		assert_eq!(
+			running_totals(&[0.0, 1.5, 0.0, 2.5, 1.0]),
+			vec![0.0, 1.5, 1.5, 4.0, 5.0]
+		);
+		assert_eq!(running_totals(&[]), Vec::<f32>::new());
+	}
+	#[test]
+	fn days_start_at_local_midnight() {
+		// From 21:00 UTC on 2024-03-29, 22:00 in Amsterdam.
+		let start = 1_711_746_000;
+		let times: Vec<i64> = (0..30).map(|hour| start + hour * 3600).collect();
+		let amsterdam = ForecastTimezone::Named(chrono_tz::Tz::Europe__Amsterdam);
+		assert_eq!(day_boundaries(&times, amsterdam), vec![2, 26]);
+		let utc = ForecastTimezone::Fixed(chrono::FixedOffset::east_opt(0).unwrap());
+		assert_eq!(day_boundaries(&times, utc), vec![3, 27]);
+		assert_eq!(
+			day_separators(&[2], 4, 100).collect::<Vec<_>>(),
+			vec![0, 0, 100, 0]
+		);
+	}
+	#[test]
+	fn thunderstorms_marked() {
+		// Clear, thunderstorm, rain, thunderstorm with slight and with heavy hail.
+		assert_eq!(
+			thunderstorm_hours(&[0, 95, 61, 96, 99]),
+			Some(vec![false, true, false, true, true])
+		);
+		// Heavy rain showers aren't a thunderstorm, so there is nothing to mark.
+		assert_eq!(thunderstorm_hours(&[0, 3, 82]), None);
+		assert_eq!(thunderstorm_hours(&[]), None);
+	}
+	#[test]
+	fn gaps_leave_series_empty() {
+		let hourly: HourlyWeather = serde_json::from_str(
+			r#"{
+				"time": [0, 3600],
+				"temperature_2m": [11.0, null],
+				"relative_humidity_2m": [80, 82],
+				"precipitation_probability": null,
+				"precipitation": [0.0, 0.4],
+				"showers": [0.0, null],
+				"cloud_cover_low": null,
+				"weather_code": [3, 61],
+				"wind_gusts_10m": [30.0, 35.0]
+			}"#,
+		)
+		.unwrap();
+		// Left out, sent as null, or with a gap, the series is dropped rather than the forecast.
+		assert!(hourly.temperature_2m.is_empty());
+		assert!(hourly.apparent_temperature.is_empty());
+		assert!(hourly.precipitation_probability.is_empty());
+		assert!(hourly.wind_speed_10m.is_empty());
+		assert_eq!(hourly.relative_humidity_2m, vec![80, 82]);
+		assert_eq!(hourly.precipitation, vec![0.0, 0.4]);
+		assert!(hourly.showers.is_empty());
+		assert!(hourly.cloud_cover_low.is_empty());
+		assert_eq!(hourly.weather_code, vec![3, 61]);
+	}
+}
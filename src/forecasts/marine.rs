@@ -0,0 +1,278 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::{GradientPoint, MultiPointGradient, Range},
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, GradientBars, HorizontalLines, Line, Rgb},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	render::check_forecast_data,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	util::{convert_num, ForecastTimezone},
+};
+
+/// From calm to rough seas.
+const WAVE_GRADIENT: [[u8; 3]; 3] = [[0, 148, 255], [255, 255, 33], [255, 0, 33]];
+const SWELL: Rgb = Rgb([255, 255, 255]);
+const PERIOD: Rgb = Rgb([0, 255, 33]);
+
+const HOURS: u32 = 48;
+
+#[derive(Debug, Deserialize)]
+struct MarineHourly {
+	time: Vec<i64>,
+	wave_height: Vec<Option<f32>>,
+	wave_period: Vec<Option<f32>>,
+	swell_wave_height: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs/marine-weather-api
+#[derive(Debug, Deserialize)]
+struct MarineResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: MarineHourly,
+}
+
+impl MarineResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://marine-api.open-meteo.com/v1/marine")
+			.query(&[("hourly", "wave_height")])
+			.query(&[("hourly", "wave_period")])
+			.query(&[("hourly", "swell_wave_height")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl MarineHourly {
+	/// Whether there is any wave data at all, which there isn't for coordinates away from the sea.
+	fn has_waves(&self) -> bool {
+		self.wave_height.iter().any(Option::is_some)
+	}
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_marine(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = MarineResult::get(location.coordinates(), &client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	if !result.hourly.has_waves() {
+		return Err(Error::friendly("No marine data for this location"));
+	}
+	let image = draw_marine(result, font, header_font);
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!(
+					"Marine forecast for {}.",
+					location.short_description()
+				))
+				.add_file(CreateAttachment::bytes(image, "marine.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the wave height with the swell part of it marked, and the wave period below it. Missing hours are drawn as zero.
+fn draw_marine(
+	result: MarineResult,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let readings = |values: Vec<Option<f32>>| -> Vec<i32> {
+		values
+			.into_iter()
+			.map(|value| convert_num(value.unwrap_or(0.0)))
+			.collect()
+	};
+	let heights = readings(result.hourly.wave_height);
+	let swells = readings(result.hourly.swell_wave_height);
+	let periods = readings(result.hourly.wave_period);
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 3,
+	};
+
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 30,
+	};
+	let height_range = Range::new(
+		0,
+		next_multiple(heights.iter().chain(&swells).copied().max().unwrap_or(0), 1),
+	);
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Wave height", Rgb(WAVE_GRADIENT[0])),
+			TextSegment::white(" (and "),
+			TextSegment::new("swell", SWELL),
+			TextSegment::white(") in metres"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		times.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len() + 1,
+		height_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 1),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: height_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(GradientBars {
+		gradient: MultiPointGradient::new(vec![
+			GradientPoint::from_rgb(padding.below, WAVE_GRADIENT[0]),
+			GradientPoint::from_rgb(padding.below + spacing.vertical * 2, WAVE_GRADIENT[1]),
+			GradientPoint::from_rgb(padding.below + spacing.vertical * 4, WAVE_GRADIENT[2]),
+		]),
+		data: heights.into_iter(),
+	});
+	chart.draw(HorizontalLines {
+		colour: SWELL,
+		data: swells.into_iter(),
+	});
+	let height_image = chart.into_canvas();
+
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 8,
+	};
+	let period_range = Range::new(0, next_multiple(*periods.iter().max().unwrap_or(&0), 5));
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Wave period", PERIOD),
+			TextSegment::white(" in seconds"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		(times.len() - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		period_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 5),
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: period_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(Line {
+		colour: PERIOD,
+		data: periods.into_iter(),
+		max: period_range.end(),
+	});
+	let period_image = chart.into_canvas();
+
+	make_png(composite(&[height_image, period_image]))
+}
+
+pub fn create_marine() -> CreateCommand {
+	i18n::command("marine", "Wave height and period for the next two days")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the marine forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn inland_has_no_waves() {
+		let inland = MarineHourly {
+			time: vec![0, 3600],
+			wave_height: vec![None, None],
+			wave_period: vec![None, None],
+			swell_wave_height: vec![None, None],
+		};
+		assert!(!inland.has_waves());
+		let coast = MarineHourly {
+			wave_height: vec![None, Some(0.4)],
+			..inland
+		};
+		assert!(coast.has_waves());
+	}
+}
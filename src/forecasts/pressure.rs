@@ -0,0 +1,294 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb},
+	text_box::{TextBox, TextSegment},
+	util::{make_png, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, complete_series, RequestBuilderExt},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	util::{convert_num, ForecastTimezone},
+};
+
+const SEA_LEVEL: Rgb = Rgb([0, 148, 255]);
+const SURFACE: Rgb = Rgb([214, 158, 46]);
+
+const HOURS: u32 = 48;
+/// Meteorologists call the change in pressure over three hours the pressure tendency.
+const TENDENCY_HOURS: usize = 3;
+/// The most hPa between the lowest and highest pressure to still draw on the detailed scale.
+const WIDE_RANGE: i32 = 40;
+
+#[derive(Debug, Deserialize)]
+struct PressureHourly {
+	time: Vec<i64>,
+	/// Empty if Open-Meteo has no complete series for it, like surface pressure. A line without data is left out.
+	#[serde(default, deserialize_with = "complete_series")]
+	pressure_msl: Vec<f32>,
+	#[serde(default, deserialize_with = "complete_series")]
+	surface_pressure: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PressureResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: PressureHourly,
+}
+
+impl PressureResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[("hourly", "pressure_msl")])
+			.query(&[("hourly", "surface_pressure")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// How much the pressure changes over the first few hours, if there are enough of them.
+fn tendency(pressures: &[f32]) -> Option<f32> {
+	Some(pressures.get(TENDENCY_HOURS)? - pressures.first()?)
+}
+
+/// Describe the pressure tendency the way a forecaster would, noting a fast fall as a sign of a storm.
+fn describe_tendency(change: f32) -> String {
+	let amount = change.abs();
+	if amount < 0.5 {
+		return format!("steady over the next {TENDENCY_HOURS} hours");
+	}
+	let direction = if change > 0.0 { "rise" } else { "fall" };
+	let note = if change <= -6.0 {
+		", which often means a storm is coming"
+	} else {
+		""
+	};
+	format!("expected to {direction} by {amount:.1} hPa over the next {TENDENCY_HOURS} hours{note}")
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_pressure(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = PressureResult::get(location.coordinates(), &client).await?;
+	let mut content = format!("Pressure forecast for {}.", location.short_description());
+	if let Some(change) = tendency(&result.hourly.pressure_msl) {
+		content += &format!(" Sea-level pressure is {}.", describe_tendency(change));
+	}
+	let image = draw_pressure(result, font, header_font)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(content)
+				.add_file(CreateAttachment::bytes(image, "pressure.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw sea-level and surface pressure as two lines, on an axis spanning just the pressures forecast rather than starting from zero.
+fn draw_pressure(
+	result: PressureResult,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let hours = result.hourly.time.len();
+	if hours < 2
+		|| (result.hourly.pressure_msl.len() != hours
+			&& result.hourly.surface_pressure.len() != hours)
+	{
+		return Err(Error::friendly(
+			"No pressure forecast is available for this location.",
+		));
+	}
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let sea_level: Vec<_> = result
+		.hourly
+		.pressure_msl
+		.into_iter()
+		.map(convert_num)
+		.collect();
+	let surface: Vec<_> = result
+		.hourly
+		.surface_pressure
+		.into_iter()
+		.map(convert_num)
+		.collect();
+
+	let pressure_range = sea_level
+		.iter()
+		.chain(&surface)
+		.copied()
+		.minmax()
+		.into_option()
+		.unwrap_or((0, 0));
+	// Surface pressure is far below sea-level pressure in the mountains, so a wide range gets a more compact scale.
+	let wide = pressure_range.1 - pressure_range.0 > WIDE_RANGE * 100;
+	let (vertical_spacing, vertical_intervals, multiple) = if wide {
+		(1, MarkIntervals::new(5, 10), 10)
+	} else {
+		(4, MarkIntervals::new(1, 5), 5)
+	};
+	let chart_range =
+		previous_and_next_multiple(Range::new(pressure_range.0, pressure_range.1), multiple);
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 27,
+		right: 3,
+	};
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: vertical_spacing,
+	};
+	let segments = match (sea_level.is_empty(), surface.is_empty()) {
+		(false, false) => vec![
+			TextSegment::new("Sea-level", SEA_LEVEL),
+			TextSegment::white(" and "),
+			TextSegment::new("surface", SURFACE),
+		],
+		(false, true) => vec![TextSegment::new("Sea-level", SEA_LEVEL)],
+		(true, _) => vec![TextSegment::new("Surface", SURFACE)],
+	};
+	let label = TextBox::new(
+		&segments
+			.into_iter()
+			.chain([TextSegment::white(" pressure (hPa)")])
+			.collect::<Vec<_>>(),
+		header_font.clone(),
+		LABEL_SIZE,
+		(times.len() - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		chart_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals,
+		horizontal_intervals: MarkIntervals::new(1, 2),
+		vertical_label_range: chart_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(Line {
+		colour: SURFACE,
+		data: surface.into_iter(),
+		max: chart_range.end(),
+	});
+	chart.draw(Line {
+		colour: SEA_LEVEL,
+		data: sea_level.into_iter(),
+		max: chart_range.end(),
+	});
+	Ok(make_png(chart.into_canvas()))
+}
+
+pub fn create_pressure() -> CreateCommand {
+	i18n::command(
+		"pressure",
+		"Sea-level and surface air pressure for the next two days",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the pressure forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tendency_over_three_hours() {
+		assert_eq!(
+			tendency(&[1012.0, 1011.0, 1010.0, 1009.0, 1000.0]),
+			Some(-3.0)
+		);
+		assert_eq!(tendency(&[1012.0, 1011.0, 1010.0]), None);
+	}
+	#[test]
+	fn tendency_described() {
+		assert_eq!(describe_tendency(0.3), "steady over the next 3 hours");
+		assert_eq!(
+			describe_tendency(2.0),
+			"expected to rise by 2.0 hPa over the next 3 hours"
+		);
+		assert!(describe_tendency(-7.0).ends_with("storm is coming"));
+	}
+	#[test]
+	fn gaps_leave_series_empty() {
+		let hourly: PressureHourly = serde_json::from_str(
+			r#"{
+				"time": [0, 3600],
+				"pressure_msl": [1012.0, 1011.5],
+				"surface_pressure": [null, 870.0]
+			}"#,
+		)
+		.unwrap();
+		assert_eq!(hourly.pressure_msl, vec![1012.0, 1011.5]);
+		assert!(hourly.surface_pressure.is_empty());
+	}
+}
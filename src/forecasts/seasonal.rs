@@ -0,0 +1,292 @@
+use std::{
+	sync::{Arc, LazyLock},
+	time::Duration,
+};
+
+use ab_glyph::{FontRef, PxScale};
+use chrono::{DateTime, FixedOffset};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Rgb, SolidBars},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	cache::{coordinates_key, TtlCache},
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	util::convert_num,
+};
+
+const WARMER: Rgb = Rgb([255, 0, 0]);
+const COLDER: Rgb = Rgb([0, 148, 255]);
+const WETTER: Rgb = Rgb([0, 148, 255]);
+const DRIER: Rgb = Rgb([214, 158, 46]);
+
+#[derive(Debug, Deserialize)]
+struct SeasonalMonthly {
+	time: Vec<i64>,
+	temperature_2m_anomaly: Vec<Option<f32>>,
+	precipitation_anomaly: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SeasonalResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	monthly: SeasonalMonthly,
+}
+
+/// The seasonal forecast is only rerun about once a month, so it can be cached for a long time.
+static SEASONAL_CACHE: LazyLock<TtlCache<(i32, i32), Arc<SeasonalResult>>> =
+	LazyLock::new(|| TtlCache::new(Duration::from_secs(24 * 60 * 60)));
+
+impl SeasonalResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Arc<Self>, Error> {
+		let key = coordinates_key(coordinates);
+		if let Some(result) = SEASONAL_CACHE.get(&key) {
+			return Ok(result);
+		}
+		let result = client
+			.get("https://seasonal-api.open-meteo.com/v1/seasonal")
+			.query(&[
+				("monthly", "temperature_2m_anomaly"),
+				("monthly", "precipitation_anomaly"),
+				("timeformat", "unixtime"),
+				("timezone", "auto"),
+			])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo::<SeasonalResult>()
+			.await?;
+		let result = Arc::new(result);
+		SEASONAL_CACHE.insert(key, result.clone());
+		Ok(result)
+	}
+}
+
+/// Split anomalies into the magnitudes above and below normal, so they can be drawn as two sets of bars in different colours.
+fn split_anomalies(anomalies: &[f32]) -> (Vec<i32>, Vec<i32>) {
+	anomalies
+		.iter()
+		.map(|&anomaly| {
+			(
+				convert_num(anomaly.max(0.0)),
+				convert_num((-anomaly).max(0.0)),
+			)
+		})
+		.unzip()
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_seasonal(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let image = render_seasonal(location.coordinates(), &client, font, header_font).await?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new().ephemeral(private)
+				.content("Seasonal outlook: deviation from normal for the coming months. This is a low-confidence, coarse-resolution outlook, not a forecast for any particular day.")
+				.add_file(CreateAttachment::bytes(image, "seasonal.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Fetch the seasonal outlook for the coordinates and draw its anomalies as a PNG.
+async fn render_seasonal(
+	coordinates: Coordinates,
+	client: &Client,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let result = SeasonalResult::get(coordinates, client).await?;
+	let offset = FixedOffset::east_opt(result.utc_offset_seconds)
+		.ok_or_else(|| Error::custom_unfriendly("Invalid UTC offset"))?;
+
+	let mut months = Vec::new();
+	let mut temperature_anomalies = Vec::new();
+	let mut precipitation_anomalies = Vec::new();
+	for ((&time, temperature), precipitation) in result
+		.monthly
+		.time
+		.iter()
+		.zip(&result.monthly.temperature_2m_anomaly)
+		.zip(&result.monthly.precipitation_anomaly)
+	{
+		let (Some(temperature), Some(precipitation)) = (temperature, precipitation) else {
+			continue;
+		};
+		let Some(month) = DateTime::from_timestamp(time, 0) else {
+			continue;
+		};
+		months.push(month.with_timezone(&offset).format("%b").to_string());
+		temperature_anomalies.push(*temperature);
+		precipitation_anomalies.push(*precipitation);
+	}
+	if months.is_empty() {
+		return Err(Error::friendly(
+			"No seasonal outlook is available for this location.",
+		));
+	}
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 9,
+	};
+
+	let (warmer, colder) = split_anomalies(&temperature_anomalies);
+	let temperature_range = Range::new(
+		0,
+		next_multiple(*warmer.iter().chain(&colder).max().unwrap_or(&0), 1),
+	);
+	let spacing = Spacing {
+		horizontal: 30,
+		vertical: 20,
+	};
+	let label = TextBox::new(
+		&[
+			TextSegment::white("Temperature anomaly (°C): "),
+			TextSegment::new("warmer", WARMER),
+			TextSegment::white(" or "),
+			TextSegment::new("colder", COLDER),
+			TextSegment::white(" than normal"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		months.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		months.len() + 1,
+		temperature_range.end() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 1),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: temperature_range,
+		horizontal_labels: months.iter(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(SolidBars {
+		colour: WARMER,
+		data: warmer.into_iter(),
+	});
+	chart.draw(SolidBars {
+		colour: COLDER,
+		data: colder.into_iter(),
+	});
+	let temperature_image = chart.into_canvas();
+
+	let (wetter, drier) = split_anomalies(&precipitation_anomalies);
+	let precipitation_range = Range::new(
+		0,
+		next_multiple(*wetter.iter().chain(&drier).max().unwrap_or(&0), 5),
+	);
+	let spacing = Spacing {
+		horizontal: 30,
+		vertical: 1,
+	};
+	let label = TextBox::new(
+		&[
+			TextSegment::white("Precipitation anomaly (mm): "),
+			TextSegment::new("wetter", WETTER),
+			TextSegment::white(" or "),
+			TextSegment::new("drier", DRIER),
+			TextSegment::white(" than normal"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		months.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		months.len() + 1,
+		precipitation_range.end() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(25, 25),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: precipitation_range,
+		horizontal_labels: months.iter(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(SolidBars {
+		colour: WETTER,
+		data: wetter.into_iter(),
+	});
+	chart.draw(SolidBars {
+		colour: DRIER,
+		data: drier.into_iter(),
+	});
+	let precipitation_image = chart.into_canvas();
+
+	let composite = composite(&[temperature_image, precipitation_image]);
+	Ok(make_png(composite))
+}
+
+pub fn create_seasonal() -> CreateCommand {
+	i18n::command(
+		"seasonal",
+		"Low-confidence outlook of temperature and precipitation for the coming months",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the seasonal outlook of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_private_option())
+}
@@ -0,0 +1,339 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb, SolidBars},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	render::check_forecast_data,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	util::{convert_num, ForecastTimezone},
+};
+
+const SNOWFALL: Rgb = Rgb([255, 255, 255]);
+const SNOW_DEPTH: Rgb = Rgb([118, 215, 234]);
+const FREEZING_LEVEL: Rgb = Rgb([0, 148, 255]);
+
+const HOURS: u32 = 72;
+
+#[derive(Debug, Deserialize)]
+struct SnowHourly {
+	time: Vec<i64>,
+	/// In centimetres.
+	snowfall: Vec<Option<f32>>,
+	/// In metres.
+	snow_depth: Vec<Option<f32>>,
+	/// In metres. Missing where no model covers it.
+	freezing_level_height: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs
+#[derive(Debug, Deserialize)]
+struct SnowResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: SnowHourly,
+}
+
+impl SnowResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[("hourly", "snowfall")])
+			.query(&[("hourly", "snow_depth")])
+			.query(&[("hourly", "freezing_level_height")])
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl SnowHourly {
+	/// Whether any snow is forecast to fall at all, which it isn't in summer or in the tropics.
+	fn has_snowfall(&self) -> bool {
+		self.snowfall
+			.iter()
+			.any(|snowfall| snowfall.is_some_and(|snowfall| snowfall > 0.0))
+	}
+	/// The freezing level of every hour in kilometres, if every hour has one.
+	fn freezing_levels(&self) -> Option<Vec<f32>> {
+		self.freezing_level_height
+			.iter()
+			.map(|height| height.map(|height| height / 1000.0))
+			.collect()
+	}
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_snow(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = SnowResult::get(location.coordinates(), &client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	let place = location.short_description();
+	let message = if result.hourly.has_snowfall() {
+		CreateInteractionResponseFollowup::new()
+			.ephemeral(private)
+			.content(format!("Snow forecast for {place}."))
+			.add_file(CreateAttachment::bytes(
+				draw_snow(result, font, header_font),
+				"snow.png",
+			))
+	} else {
+		CreateInteractionResponseFollowup::new()
+			.ephemeral(private)
+			.content(format!(
+				"No snow forecast for {place} in the next {} days.",
+				HOURS / 24
+			))
+	};
+	interaction.create_followup(context, message).await?;
+	Ok(())
+}
+
+/// Draw the snowfall as bars and the snow depth below it, and then the freezing level if it is known for every hour. Missing hours of snowfall and snow depth are drawn as zero.
+fn draw_snow(
+	result: SnowResult,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	let freezing_levels = result.hourly.freezing_levels();
+	let snowfall: Vec<_> = result
+		.hourly
+		.snowfall
+		.into_iter()
+		.map(|snowfall| convert_num(snowfall.unwrap_or(0.0)))
+		.collect();
+	// In centimetres, like the snowfall.
+	let depths: Vec<_> = result
+		.hourly
+		.snow_depth
+		.into_iter()
+		.map(|depth| convert_num(depth.unwrap_or(0.0) * 100.0))
+		.collect();
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 27,
+		right: 3,
+	};
+
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 20,
+	};
+	let snowfall_range = Range::new(0, next_multiple(*snowfall.iter().max().unwrap_or(&0), 1));
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Snowfall", SNOWFALL),
+			TextSegment::white(" in centimetres per hour"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		times.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len() + 1,
+		snowfall_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 1),
+		horizontal_intervals: MarkIntervals::new(1, 3),
+		vertical_label_range: snowfall_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(SolidBars {
+		colour: SNOWFALL,
+		data: snowfall.into_iter(),
+	});
+	let mut images = vec![chart.into_canvas()];
+
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 2,
+	};
+	// At least ten centimetres high, so a thin layer doesn't fill the whole panel.
+	let depth_range = Range::new(
+		0,
+		next_multiple(*depths.iter().max().unwrap_or(&0), 10).max(10 * 100),
+	);
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Snow depth", SNOW_DEPTH),
+			TextSegment::white(" in centimetres"),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		(times.len() - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		depth_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(10, 20),
+		horizontal_intervals: MarkIntervals::new(1, 3),
+		vertical_label_range: depth_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(Line {
+		colour: SNOW_DEPTH,
+		data: depths.into_iter(),
+		max: depth_range.end(),
+	});
+	images.push(chart.into_canvas());
+
+	if let Some(levels) = freezing_levels {
+		let levels: Vec<_> = levels.into_iter().map(convert_num).collect();
+		let (&min, &max) = levels.iter().minmax().into_option().unwrap_or((&0, &0));
+		let level_range = previous_and_next_multiple(Range::new(min, max), 1);
+
+		let spacing = Spacing {
+			horizontal: 8,
+			vertical: 30,
+		};
+		let label = TextBox::new(
+			&[
+				TextSegment::new("Freezing level", FREEZING_LEVEL),
+				TextSegment::white(" in kilometres"),
+			],
+			header_font.clone(),
+			LABEL_SIZE,
+			(times.len() - 1) as u32 * spacing.horizontal,
+			2,
+		);
+		let mut chart = Chart::new(
+			times.len(),
+			level_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + label.height(),
+				..padding
+			},
+		);
+		chart.draw(label);
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 1),
+			horizontal_intervals: MarkIntervals::new(1, 3),
+			vertical_label_range: level_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: AXIS_LABEL_SIZE,
+		});
+		chart.draw(Line {
+			colour: FREEZING_LEVEL,
+			data: levels.into_iter(),
+			max: level_range.end(),
+		});
+		images.push(chart.into_canvas());
+	}
+
+	make_png(composite(&images))
+}
+
+pub fn create_snow() -> CreateCommand {
+	i18n::command(
+		"snow",
+		"Snowfall, snow depth and freezing level for the next three days",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the snow forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn snowfall_needs_more_than_zero() {
+		let summer = SnowHourly {
+			time: vec![0, 3600],
+			snowfall: vec![Some(0.0), None],
+			snow_depth: vec![Some(0.4), Some(0.4)],
+			freezing_level_height: vec![Some(3900.0), Some(4100.0)],
+		};
+		assert!(!summer.has_snowfall());
+		assert_eq!(summer.freezing_levels(), Some(vec![3.9, 4.1]));
+		let winter = SnowHourly {
+			snowfall: vec![Some(0.0), Some(0.7)],
+			freezing_level_height: vec![Some(600.0), None],
+			..summer
+		};
+		assert!(winter.has_snowfall());
+		assert_eq!(winter.freezing_levels(), None);
+	}
+}
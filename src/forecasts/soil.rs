@@ -0,0 +1,417 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb},
+	text_box::{TextBox, TextSegment},
+	util::{composite, make_png, next_multiple, previous_and_next_multiple},
+};
+use itertools::Itertools;
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::hourly::hour_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	render::check_forecast_data,
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+	units::UnitSystem,
+	util::{convert_num, CommandInteractionExt, ForecastTimezone},
+};
+
+/// One colour per moisture layer, from the surface down.
+const LAYER_COLOURS: [Rgb; 5] = [
+	Rgb([118, 215, 234]),
+	Rgb([0, 148, 255]),
+	Rgb([0, 255, 33]),
+	Rgb([255, 216, 0]),
+	Rgb([200, 120, 60]),
+];
+/// The moisture layers, with their depths for the header.
+const MOISTURE_LAYERS: [&str; 5] = ["0-1", "1-3", "3-9", "9-27", "27-81"];
+/// The depths soil temperature is measured at, each with the colour of the moisture layer it lies in.
+const TEMPERATURE_DEPTHS: [(&str, Rgb); 4] = [
+	("0", LAYER_COLOURS[0]),
+	("6", LAYER_COLOURS[2]),
+	("18", LAYER_COLOURS[3]),
+	("54", LAYER_COLOURS[4]),
+];
+
+const HOURS: u32 = 72;
+
+#[derive(Debug, Deserialize)]
+struct SoilHourly {
+	time: Vec<i64>,
+	/// In m³/m³, like the rest of the moisture layers.
+	soil_moisture_0_to_1cm: Vec<Option<f32>>,
+	soil_moisture_1_to_3cm: Vec<Option<f32>>,
+	soil_moisture_3_to_9cm: Vec<Option<f32>>,
+	soil_moisture_9_to_27cm: Vec<Option<f32>>,
+	soil_moisture_27_to_81cm: Vec<Option<f32>>,
+	/// In the user's temperature unit, like the rest of the temperature depths. Only asked for when the temperature panel is.
+	#[serde(default)]
+	soil_temperature_0cm: Vec<Option<f32>>,
+	#[serde(default)]
+	soil_temperature_6cm: Vec<Option<f32>>,
+	#[serde(default)]
+	soil_temperature_18cm: Vec<Option<f32>>,
+	#[serde(default)]
+	soil_temperature_54cm: Vec<Option<f32>>,
+}
+
+/// https://open-meteo.com/en/docs
+#[derive(Debug, Deserialize)]
+struct SoilResult {
+	#[serde(rename = "latitude")]
+	_latitude: f32,
+	#[serde(rename = "longitude")]
+	_longitude: f32,
+	utc_offset_seconds: i32,
+	timezone: String,
+	hourly: SoilHourly,
+}
+
+impl SoilResult {
+	async fn get(
+		coordinates: Coordinates,
+		with_temperature: bool,
+		units: UnitSystem,
+		client: &Client,
+	) -> Result<Self, Error> {
+		let mut request = client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[
+				("hourly", "soil_moisture_0_to_1cm"),
+				("hourly", "soil_moisture_1_to_3cm"),
+				("hourly", "soil_moisture_3_to_9cm"),
+				("hourly", "soil_moisture_9_to_27cm"),
+				("hourly", "soil_moisture_27_to_81cm"),
+			]);
+		if with_temperature {
+			request = request
+				.query(&[
+					("hourly", "soil_temperature_0cm"),
+					("hourly", "soil_temperature_6cm"),
+					("hourly", "soil_temperature_18cm"),
+					("hourly", "soil_temperature_54cm"),
+				])
+				.query(&units.query());
+		}
+		request
+			.query(&[("timeformat", "unixtime"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+impl SoilHourly {
+	/// Whether there is any moisture data at all, which there isn't for coordinates out at sea.
+	fn has_soil(&self) -> bool {
+		self.moisture_layers()
+			.iter()
+			.any(|layer| layer.iter().any(Option::is_some))
+	}
+	fn moisture_layers(&self) -> [&[Option<f32>]; 5] {
+		[
+			&self.soil_moisture_0_to_1cm,
+			&self.soil_moisture_1_to_3cm,
+			&self.soil_moisture_3_to_9cm,
+			&self.soil_moisture_9_to_27cm,
+			&self.soil_moisture_27_to_81cm,
+		]
+	}
+	fn temperature_layers(&self) -> [&[Option<f32>]; 4] {
+		[
+			&self.soil_temperature_0cm,
+			&self.soil_temperature_6cm,
+			&self.soil_temperature_18cm,
+			&self.soil_temperature_54cm,
+		]
+	}
+}
+
+/// Every hour of a layer, if every hour has a value.
+fn complete(layer: &[Option<f32>]) -> Option<Vec<f32>> {
+	if layer.is_empty() {
+		return None;
+	}
+	layer.iter().copied().collect()
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 18.0, y: 18.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_soil(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let with_temperature = interaction.bool_option("temperature").unwrap_or(false);
+	let units = UnitSystem::get_for_user(database, interaction.user.id).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = SoilResult::get(location.coordinates(), with_temperature, units, &client).await?;
+	check_forecast_data(&result.hourly.time)?;
+	if !result.hourly.has_soil() {
+		return Err(Error::friendly("No soil data for this location"));
+	}
+	let image = draw_soil(result, units, font, header_font);
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!(
+					"Soil forecast for {}.",
+					location.short_description()
+				))
+				.add_file(CreateAttachment::bytes(image, "soil.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the moisture of each soil layer, and below it the temperature at each depth if it was asked for. Layers missing any hours are left out.
+fn draw_soil(
+	result: SoilResult,
+	units: UnitSystem,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let times: Vec<_> = result
+		.hourly
+		.time
+		.iter()
+		.map(|&time| hour_from_timestamp(time, timezone))
+		.collect();
+	// In percent of the volume.
+	let moisture: Vec<(Rgb, Vec<i32>)> = LAYER_COLOURS
+		.into_iter()
+		.zip(result.hourly.moisture_layers())
+		.filter_map(|(colour, layer)| {
+			let values = complete(layer)?;
+			Some((
+				colour,
+				values
+					.into_iter()
+					.map(|value| convert_num(value * 100.0))
+					.collect(),
+			))
+		})
+		.collect();
+	let temperatures: Vec<(Rgb, Vec<i32>)> = TEMPERATURE_DEPTHS
+		.into_iter()
+		.zip(result.hourly.temperature_layers())
+		.filter_map(|((_, colour), layer)| {
+			Some((
+				colour,
+				complete(layer)?.into_iter().map(convert_num).collect(),
+			))
+		})
+		.collect();
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 27,
+		right: 3,
+	};
+
+	let spacing = Spacing {
+		horizontal: 8,
+		vertical: 3,
+	};
+	let max = moisture
+		.iter()
+		.flat_map(|(_, values)| values)
+		.copied()
+		.max()
+		.unwrap_or(0);
+	let moisture_range = Range::new(0, next_multiple(max, 10));
+	let mut segments = vec![TextSegment::white("Soil moisture at ")];
+	for (index, (depth, colour)) in MOISTURE_LAYERS.iter().zip(LAYER_COLOURS).enumerate() {
+		if index > 0 {
+			segments.push(TextSegment::white(", "));
+		}
+		segments.push(TextSegment::new(depth, colour));
+	}
+	segments.push(TextSegment::white(" cm in % of the volume"));
+	let label = TextBox::new(
+		&segments,
+		header_font.clone(),
+		LABEL_SIZE,
+		(times.len() - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		times.len(),
+		moisture_range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(5, 10),
+		horizontal_intervals: MarkIntervals::new(1, 3),
+		vertical_label_range: moisture_range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	// The deepest layer first, so the surface, which changes the most, is drawn on top.
+	for (colour, values) in moisture.into_iter().rev() {
+		chart.draw(Line {
+			colour,
+			data: values.into_iter(),
+			max: moisture_range.end(),
+		});
+	}
+	let mut images = vec![chart.into_canvas()];
+
+	if !temperatures.is_empty() {
+		let (&min, &max) = temperatures
+			.iter()
+			.flat_map(|(_, values)| values)
+			.minmax()
+			.into_option()
+			.unwrap_or((&0, &0));
+		// Soil can freeze, so the range isn't pinned to zero.
+		let temperature_range = previous_and_next_multiple(Range::new(min, max), 2);
+
+		let spacing = Spacing {
+			horizontal: 8,
+			vertical: 8,
+		};
+		let mut segments = vec![TextSegment::white("Soil temperature at ")];
+		for (index, (depth, colour)) in TEMPERATURE_DEPTHS.iter().enumerate() {
+			if index > 0 {
+				segments.push(TextSegment::white(", "));
+			}
+			segments.push(TextSegment::new(depth, *colour));
+		}
+		let unit = format!(" cm in {}", units.temperature_symbol());
+		segments.push(TextSegment::white(&unit));
+		let label = TextBox::new(
+			&segments,
+			header_font.clone(),
+			LABEL_SIZE,
+			(times.len() - 1) as u32 * spacing.horizontal,
+			2,
+		);
+		let mut chart = Chart::new(
+			times.len(),
+			temperature_range.len() as u32,
+			spacing,
+			Padding {
+				above: padding.above + label.height(),
+				..padding
+			},
+		);
+		chart.draw(label);
+		chart.draw(AxisGridLabels {
+			vertical_intervals: MarkIntervals::new(1, 2),
+			horizontal_intervals: MarkIntervals::new(1, 3),
+			vertical_label_range: temperature_range,
+			horizontal_labels: times.iter().copied(),
+			horizontal_labels_centered: false,
+			font: font.clone(),
+			font_scale: AXIS_LABEL_SIZE,
+		});
+		for (colour, values) in temperatures.into_iter().rev() {
+			chart.draw(Line {
+				colour,
+				data: values.into_iter(),
+				max: temperature_range.end(),
+			});
+		}
+		images.push(chart.into_canvas());
+	}
+
+	make_png(composite(&images))
+}
+
+pub fn create_soil() -> CreateCommand {
+	i18n::command(
+		"soil",
+		"Soil moisture, and optionally soil temperature, for the next three days",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the soil forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::Boolean,
+			"temperature",
+			"Whether to add the soil temperature at four depths below the moisture.",
+		)
+		.required(false),
+	)
+	.add_option(create_label_option())
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn incomplete_layers_left_out() {
+		let hourly: SoilHourly = serde_json::from_str(
+			r#"{
+				"time": [0, 3600],
+				"soil_moisture_0_to_1cm": [0.31, 0.3],
+				"soil_moisture_1_to_3cm": [0.32, null],
+				"soil_moisture_3_to_9cm": [0.33, 0.33],
+				"soil_moisture_9_to_27cm": [0.35, 0.35],
+				"soil_moisture_27_to_81cm": [0.36, 0.36]
+			}"#,
+		)
+		.unwrap();
+		assert!(hourly.has_soil());
+		let [surface, shallow, ..] = hourly.moisture_layers();
+		assert_eq!(complete(surface), Some(vec![0.31, 0.3]));
+		assert_eq!(complete(shallow), None);
+		// Without the temperature panel, the temperatures aren't there at all.
+		assert_eq!(complete(hourly.temperature_layers()[0]), None);
+
+		let sea = SoilHourly {
+			soil_moisture_0_to_1cm: vec![None, None],
+			soil_moisture_1_to_3cm: vec![None, None],
+			soil_moisture_3_to_9cm: vec![None, None],
+			soil_moisture_9_to_27cm: vec![None, None],
+			soil_moisture_27_to_81cm: vec![None, None],
+			..hourly
+		};
+		assert!(!sea.has_soil());
+	}
+}
@@ -0,0 +1,209 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Rgb, SolidBars},
+	text_box::{TextBox, TextSegment},
+	util::{make_png, next_multiple},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateInteractionResponseFollowup,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, RequestBuilderExt},
+	reply_shortcuts::{create_private_option, private_option, ReplyShortcuts},
+};
+
+const BARS: Rgb = Rgb([255, 0, 0]);
+/// One week of hours.
+const HOURS: u32 = 7 * 24;
+/// The most bins to draw before doubling their width, to keep the chart a sensible width.
+const MAX_NARROW_BINS: i32 = 24;
+
+#[derive(Debug, Deserialize)]
+struct HistogramHourly {
+	temperature_2m: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistogramResult {
+	hourly: HistogramHourly,
+}
+
+impl HistogramResult {
+	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
+		client
+			.get("https://api.open-meteo.com/v1/forecast")
+			.query(&[("hourly", "temperature_2m"), ("timezone", "auto")])
+			.query(&[("forecast_hours", HOURS)])
+			.query(&[
+				("latitude", coordinates.latitude),
+				("longitude", coordinates.longitude),
+			])
+			.fetch_open_meteo()
+			.await
+	}
+}
+
+/// How many temperatures fall into each bin of `width` whole degrees.
+///
+/// Returns the lower bound of the first bin, and the count of every bin from there up to and including the warmest, so empty bins in between are kept.
+fn bin_temperatures(temperatures: &[f32], width: i32) -> Option<(i32, Vec<u32>)> {
+	let bin = |temperature: f32| (temperature / width as f32).floor() as i32;
+	let first = temperatures.iter().map(|&t| bin(t)).min()?;
+	let last = temperatures.iter().map(|&t| bin(t)).max()?;
+	let mut counts = vec![0; (last - first + 1) as usize];
+	for &temperature in temperatures {
+		counts[(bin(temperature) - first) as usize] += 1;
+	}
+	Some((first * width, counts))
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+pub async fn handle_temperature_histogram(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let private = private_option(interaction);
+	interaction.defer_reply(&context.http, private).await?;
+
+	let result = HistogramResult::get(location.coordinates(), &client).await?;
+	let temperatures: Vec<f32> = result.hourly.temperature_2m.into_iter().flatten().collect();
+	let image = draw_histogram(&temperatures, font, header_font)?;
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.ephemeral(private)
+				.content(format!(
+					"How many of the next {} hours are forecast at each temperature in {}.",
+					temperatures.len(),
+					location.short_description()
+				))
+				.add_file(CreateAttachment::bytes(image, "temperature_histogram.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+/// Draw the distribution of temperatures as bars, one per bin, labelled with the bin's lower bound.
+fn draw_histogram(
+	temperatures: &[f32],
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<Vec<u8>, Error> {
+	let (mut start, mut counts) = bin_temperatures(temperatures, 1)
+		.ok_or_else(|| Error::friendly("No temperature forecast available."))?;
+	let mut width = 1;
+	while counts.len() as i32 > MAX_NARROW_BINS {
+		width *= 2;
+		(start, counts) = bin_temperatures(temperatures, width)
+			.ok_or_else(|| Error::friendly("No temperature forecast available."))?;
+	}
+	let bins: Vec<i32> = (0..counts.len() as i32)
+		.map(|index| start + index * width)
+		.collect();
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 9,
+	};
+	let spacing = Spacing {
+		horizontal: 24,
+		vertical: 2,
+	};
+	let count_range = Range::new(
+		0,
+		next_multiple(*counts.iter().max().unwrap_or(&0) as i32 * 100, 5),
+	);
+	let label = TextBox::new(
+		&[
+			TextSegment::white("Hours at each "),
+			TextSegment::new("temperature", BARS),
+			TextSegment::white(&format!(" (bins of {width} °C)")),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		counts.len() as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		counts.len() + 1,
+		count_range.end() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(5, 10),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: count_range,
+		horizontal_labels: bins.iter(),
+		horizontal_labels_centered: true,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(SolidBars {
+		colour: BARS,
+		data: counts.into_iter().map(|count| count as i32 * 100),
+	});
+	Ok(make_png(chart.into_canvas()))
+}
+
+pub fn create_temperature_histogram() -> CreateCommand {
+	i18n::command(
+		"temperature_histogram",
+		"How many hours of the coming week are forecast at each temperature",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the temperatures of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(create_private_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bins_keep_empty_gaps() {
+		let (start, counts) = bin_temperatures(&[10.2, 10.9, 12.5, 13.0], 1).unwrap();
+		assert_eq!(start, 10);
+		assert_eq!(counts, vec![2, 0, 1, 1]);
+	}
+	#[test]
+	fn bins_below_freezing() {
+		let (start, counts) = bin_temperatures(&[-0.5, 0.0, -3.9, 1.5], 2).unwrap();
+		assert_eq!(start, -4);
+		assert_eq!(counts, vec![1, 1, 2]);
+		assert!(bin_temperatures(&[], 1).is_none());
+	}
+}
@@ -1,11 +1,76 @@
+use std::{
+	collections::HashMap,
+	sync::{LazyLock, Mutex},
+	time::Duration,
+};
+
+use chrono::Utc;
+use itertools::Itertools;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serenity::all::{
-	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-	CreateInteractionResponse, CreateInteractionResponseMessage,
+	Colour, CommandInteraction, CommandOptionType, ComponentInteraction,
+	ComponentInteractionDataKind, Context, CreateAutocompleteResponse, CreateCommand,
+	CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup,
+	CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind,
+	CreateSelectMenuOption, EditInteractionResponse, InteractionId, UserId,
+};
+use sqlx::{query, Pool, Sqlite};
+
+use crate::{
+	cache::TtlCache,
+	error::Error,
+	i18n,
+	location::{format_elevation, Coordinates},
+	open_meteo::{self, RequestBuilderExt, ResponseExt},
+	reply_shortcuts::{record_response, ReplyShortcuts, ResponseState},
+	util::CommandInteractionExt,
 };
 
-use crate::error::Error;
+/// The sidebar of the embed describing a place, the blue of the precipitation in the charts.
+const PLACE_COLOUR: Colour = Colour::new(0x0094FF);
+
+/// How many matches to consider when picking the one nearest to somewhere.
+const MAX_CANDIDATES: u32 = 10;
+/// How many matches to offer in the menu when a place name is ambiguous.
+const PICKER_CANDIDATES: u32 = 5;
+/// How long to wait for a match to be picked from the menu.
+const PICKER_TIMEOUT: Duration = Duration::from_secs(120);
+/// The custom ID of the menu for picking a match.
+const PICKER_ID: &str = "pick_place";
+/// How many matches to suggest while typing a place name, which is the most Discord shows.
+const MAX_SUGGESTIONS: u32 = 25;
+/// The shortest input to suggest matches for, in characters. Shorter ones match too much to be useful.
+const MIN_SUGGESTION_INPUT: usize = 2;
+/// How long to wait for more typing before looking up suggestions, since Discord asks for them on every keystroke.
+const SUGGESTION_DELAY: Duration = Duration::from_millis(300);
+/// How long a cached geocoding lookup is used before looking it up again, in seconds.
+const CACHE_TTL: i64 = 30 * 24 * 60 * 60;
+/// How long suggestions for what has been typed are kept in memory.
+const SUGGESTION_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// The longest label Discord allows on a select menu option, in characters.
+const MAX_OPTION_LABEL_LENGTH: usize = 100;
+/// The longest place name that will be looked up, in characters.
+const MAX_PLACE_NAME_LENGTH: usize = 100;
+
+/// Reject place names that are overly long or contain control characters before they get sent anywhere, and normalize the whitespace in the rest.
+fn validate_place_name(place_name: &str) -> Result<String, Error> {
+	if place_name.chars().count() > MAX_PLACE_NAME_LENGTH {
+		return Err(Error::friendly(format!(
+			"Place names can be at most {MAX_PLACE_NAME_LENGTH} characters long"
+		)));
+	}
+	if place_name.chars().any(char::is_control) {
+		return Err(Error::friendly(
+			"Place names can't contain control characters like line breaks",
+		));
+	}
+	let normalized = place_name.split_whitespace().join(" ");
+	if normalized.is_empty() {
+		return Err(Error::friendly("No place name given"));
+	}
+	Ok(normalized)
+}
 
 #[derive(Debug, Deserialize)]
 pub struct GeocodingResultMinimal {
@@ -17,13 +82,14 @@ pub struct GeocodingResultMinimal {
 
 impl GeocodingResultMinimal {
 	pub async fn get(place_name: &str, client: &Client) -> Result<Self, Error> {
+		let place_name = validate_place_name(place_name)?;
 		let mut results: GeocodingResultsMinimal = client
 			.get("https://geocoding-api.open-meteo.com/v1/search")
 			.query(&[("count", 1)])
-			.query(&[("format", "json"), ("name", place_name)])
-			.send()
+			.query(&[("format", "json"), ("name", &place_name)])
+			.send_open_meteo()
 			.await?
-			.json()
+			.json_limited()
 			.await?;
 		results
 			.results
@@ -33,7 +99,7 @@ impl GeocodingResultMinimal {
 }
 
 /// https://open-meteo.com/en/docs/geocoding-api
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GeocodingResult {
 	pub id: u32,
 	pub name: String,
@@ -43,23 +109,415 @@ pub struct GeocodingResult {
 	pub feature_code: String,
 	pub country_code: Option<String>,
 	pub country: Option<String>,
+	/// The largest administrative area the place is in, like a state or province.
+	pub admin1: Option<String>,
 	pub population: Option<u32>,
 }
 
 impl GeocodingResult {
-	pub async fn get(place_name: &str, client: &Client) -> Result<Self, Error> {
-		let mut results: GeocodingResults = client
-			.get("https://geocoding-api.open-meteo.com/v1/search")
-			.query(&[("count", "1"), ("format", "json"), ("name", place_name)])
-			.send()
+	pub async fn get(
+		place_name: &str,
+		client: &Client,
+		database: &Pool<Sqlite>,
+	) -> Result<Self, Error> {
+		Self::get_candidates(place_name, 1, client, database)
 			.await?
-			.json()
-			.await?;
-		results
-			.results
 			.pop()
 			.ok_or_else(|| Error::friendly("No geocoding results"))
 	}
+	/// Up to `count` matches for the place name, most prominent first, from the cache if it was looked up recently enough.
+	pub async fn get_candidates(
+		place_name: &str,
+		count: u32,
+		client: &Client,
+		database: &Pool<Sqlite>,
+	) -> Result<Vec<Self>, Error> {
+		let place_name = validate_place_name(place_name)?;
+		let key = place_name.to_lowercase();
+		match get_cached(database, &key, count).await {
+			Ok(Some(results)) => return Ok(results),
+			Ok(None) => (),
+			Err(error) => tracing::error!(error = %error.chain(), "Failed to read geocoding cache"),
+		}
+		let results = fetch_candidates(&place_name, count, client).await?;
+		if let Err(error) = cache(database, &key, count, &results).await {
+			tracing::error!(error = %error.chain(), "Failed to cache geocoding results");
+		}
+		Ok(results)
+	}
+	/// Up to `count` matches for what has been typed so far, to suggest while typing. These are only kept in memory for a short while, since most of what gets typed is never looked up again.
+	pub async fn get_suggestions(
+		place_name: &str,
+		count: u32,
+		client: &Client,
+	) -> Result<Vec<Self>, Error> {
+		let place_name = validate_place_name(place_name)?;
+		let key = place_name.to_lowercase();
+		if let Some(results) = SUGGESTION_CACHE
+			.get(&key)
+			.and_then(|(cached_count, results)| enough_cached(cached_count, results, count))
+		{
+			return Ok(results);
+		}
+		let results = fetch_candidates(&place_name, count, client).await?;
+		SUGGESTION_CACHE.insert(key, (count, results.clone()));
+		Ok(results)
+	}
+	/// The match for the place name nearest to `reference`, out of the most prominent few, rather than the most prominent one overall.
+	pub async fn get_nearest(
+		place_name: &str,
+		reference: Coordinates,
+		client: &Client,
+		database: &Pool<Sqlite>,
+	) -> Result<Self, Error> {
+		let candidates = Self::get_candidates(place_name, MAX_CANDIDATES, client, database).await?;
+		nearest(candidates, reference).ok_or_else(|| Error::friendly("No geocoding results"))
+	}
+	pub fn coordinates(&self) -> Coordinates {
+		Coordinates::new(self.latitude, self.longitude)
+	}
+	/// The name along with the administrative area and country it is in, as far as they are known, to tell apart places with the same name.
+	fn label(&self) -> String {
+		[
+			Some(&self.name),
+			self.admin1.as_ref(),
+			self.country.as_ref(),
+		]
+		.into_iter()
+		.flatten()
+		.dedup()
+		.join(", ")
+	}
+}
+
+/// A geocoding match, and how to reply to the command that looked it up.
+pub struct Picked {
+	pub result: GeocodingResult,
+	pub reply: PickReply,
+}
+
+/// How to reply to a command once its place has been looked up.
+#[derive(Debug, Clone, Copy)]
+pub enum PickReply {
+	/// The command has not been responded to yet.
+	Respond,
+	/// The command was responded to with a menu to pick the place from, so the reply has to be a follow-up.
+	FollowUp,
+}
+
+impl PickReply {
+	pub async fn send(
+		self,
+		context: &Context,
+		interaction: &CommandInteraction,
+		content: String,
+		ephemeral: bool,
+	) -> Result<(), Error> {
+		match self {
+			Self::Respond => interaction.reply(&context.http, content, ephemeral).await?,
+			Self::FollowUp => {
+				interaction
+					.create_followup(
+						context,
+						CreateInteractionResponseFollowup::new()
+							.content(content)
+							.ephemeral(ephemeral),
+					)
+					.await?;
+			}
+		}
+		Ok(())
+	}
+	pub async fn send_embed(
+		self,
+		context: &Context,
+		interaction: &CommandInteraction,
+		embed: CreateEmbed,
+		ephemeral: bool,
+	) -> Result<(), Error> {
+		match self {
+			Self::Respond => {
+				interaction
+					.reply_embed(&context.http, embed, ephemeral)
+					.await?
+			}
+			Self::FollowUp => {
+				interaction
+					.create_followup(
+						context,
+						CreateInteractionResponseFollowup::new()
+							.embed(embed)
+							.ephemeral(ephemeral),
+					)
+					.await?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Look up the place name, and when it matches several places, let the user pick the one they meant from a menu.
+///
+/// Returns `None` if nothing was picked in time, after telling the user so.
+pub async fn pick(
+	context: &Context,
+	interaction: &CommandInteraction,
+	place_name: &str,
+	client: &Client,
+	database: &Pool<Sqlite>,
+) -> Result<Option<Picked>, Error> {
+	let mut candidates =
+		GeocodingResult::get_candidates(place_name, PICKER_CANDIDATES, client, database).await?;
+	if candidates.len() <= 1 {
+		let result = candidates
+			.pop()
+			.ok_or_else(|| Error::friendly("No geocoding results"))?;
+		return Ok(Some(Picked {
+			result,
+			reply: PickReply::Respond,
+		}));
+	}
+
+	interaction
+		.create_response(
+			context,
+			CreateInteractionResponse::Message(
+				CreateInteractionResponseMessage::new()
+					.content(format!(
+						"Several places match \"{place_name}\". Which one did you mean?"
+					))
+					.select_menu(picker_menu(&candidates))
+					.ephemeral(true),
+			),
+		)
+		.await?;
+	record_response(ResponseState::Sent);
+	let menu = interaction.get_response(context).await?;
+	let Some(choice) = menu
+		.await_component_interaction(&context.shard)
+		.author_id(interaction.user.id)
+		.timeout(PICKER_TIMEOUT)
+		.await
+	else {
+		interaction
+			.edit_response(
+				context,
+				EditInteractionResponse::new()
+					.content(
+						"No place was picked in time. Use the command again to look it up anew.",
+					)
+					.components(Vec::new()),
+			)
+			.await?;
+		return Ok(None);
+	};
+
+	let index = picked_index(&choice)
+		.filter(|&index| index < candidates.len())
+		.ok_or_else(|| Error::custom_unfriendly("Invalid place pick"))?;
+	let result = candidates.swap_remove(index);
+	choice
+		.create_response(
+			context,
+			CreateInteractionResponse::UpdateMessage(
+				CreateInteractionResponseMessage::new()
+					.content(format!("Picked {}.", result.label()))
+					.components(Vec::new()),
+			),
+		)
+		.await?;
+	Ok(Some(Picked {
+		result,
+		reply: PickReply::FollowUp,
+	}))
+}
+
+/// A menu listing the candidates, each option's value being its index.
+fn picker_menu(candidates: &[GeocodingResult]) -> CreateSelectMenu {
+	let options = candidates
+		.iter()
+		.enumerate()
+		.map(|(index, candidate)| {
+			let label: String = candidate
+				.label()
+				.chars()
+				.take(MAX_OPTION_LABEL_LENGTH)
+				.collect();
+			CreateSelectMenuOption::new(label, index.to_string())
+				.description(candidate.coordinates().to_string())
+		})
+		.collect();
+	CreateSelectMenu::new(PICKER_ID, CreateSelectMenuKind::String { options })
+		.placeholder("Pick a place")
+}
+
+/// The index of the candidate picked from the menu.
+fn picked_index(choice: &ComponentInteraction) -> Option<usize> {
+	match &choice.data.kind {
+		ComponentInteractionDataKind::StringSelect { values } => values.first()?.parse().ok(),
+		_ => None,
+	}
+}
+
+/// Whether the component is a menu for picking a place, which is handled by whatever command is waiting for it.
+pub fn is_place_pick(interaction: &ComponentInteraction) -> bool {
+	interaction.data.custom_id == PICKER_ID
+}
+
+/// The latest request for suggestions from each user, so requests overtaken by more typing can be dropped without looking anything up.
+static LATEST_SUGGESTION_REQUESTS: LazyLock<Mutex<HashMap<UserId, InteractionId>>> =
+	LazyLock::new(Default::default);
+
+/// Recent suggestions by lowercased input, along with how many matches were asked for.
+static SUGGESTION_CACHE: LazyLock<TtlCache<String, (u32, Vec<GeocodingResult>)>> =
+	LazyLock::new(|| TtlCache::new(SUGGESTION_CACHE_TTL));
+
+/// Suggest matches for what has been typed into a place option so far. The suggestions fill in the place name, which is looked up again when the command is sent.
+pub async fn handle_place_autocomplete(
+	context: &Context,
+	interaction: &CommandInteraction,
+) -> Result<(), Error> {
+	let Some(input) = interaction
+		.data
+		.autocomplete()
+		.map(|option| option.value.trim())
+	else {
+		return Ok(());
+	};
+	// Coordinates are used as they are, so there is nothing to suggest for them.
+	let candidates =
+		if input.chars().count() < MIN_SUGGESTION_INPUT || Coordinates::parse(input).is_some() {
+			Vec::new()
+		} else {
+			let user = interaction.user.id;
+			LATEST_SUGGESTION_REQUESTS
+				.lock()
+				.unwrap()
+				.insert(user, interaction.id);
+			tokio::time::sleep(SUGGESTION_DELAY).await;
+			{
+				let mut latest = LATEST_SUGGESTION_REQUESTS.lock().unwrap();
+				if latest.get(&user) != Some(&interaction.id) {
+					return Ok(());
+				}
+				latest.remove(&user);
+			}
+			let client = open_meteo::client();
+			GeocodingResult::get_suggestions(input, MAX_SUGGESTIONS, &client).await?
+		};
+	interaction
+		.create_response(
+			&context.http,
+			CreateInteractionResponse::Autocomplete(suggestions(&candidates)),
+		)
+		.await?;
+	Ok(())
+}
+
+fn suggestions(candidates: &[GeocodingResult]) -> CreateAutocompleteResponse {
+	candidates
+		.iter()
+		.fold(CreateAutocompleteResponse::new(), |response, candidate| {
+			let label: String = candidate
+				.label()
+				.chars()
+				.take(MAX_OPTION_LABEL_LENGTH)
+				.collect();
+			response.add_string_choice(label, candidate.name.clone())
+		})
+}
+
+/// The cached matches for the lowercased place name, if they are recent and there are enough of them.
+async fn get_cached(
+	database: &Pool<Sqlite>,
+	key: &str,
+	count: u32,
+) -> Result<Option<Vec<GeocodingResult>>, Error> {
+	let oldest = Utc::now().timestamp() - CACHE_TTL;
+	let Some(cached) = query!(
+		"
+		SELECT count, results
+		FROM geocoding_cache
+		WHERE query = ? AND fetched >= ?
+		",
+		key,
+		oldest
+	)
+	.fetch_optional(database)
+	.await?
+	else {
+		return Ok(None);
+	};
+	let results = serde_json::from_str(&cached.results)?;
+	Ok(enough_cached(cached.count as u32, results, count))
+}
+
+/// The first `count` of the cached results, if the lookup asked for at least that many, or got fewer than it asked for so there are no more to find.
+fn enough_cached(
+	cached_count: u32,
+	mut results: Vec<GeocodingResult>,
+	count: u32,
+) -> Option<Vec<GeocodingResult>> {
+	if cached_count < count && results.len() >= cached_count as usize {
+		return None;
+	}
+	results.truncate(count as usize);
+	Some(results)
+}
+
+async fn cache(
+	database: &Pool<Sqlite>,
+	key: &str,
+	count: u32,
+	results: &[GeocodingResult],
+) -> Result<(), Error> {
+	let fetched = Utc::now().timestamp();
+	let results = serde_json::to_string(results)?;
+	let oldest = fetched - CACHE_TTL;
+	let mut transaction = database.begin().await?;
+	// Expired lookups are cleared out along the way, so the cache doesn't grow forever.
+	query!("DELETE FROM geocoding_cache WHERE fetched < ?", oldest)
+		.execute(&mut *transaction)
+		.await?;
+	query!(
+		"
+		INSERT INTO geocoding_cache (query, count, fetched, results)
+		VALUES (?, ?, ?, ?)
+		",
+		key,
+		count,
+		fetched,
+		results
+	)
+	.execute(&mut *transaction)
+	.await?;
+	transaction.commit().await?;
+	Ok(())
+}
+
+async fn fetch_candidates(
+	place_name: &str,
+	count: u32,
+	client: &Client,
+) -> Result<Vec<GeocodingResult>, Error> {
+	let results: GeocodingResults = client
+		.get("https://geocoding-api.open-meteo.com/v1/search")
+		.query(&[("count", count)])
+		.query(&[("format", "json"), ("name", place_name)])
+		.send_open_meteo()
+		.await?
+		.json_limited()
+		.await?;
+	Ok(results.results)
+}
+
+/// Pick the candidate closest to the reference. With ties, the earlier, more prominent candidate wins.
+fn nearest(candidates: Vec<GeocodingResult>, reference: Coordinates) -> Option<GeocodingResult> {
+	candidates.into_iter().min_by(|a, b| {
+		a.coordinates()
+			.distance_km(reference)
+			.total_cmp(&b.coordinates().distance_km(reference))
+	})
 }
 
 #[derive(Debug, Deserialize)]
@@ -77,50 +535,162 @@ struct GeocodingResultsMinimal {
 pub async fn handle_find_coordinates(
 	context: &Context,
 	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
 ) -> Result<(), Error> {
-	let Some(place) = interaction
-		.data
-		.options
-		.first()
-		.and_then(|option| option.value.as_str())
-	else {
+	let Some(place) = interaction.string_option("place") else {
 		return Err(Error::friendly("No argument"));
 	};
-	let client = Client::new();
-	let result = GeocodingResult::get(place, &client).await?;
-	let content = format!(
-		"Name: {}, population: {}, latitude: {}, longitude: {}, feature code: {}, country: {}",
-		result.name,
-		result
-			.population
-			.map_or_else(|| String::from("unknown"), |n| format!("{n}")),
-		result.latitude,
-		result.longitude,
-		result.feature_code,
-		result
-			.country
-			.unwrap_or_else(|| String::from("unspecified")),
-	);
-	interaction
-		.create_response(
-			context,
-			CreateInteractionResponse::Message(
-				CreateInteractionResponseMessage::new().content(content),
-			),
+	let client = open_meteo::client();
+	let Some(picked) = pick(context, interaction, place, &client, database).await? else {
+		return Ok(());
+	};
+	let Picked { result, reply } = picked;
+	let embed = CreateEmbed::new()
+		.title(&result.name)
+		.colour(PLACE_COLOUR)
+		.field(
+			"Population",
+			result
+				.population
+				.map_or_else(|| String::from("unknown"), |n| format!("{n}")),
+			true,
 		)
-		.await?;
-	Ok(())
+		.field("Latitude", result.latitude.to_string(), true)
+		.field("Longitude", result.longitude.to_string(), true)
+		.field("Elevation", format_elevation(result.elevation), true)
+		// Discord rejects fields with empty values.
+		.field(
+			"Feature code",
+			match result.feature_code.as_str() {
+				"" => "unspecified",
+				code => code,
+			},
+			true,
+		)
+		.field(
+			"Country",
+			result.country.as_deref().unwrap_or("unspecified"),
+			true,
+		);
+	reply.send_embed(context, interaction, embed, false).await
 }
 
 pub fn create_find_coordinates() -> CreateCommand {
-	CreateCommand::new("find_coordinates")
-		.description("Finds the coordinates of the specified place.")
-		.add_option(
-			CreateCommandOption::new(
-				CommandOptionType::String,
-				"place",
-				"The place to find the coordinates of.",
-			)
-			.required(true),
+	i18n::command(
+		"find_coordinates",
+		"Finds the coordinates of the specified place.",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to find the coordinates of.",
 		)
+		.required(true)
+		.set_autocomplete(true),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn place_name_too_long() {
+		let place_name = "a".repeat(MAX_PLACE_NAME_LENGTH + 1);
+		assert!(matches!(
+			validate_place_name(&place_name),
+			Err(Error::Friendly(_))
+		));
+	}
+	#[test]
+	fn place_name_with_newline() {
+		assert!(matches!(
+			validate_place_name("Berlin\nParis"),
+			Err(Error::Friendly(_))
+		));
+	}
+	#[test]
+	fn place_name_normalized() {
+		assert_eq!(
+			validate_place_name("  New   York ").unwrap(),
+			String::from("New York")
+		);
+	}
+	fn candidate(name: &str, latitude: f32, longitude: f32) -> GeocodingResult {
+		GeocodingResult {
+			id: 0,
+			name: String::from(name),
+			latitude,
+			longitude,
+			elevation: None,
+			feature_code: String::from("PPL"),
+			country_code: None,
+			country: None,
+			admin1: None,
+			population: None,
+		}
+	}
+	#[test]
+	fn nearest_candidate_chosen() {
+		let candidates = vec![
+			candidate("Paris, France", 48.85, 2.35),
+			candidate("Paris, Texas", 33.66, -95.56),
+			candidate("Paris, Ontario", 43.2, -80.38),
+		];
+		let dallas = Coordinates::new(32.78, -96.8);
+		assert_eq!(nearest(candidates, dallas).unwrap().name, "Paris, Texas");
+	}
+	#[test]
+	fn nearest_prefers_earlier_on_ties() {
+		let candidates = vec![candidate("First", 1.0, 0.0), candidate("Second", -1.0, 0.0)];
+		let equator = Coordinates::new(0.0, 0.0);
+		assert_eq!(nearest(candidates, equator).unwrap().name, "First");
+		assert!(nearest(Vec::new(), equator).is_none());
+	}
+	#[test]
+	fn label_tells_places_apart() {
+		let mut paris = candidate("Paris", 33.66, -95.56);
+		paris.admin1 = Some(String::from("Texas"));
+		paris.country = Some(String::from("United States"));
+		assert_eq!(paris.label(), "Paris, Texas, United States");
+		let mut singapore = candidate("Singapore", 1.29, 103.85);
+		singapore.admin1 = Some(String::from("Singapore"));
+		singapore.country = Some(String::from("Singapore"));
+		assert_eq!(singapore.label(), "Singapore");
+		assert_eq!(candidate("Nowhere", 0.0, 0.0).label(), "Nowhere");
+	}
+	#[test]
+	fn cache_used_when_enough() {
+		let results = vec![candidate("A", 0.0, 0.0), candidate("B", 1.0, 1.0)];
+		// Asked for 5 and got 2: there are no more to find, however many are asked for.
+		assert_eq!(enough_cached(5, results.clone(), 10).unwrap().len(), 2);
+		assert_eq!(enough_cached(5, results.clone(), 1).unwrap().len(), 1);
+		// Asked for 2 and got 2: there may be more than were cached.
+		assert!(enough_cached(2, results.clone(), 5).is_none());
+		assert_eq!(enough_cached(2, results, 2).unwrap().len(), 2);
+	}
+	#[tokio::test]
+	async fn expired_cache_pruned() {
+		let database = crate::database::in_memory().await;
+		let expired = Utc::now().timestamp() - CACHE_TTL - 1;
+		query!(
+			"INSERT INTO geocoding_cache (query, count, fetched, results) VALUES ('ber', 25, ?, '[]')",
+			expired
+		)
+		.execute(&database)
+		.await
+		.unwrap();
+		cache(&database, "bergen", 1, &[candidate("Bergen", 60.39, 5.32)])
+			.await
+			.unwrap();
+		let queries: Vec<String> = query!("SELECT query FROM geocoding_cache")
+			.fetch_all(&database)
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|row| row.query)
+			.collect();
+		assert_eq!(queries, ["bergen"]);
+	}
 }
@@ -0,0 +1,58 @@
+use chrono_tz::Tz;
+use serenity::all::UserId;
+use sqlx::{query, Pool, Sqlite};
+
+use crate::error::Error;
+
+/// The timezone a user chose to see forecast times in wherever the forecast is for, if they chose one.
+pub async fn get_for_user(database: &Pool<Sqlite>, user: UserId) -> Result<Option<Tz>, Error> {
+	let user = user.get() as i64;
+	let settings = query!("SELECT timezone FROM user_settings WHERE user = ?", user)
+		.fetch_optional(database)
+		.await?;
+	// A name chrono-tz no longer knows is treated as no choice, rather than failing every forecast.
+	Ok(settings
+		.and_then(|settings| settings.timezone)
+		.and_then(|name| name.parse().ok()))
+}
+
+/// Set the user's home timezone, or clear it with `None`. Their other settings are kept.
+pub async fn set_for_user(
+	database: &Pool<Sqlite>,
+	user: UserId,
+	timezone: Option<Tz>,
+) -> Result<(), Error> {
+	let user = user.get() as i64;
+	let timezone = timezone.map(|timezone| timezone.name());
+	query!(
+		"INSERT INTO user_settings (user, timezone) VALUES (?, ?) ON CONFLICT (user) DO UPDATE SET timezone = excluded.timezone",
+		user,
+		timezone
+	)
+	.execute(database)
+	.await?;
+	Ok(())
+}
+
+/// An IANA timezone name, like "Europe/Amsterdam", or a friendly error if chrono-tz doesn't know it.
+pub fn parse(name: &str) -> Result<Tz, Error> {
+	name.trim().parse().map_err(|_| {
+		Error::friendly(format!(
+			"Unknown timezone \"{name}\". Use a name from the IANA timezone database, like Europe/Amsterdam or America/New_York."
+		))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn timezone_names_validated() {
+		assert_eq!(parse("Europe/Amsterdam").ok(), Some(Tz::Europe__Amsterdam));
+		assert_eq!(parse(" Asia/Tokyo ").ok(), Some(Tz::Asia__Tokyo));
+		assert!(matches!(parse("Europe/Atlantis"), Err(Error::Friendly(_))));
+		assert!(parse("GMT+25").is_err());
+		assert!(parse("").is_err());
+	}
+}
@@ -0,0 +1,491 @@
+use serenity::all::CreateCommand;
+
+/// The languages besides English that command descriptions and common replies are translated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+	German,
+	Spanish,
+	Dutch,
+}
+
+impl Language {
+	const ALL: [Self; 3] = [Self::German, Self::Spanish, Self::Dutch];
+	/// The Discord locales that use this language.
+	fn discord_locales(self) -> &'static [&'static str] {
+		match self {
+			Self::German => &["de"],
+			Self::Spanish => &["es-ES", "es-419"],
+			Self::Dutch => &["nl"],
+		}
+	}
+	/// The language of a Discord locale, or `None` for English and for languages there are no translations for.
+	fn from_locale(locale: &str) -> Option<Self> {
+		Self::ALL
+			.into_iter()
+			.find(|language| language.discord_locales().contains(&locale))
+	}
+	/// This language's position in a catalog entry.
+	fn index(self) -> usize {
+		match self {
+			Self::German => 0,
+			Self::Spanish => 1,
+			Self::Dutch => 2,
+		}
+	}
+}
+
+/// Translations keyed by the English text, in the order of [`Language::ALL`]. Command descriptions have to fit in Discord's 100 characters in every language.
+const CATALOG: &[(&str, [&str; 3])] = &[
+	// Command descriptions.
+	(
+		"Finds the coordinates of the specified place.",
+		[
+			"Findet die Koordinaten des angegebenen Ortes.",
+			"Busca las coordenadas del lugar indicado.",
+			"Zoekt de coördinaten van de opgegeven plaats op.",
+		],
+	),
+	(
+		"Current weather",
+		["Aktuelles Wetter", "Tiempo actual", "Huidig weer"],
+	),
+	(
+		"What it's like now and for the rest of today",
+		[
+			"Wie es jetzt ist und für den Rest des Tages",
+			"Cómo está ahora y el resto del día",
+			"Hoe het nu is en de rest van vandaag",
+		],
+	),
+	(
+		"Current weather in a single line",
+		[
+			"Aktuelles Wetter in einer Zeile",
+			"Tiempo actual en una sola línea",
+			"Huidig weer in één regel",
+		],
+	),
+	(
+		"Current weather in both metric and imperial units",
+		[
+			"Aktuelles Wetter in metrischen und imperialen Einheiten",
+			"Tiempo actual en unidades métricas e imperiales",
+			"Huidig weer in metrische en imperiale eenheden",
+		],
+	),
+	(
+		"Current weather at the match for a place name that is nearest to you",
+		[
+			"Aktuelles Wetter am nächstgelegenen Ort mit diesem Namen",
+			"Tiempo actual en el lugar con ese nombre más cercano a ti",
+			"Huidig weer op de dichtstbijzijnde plaats met die naam",
+		],
+	),
+	(
+		"Why it feels warmer or colder than the thermometer says",
+		[
+			"Warum es sich wärmer oder kälter anfühlt, als das Thermometer sagt",
+			"Por qué la sensación es más cálida o más fría de lo que marca el termómetro",
+			"Waarom het warmer of kouder aanvoelt dan de thermometer zegt",
+		],
+	),
+	(
+		"Current weather somewhere in the world",
+		[
+			"Aktuelles Wetter irgendwo auf der Welt",
+			"Tiempo actual en algún lugar del mundo",
+			"Huidig weer ergens op de wereld",
+		],
+	),
+	(
+		"Whether it's foggy right now",
+		[
+			"Ob es gerade neblig ist",
+			"Si hay niebla ahora mismo",
+			"Of het nu mistig is",
+		],
+	),
+	(
+		"Visibility for the next two days, for spotting fog and haze",
+		[
+			"Sichtweite für die nächsten zwei Tage, um Nebel und Dunst zu erkennen",
+			"Visibilidad para los próximos dos días, para ver niebla y calima",
+			"Zicht voor de komende twee dagen, om mist en nevel te zien aankomen",
+		],
+	),
+	(
+		"Get a forecast in a direct message every day or every week",
+		[
+			"Jeden Tag oder jede Woche eine Vorhersage als Direktnachricht erhalten",
+			"Recibe un pronóstico por mensaje directo cada día o cada semana",
+			"Krijg elke dag of elke week een verwachting in een privébericht",
+		],
+	),
+	(
+		"Stop getting forecasts in direct messages",
+		[
+			"Keine Vorhersagen mehr als Direktnachricht erhalten",
+			"Deja de recibir pronósticos por mensaje directo",
+			"Stop met verwachtingen in privéberichten",
+		],
+	),
+	(
+		"Active severe weather warnings",
+		[
+			"Aktive Unwetterwarnungen",
+			"Avisos de tiempo severo activos",
+			"Actieve waarschuwingen voor zwaar weer",
+		],
+	),
+	(
+		"Forecast summary for a specific window of time",
+		[
+			"Zusammenfassung der Vorhersage für einen bestimmten Zeitraum",
+			"Resumen del pronóstico para un periodo concreto",
+			"Samenvatting van de verwachting voor een bepaalde periode",
+		],
+	),
+	(
+		"Set the location to use by default for weather commands.",
+		[
+			"Den Standardort für Wetterbefehle festlegen.",
+			"Establece la ubicación predeterminada para los comandos del tiempo.",
+			"Stel de standaardlocatie voor weercommando's in.",
+		],
+	),
+	(
+		"Set the location weather commands in this server use for anyone who hasn't set their own.",
+		[
+			"Den Ort festlegen, den Wetterbefehle hier für alle ohne eigenen Ort verwenden.",
+			"Establece la ubicación del servidor para quien no tenga una propia.",
+			"Stel de locatie in die weercommando's hier gebruiken voor wie er zelf geen heeft.",
+		],
+	),
+	(
+		"Set the coordinates to use by default for weather commands.",
+		[
+			"Die Standardkoordinaten für Wetterbefehle festlegen.",
+			"Establece las coordenadas predeterminadas para los comandos del tiempo.",
+			"Stel de standaardcoördinaten voor weercommando's in.",
+		],
+	),
+	(
+		"Unset the location to use by default for weather commands.",
+		[
+			"Den Standardort für Wetterbefehle entfernen.",
+			"Quita la ubicación predeterminada de los comandos del tiempo.",
+			"Verwijder de standaardlocatie voor weercommando's.",
+		],
+	),
+	(
+		"List the locations you have saved.",
+		[
+			"Deine gespeicherten Orte auflisten.",
+			"Muestra las ubicaciones que has guardado.",
+			"Toon de locaties die je hebt opgeslagen.",
+		],
+	),
+	(
+		"Delete your saved locations in every server and in direct messages, your settings and subscription.",
+		[
+			"Deine gespeicherten Orte überall, deine Einstellungen und dein Abo löschen.",
+			"Borra tus ubicaciones guardadas en todas partes, tus ajustes y tu suscripción.",
+			"Verwijder je opgeslagen locaties overal, je instellingen en je abonnement.",
+		],
+	),
+	(
+		"Set whether weather commands use metric or imperial units.",
+		[
+			"Festlegen, ob Wetterbefehle metrische oder imperiale Einheiten verwenden.",
+			"Elige si los comandos del tiempo usan unidades métricas o imperiales.",
+			"Kies of weercommando's metrische of imperiale eenheden gebruiken.",
+		],
+	),
+	(
+		"Show hourly forecast times in your own timezone instead of the location's.",
+		[
+			"Zeiten der stündlichen Vorhersage in deiner eigenen Zeitzone anzeigen.",
+			"Muestra las horas del pronóstico por horas en tu propia zona horaria.",
+			"Toon de tijden van de uurverwachting in je eigen tijdzone.",
+		],
+	),
+	(
+		"Post a forecast that keeps updating itself",
+		[
+			"Eine Vorhersage posten, die sich selbst aktualisiert",
+			"Publica un pronóstico que se actualiza solo",
+			"Plaats een verwachting die zichzelf blijft bijwerken",
+		],
+	),
+	(
+		"Today's moonrise and moonset, and the moon's phase",
+		[
+			"Mondaufgang und -untergang von heute und die Mondphase",
+			"La salida y la puesta de la luna de hoy, y su fase",
+			"Maanopkomst en -ondergang van vandaag, en de maanfase",
+		],
+	),
+	(
+		"The moon's phase today",
+		[
+			"Die heutige Mondphase",
+			"La fase lunar de hoy",
+			"De maanfase van vandaag",
+		],
+	),
+	(
+		"What today is historically like",
+		[
+			"Wie das Wetter an diesem Tag früher war",
+			"Cómo suele ser el tiempo en este día",
+			"Hoe het weer op deze dag vroeger was",
+		],
+	),
+	(
+		"Soil moisture, and optionally soil temperature, for the next three days",
+		[
+			"Bodenfeuchte und optional Bodentemperatur für die nächsten drei Tage",
+			"Humedad del suelo y, si quieres, su temperatura para los próximos tres días",
+			"Bodemvocht en eventueel bodemtemperatuur voor de komende drie dagen",
+		],
+	),
+	(
+		"Air quality forecast for the next two days",
+		[
+			"Luftqualitätsvorhersage für die nächsten zwei Tage",
+			"Pronóstico de la calidad del aire para los próximos dos días",
+			"Luchtkwaliteitsverwachting voor de komende twee dagen",
+		],
+	),
+	(
+		"Pollen forecast for the next two days, in Europe",
+		[
+			"Pollenvorhersage für die nächsten zwei Tage, in Europa",
+			"Pronóstico de polen para los próximos dos días, en Europa",
+			"Pollenverwachting voor de komende twee dagen, in Europa",
+		],
+	),
+	(
+		"Wave height and period for the next two days",
+		[
+			"Wellenhöhe und -periode für die nächsten zwei Tage",
+			"Altura y periodo de las olas para los próximos dos días",
+			"Golfhoogte en -periode voor de komende twee dagen",
+		],
+	),
+	(
+		"Past daily temperatures and precipitation",
+		[
+			"Vergangene Tagestemperaturen und Niederschläge",
+			"Temperaturas y precipitación diarias pasadas",
+			"Dagtemperaturen en neerslag uit het verleden",
+		],
+	),
+	(
+		"How many hours of the coming week are forecast at each temperature",
+		[
+			"Wie viele Stunden der kommenden Woche bei jeder Temperatur vorhergesagt sind",
+			"Cuántas horas de la próxima semana se esperan a cada temperatura",
+			"Hoeveel uur van de komende week bij elke temperatuur verwacht wordt",
+		],
+	),
+	(
+		"Daily weather forecast",
+		[
+			"Tägliche Wettervorhersage",
+			"Pronóstico del tiempo por días",
+			"Weersverwachting per dag",
+		],
+	),
+	(
+		"Daily weather forecast in words, one sentence per day",
+		[
+			"Tägliche Wettervorhersage in Worten, ein Satz pro Tag",
+			"Pronóstico por días en palabras, una frase por día",
+			"Weersverwachting per dag in woorden, één zin per dag",
+		],
+	),
+	(
+		"Low-confidence outlook of temperature and precipitation for the coming months",
+		[
+			"Unsichere Aussicht auf Temperatur und Niederschlag für die kommenden Monate",
+			"Perspectiva poco fiable de temperatura y precipitación para los próximos meses",
+			"Onzekere vooruitblik op temperatuur en neerslag voor de komende maanden",
+		],
+	),
+	(
+		"Snowfall, snow depth and freezing level for the next three days",
+		[
+			"Schneefall, Schneehöhe und Nullgradgrenze für die nächsten drei Tage",
+			"Nevadas, espesor de nieve y cota de congelación para los próximos tres días",
+			"Sneeuwval, sneeuwdikte en vriesniveau voor de komende drie dagen",
+		],
+	),
+	(
+		"Hourly weather forecast",
+		[
+			"Stündliche Wettervorhersage",
+			"Pronóstico del tiempo por horas",
+			"Weersverwachting per uur",
+		],
+	),
+	(
+		"The hourly temperature of two places on one chart",
+		[
+			"Die stündliche Temperatur zweier Orte in einem Diagramm",
+			"La temperatura por horas de dos lugares en un gráfico",
+			"De temperatuur per uur van twee plaatsen in één grafiek",
+		],
+	),
+	(
+		"How much precipitation adds up to over the coming hours",
+		[
+			"Wie viel Niederschlag in den kommenden Stunden zusammenkommt",
+			"Cuánta precipitación se acumula en las próximas horas",
+			"Hoeveel neerslag er de komende uren bij elkaar valt",
+		],
+	),
+	(
+		"The hottest hour of the next 48 hours",
+		[
+			"Die heißeste Stunde der nächsten 48 Stunden",
+			"La hora más calurosa de las próximas 48 horas",
+			"Het warmste uur van de komende 48 uur",
+		],
+	),
+	(
+		"The coldest hour of the next 48 hours",
+		[
+			"Die kälteste Stunde der nächsten 48 Stunden",
+			"La hora más fría de las próximas 48 horas",
+			"Het koudste uur van de komende 48 uur",
+		],
+	),
+	(
+		"Sea-level and surface air pressure for the next two days",
+		[
+			"Luftdruck auf Meereshöhe und am Boden für die nächsten zwei Tage",
+			"Presión a nivel del mar y en superficie para los próximos dos días",
+			"Luchtdruk op zeeniveau en aan de grond voor de komende twee dagen",
+		],
+	),
+	(
+		"Status of the bot and the services it relies on",
+		[
+			"Status des Bots und der Dienste, die er nutzt",
+			"Estado del bot y de los servicios de los que depende",
+			"Status van de bot en de diensten waar hij op steunt",
+		],
+	),
+	(
+		"Next sunrise and sunset, with day length and twilight",
+		[
+			"Nächster Sonnenaufgang und -untergang, mit Tageslänge und Dämmerung",
+			"Próxima salida y puesta del sol, con duración del día y crepúsculo",
+			"Volgende zonsopkomst en -ondergang, met daglengte en schemering",
+		],
+	),
+	// Replies.
+	("Error", ["Fehler", "Error", "Fout"]),
+	(
+		"No location set, and no location provided",
+		[
+			"Kein Ort festgelegt und kein Ort angegeben",
+			"No hay ninguna ubicación guardada ni indicada",
+			"Geen locatie ingesteld en geen locatie opgegeven",
+		],
+	),
+	(
+		"No location set, and no location to search near provided",
+		[
+			"Kein Ort festgelegt und kein Ort angegeben, in dessen Nähe gesucht werden soll",
+			"No hay ninguna ubicación guardada ni indicada cerca de la que buscar",
+			"Geen locatie ingesteld en geen locatie opgegeven om in de buurt van te zoeken",
+		],
+	),
+	(
+		"No geocoding results",
+		[
+			"Kein Ort mit diesem Namen gefunden",
+			"No se encontró ningún lugar con ese nombre",
+			"Geen plaats met die naam gevonden",
+		],
+	),
+	(
+		"No forecast data available for this location.",
+		[
+			"Für diesen Ort sind keine Vorhersagedaten verfügbar.",
+			"No hay datos de pronóstico para esta ubicación.",
+			"Er zijn geen verwachtingsgegevens voor deze locatie.",
+		],
+	),
+	(
+		"Weather service is temporarily unavailable",
+		[
+			"Der Wetterdienst ist vorübergehend nicht erreichbar",
+			"El servicio meteorológico no está disponible temporalmente",
+			"De weerdienst is tijdelijk niet bereikbaar",
+		],
+	),
+	(
+		"The weather service took too long to respond",
+		[
+			"Der Wetterdienst hat zu lange für eine Antwort gebraucht",
+			"El servicio meteorológico tardó demasiado en responder",
+			"De weerdienst deed er te lang over om te antwoorden",
+		],
+	),
+];
+
+fn translations(english: &str) -> Option<&'static [&'static str; 3]> {
+	CATALOG
+		.iter()
+		.find(|(key, _)| *key == english)
+		.map(|(_, translations)| translations)
+}
+
+/// The text in the language of the Discord locale, or in English if there is no translation for it.
+pub fn translate<'a>(english: &'a str, locale: &str) -> &'a str {
+	match (Language::from_locale(locale), translations(english)) {
+		(Some(language), Some(translations)) => translations[language.index()],
+		_ => english,
+	}
+}
+
+/// A command with its English description, and the description in every other language it has been translated into.
+pub fn command(name: &str, description: &str) -> CreateCommand {
+	let mut command = CreateCommand::new(name).description(description);
+	if let Some(translations) = translations(description) {
+		for language in Language::ALL {
+			for &locale in language.discord_locales() {
+				command = command.description_localized(locale, translations[language.index()]);
+			}
+		}
+	}
+	command
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn translated_with_english_fallback() {
+		assert_eq!(translate("Current weather", "de"), "Aktuelles Wetter");
+		assert_eq!(translate("Current weather", "es-419"), "Tiempo actual");
+		assert_eq!(translate("Current weather", "en-GB"), "Current weather");
+		assert_eq!(translate("Current weather", "fr"), "Current weather");
+		assert_eq!(translate("Not in the catalog", "nl"), "Not in the catalog");
+	}
+	#[test]
+	fn translations_fit_in_descriptions() {
+		for (english, translations) in CATALOG {
+			for translation in translations {
+				assert!(
+					(1..=100).contains(&translation.chars().count()),
+					"translation of \"{english}\" doesn't fit: {translation}"
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,119 @@
+use ab_glyph::{FontRef, PxScale};
+use graph::{
+	drawing::{Padding, Spacing},
+	generic_graph::Chart,
+	text_box::{TextBox, TextSegment},
+};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommandOption};
+
+use crate::{error::Error, util::CommandInteractionExt};
+
+/// How the panels of a forecast chart are labelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+	/// Every panel has its own header describing it.
+	Headers,
+	/// Panels are bare, with one combined legend above them all, for a denser overview.
+	Compact,
+}
+
+impl Layout {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"headers" => Some(Self::Headers),
+			"compact" => Some(Self::Compact),
+			_ => None,
+		}
+	}
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Headers => "headers",
+			Self::Compact => "compact",
+		}
+	}
+	/// The layout chosen in the command's "layout" option, or headers if there is none.
+	pub fn from_interaction(interaction: &CommandInteraction) -> Result<Self, Error> {
+		match interaction.string_option("layout") {
+			Some(name) => {
+				Self::from_name(name).ok_or_else(|| Error::custom_unfriendly("Unknown layout"))
+			}
+			None => Ok(Self::Headers),
+		}
+	}
+	/// The header of a single panel, if this layout has them.
+	pub fn header(
+		self,
+		segments: &[TextSegment],
+		font: &FontRef<'static>,
+		scale: PxScale,
+		width: u32,
+	) -> Option<TextBox> {
+		match self {
+			Self::Headers => Some(TextBox::new(segments, font.clone(), scale, width, 2)),
+			Self::Compact => None,
+		}
+	}
+	/// A chart holding nothing but the combined legend, to go above the panels, if this layout has one.
+	///
+	/// The legend gets the same width and padding as panels `columns` wide, so it lines up with them in the composite.
+	pub fn legend(
+		self,
+		segments: &[TextSegment],
+		font: &FontRef<'static>,
+		scale: PxScale,
+		columns: usize,
+		spacing: Spacing,
+		padding: Padding,
+	) -> Option<Chart> {
+		match self {
+			Self::Headers => None,
+			Self::Compact => Some(text_chart(segments, font, scale, columns, spacing, padding)),
+		}
+	}
+}
+
+/// A chart holding nothing but text, as wide as a chart `columns` wide with this spacing and padding, for stacking text with `composite`.
+pub fn text_chart(
+	segments: &[TextSegment],
+	font: &FontRef<'static>,
+	scale: PxScale,
+	columns: usize,
+	spacing: Spacing,
+	padding: Padding,
+) -> Chart {
+	let text = TextBox::new(
+		segments,
+		font.clone(),
+		scale,
+		(columns - 1) as u32 * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		columns,
+		0,
+		spacing,
+		Padding {
+			above: padding.above + text.height(),
+			below: 0,
+			..padding
+		},
+	);
+	chart.draw(text);
+	chart
+}
+
+/// The height a panel's header adds above it, which is nothing if it has none.
+pub fn header_height(header: &Option<TextBox>) -> u32 {
+	header.as_ref().map_or(0, TextBox::height)
+}
+
+pub fn create_layout_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::String,
+		"layout",
+		"Whether every panel gets its own header, or they share one compact legend.",
+	)
+	.add_string_choice("headers", "headers")
+	.add_string_choice("compact", "compact")
+	.required(false)
+}
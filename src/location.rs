@@ -3,10 +3,10 @@ use std::{fmt::Display, sync::LazyLock};
 use itertools::Itertools;
 use regex::Regex;
 use reqwest::Client;
-use serenity::all::{CommandInteraction, GuildId, UserId};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommandOption, GuildId, UserId};
 use sqlx::{query, Pool, Sqlite};
 
-use crate::{error::Error, geocoding::GeocodingResult};
+use crate::{error::Error, geocoding::GeocodingResult, util::CommandInteractionExt};
 
 /// Latitude or longitude.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -54,6 +54,26 @@ static SIMPLE_COORDS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
 static FANCIER_COORDS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
 	Regex::new(r#"(?i)^(\d{1,3})°\s*(\d{1,2})[\u2032']\s*(\d{1,2})[″"]\s*([NESW])\s*,?\s*(\d{1,3})°\s*(\d{1,2})[\u2032']\s*(\d{1,2})[″"]\s*([NESW])$"#).unwrap()
 });
+static DECIMAL_MINUTES_COORDS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r#"(?i)^(\d{1,3})°\s*(\d{1,2}(?:\.\d+)?)[\u2032']\s*([NESW])\s*,?\s*(\d{1,3})°\s*(\d{1,2}(?:\.\d+)?)[\u2032']\s*([NESW])$"#).unwrap()
+});
+static MAP_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"(?i)^https?://(?:www\.)?(?:google\.[a-z.]+/maps|openstreetmap\.org)\b").unwrap()
+});
+/// Where map URLs keep their coordinates, in order of preference: a searched or marked point before the centre of the view.
+static MAP_URL_COORDS_REGEXES: LazyLock<[Regex; 4]> = LazyLock::new(|| {
+	let number = r"([+-]?\d+(?:\.\d+)?)";
+	[
+		format!(r"(?i)[?&](?:q|query)={number}(?:,|%2C)(?:\s|\+|%20)*{number}"),
+		format!(r"(?i)[?&]mlat={number}&mlon={number}"),
+		format!(r"/@{number},{number}"),
+		format!(r"#map=\d+(?:\.\d+)?/{number}/{number}"),
+	]
+	.map(|pattern| Regex::new(&pattern).unwrap())
+});
+
+/// The digits of Open Location Codes, in order of value.
+const PLUS_CODE_DIGITS: &str = "23456789CFGHJMPQRVWX";
 
 #[derive(Debug, Clone, Copy)]
 pub struct Coordinates {
@@ -64,19 +84,34 @@ pub struct Coordinates {
 }
 
 impl Coordinates {
+	/// Longitudes outside of [-180, 180) are wrapped around, so 180° E and 180° W end up as the same meridian.
 	pub fn new(latitude: f32, longitude: f32) -> Self {
+		let longitude = if (-180.0..180.0).contains(&longitude) {
+			longitude
+		} else {
+			(longitude + 180.0).rem_euclid(360.0) - 180.0
+		};
 		Self {
 			latitude,
 			longitude,
 		}
 	}
+	/// Coordinates, unless they are off the map. Longitude can be exactly ±180.
+	fn checked(latitude: f32, longitude: f32) -> Option<Self> {
+		((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+			.then(|| Self::new(latitude, longitude))
+	}
 	/// Attempt to parse a string describing coordinates.
 	///
-	/// It currently supports two formats:
+	/// It currently supports four formats:
 	///
 	/// Decimal: `52.87619043426636, -118.0795914761888` (Google Maps gives this on right click) (comma optional)
 	///
 	/// Degrees, minutes, seconds: `52° 52′ 34″ N, 118° 4′ 46″ W` (does not support decimals, spaces and comma optional, `′` and `″` can be `'` and `"` instead)
+	///
+	/// Degrees, decimal minutes: `52° 52.57′ N, 118° 4.78′ W` (spaces and comma optional, `′` can be `'` instead)
+	///
+	/// Plus code: `9543VWGC+F5` (only full codes, not the short ones that need a nearby place to go with them)
 	pub fn parse(input: &str) -> Option<Self> {
 		if let Some(captures) = SIMPLE_COORDS_REGEX.captures(input) {
 			if let Some((Ok(latitude), Ok(longitude))) = captures
@@ -86,10 +121,7 @@ impl Coordinates {
 				.map(|capture| capture.as_str().parse::<f32>())
 				.collect_tuple()
 			{
-				return Some(Self {
-					latitude,
-					longitude,
-				});
+				return Self::checked(latitude, longitude);
 			}
 		}
 
@@ -118,18 +150,121 @@ impl Coordinates {
 				.into_iter()
 				.filter_map(|str| str.parse::<f32>().ok())
 				.collect_tuple()?;
-				if direction_a.geoaxis == direction_b.geoaxis {
-					return None; // Invalid combination of directions
-				}
 				let magnitude_a = hours_a + minutes_a / 60.0 + seconds_a / 60.0 / 60.0;
 				let magnitude_b = hours_b + minutes_b / 60.0 + seconds_b / 60.0 / 60.0;
-				let mut coordinates = Self::new(0.0, 0.0);
-				*coordinates.get_axis_mut(direction_a.geoaxis) = magnitude_a * direction_a.sign;
-				*coordinates.get_axis_mut(direction_b.geoaxis) = magnitude_b * direction_b.sign;
-				return Some(coordinates);
+				return Self::from_directions(magnitude_a, direction_a, magnitude_b, direction_b);
+			}
+		}
+
+		if let Some(captures) = DECIMAL_MINUTES_COORDS_REGEX.captures(input) {
+			if let Some((degrees_a, minutes_a, direction_a, degrees_b, minutes_b, direction_b)) =
+				captures
+					.iter()
+					.skip(1)
+					.flatten()
+					.map(|capture| capture.as_str())
+					.collect_tuple()
+			{
+				let direction_a = Direction::get(direction_a.chars().next().unwrap());
+				let direction_b = Direction::get(direction_b.chars().next().unwrap());
+				let (degrees_a, minutes_a, degrees_b, minutes_b) =
+					[degrees_a, minutes_a, degrees_b, minutes_b]
+						.into_iter()
+						.filter_map(|str| str.parse::<f32>().ok())
+						.collect_tuple()?;
+				if minutes_a >= 60.0 || minutes_b >= 60.0 {
+					return None;
+				}
+				let magnitude_a = degrees_a + minutes_a / 60.0;
+				let magnitude_b = degrees_b + minutes_b / 60.0;
+				return Self::from_directions(magnitude_a, direction_a, magnitude_b, direction_b);
 			}
 		}
-		None
+
+		Self::from_plus_code(input)
+	}
+	/// The coordinates in a Google Maps or OpenStreetMap URL, like `https://www.google.com/maps/@52.5,13.4,12z` or `https://www.openstreetmap.org/#map=12/52.5/13.4`.
+	pub fn from_map_url(input: &str) -> Option<Self> {
+		if !MAP_URL_REGEX.is_match(input) {
+			return None;
+		}
+		MAP_URL_COORDS_REGEXES.iter().find_map(|regex| {
+			let captures = regex.captures(input)?;
+			let latitude = captures[1].parse().ok()?;
+			let longitude = captures[2].parse().ok()?;
+			Self::checked(latitude, longitude)
+		})
+	}
+	/// Coordinates from two magnitudes with their compass directions, which have to be one latitude and one longitude.
+	fn from_directions(
+		magnitude_a: f32,
+		direction_a: Direction,
+		magnitude_b: f32,
+		direction_b: Direction,
+	) -> Option<Self> {
+		if direction_a.geoaxis == direction_b.geoaxis {
+			return None; // Invalid combination of directions
+		}
+		let mut coordinates = Self::new(0.0, 0.0);
+		*coordinates.get_axis_mut(direction_a.geoaxis) = magnitude_a * direction_a.sign;
+		*coordinates.get_axis_mut(direction_b.geoaxis) = magnitude_b * direction_b.sign;
+		Self::checked(coordinates.latitude, coordinates.longitude)
+	}
+	/// The centre of the area a full Open Location Code describes.
+	///
+	/// See https://github.com/google/open-location-code/blob/main/Documentation/Specification/olc_definition.adoc
+	fn from_plus_code(code: &str) -> Option<Self> {
+		let (before, after) = code.split_once('+')?;
+		if before.len() != 8 || after.len() == 1 {
+			return None;
+		}
+		// Codes for larger areas are padded with zeroes, and have nothing after the plus.
+		let digits = before.trim_end_matches('0');
+		if digits.is_empty() || digits.len() % 2 != 0 || (digits.len() < 8 && !after.is_empty()) {
+			return None;
+		}
+		let values = digits
+			.chars()
+			.chain(after.chars())
+			.map(|char| PLUS_CODE_DIGITS.find(char.to_ascii_uppercase()))
+			.collect::<Option<Vec<_>>>()?;
+		let (pairs, grid) = values.split_at(values.len().min(10));
+
+		let mut latitude = -90.0;
+		let mut longitude = -180.0;
+		// The first pairs of digits each divide the area into 20 by 20.
+		let mut resolution = 20.0;
+		let mut latitude_resolution = resolution;
+		let mut longitude_resolution = resolution;
+		for pair in pairs.chunks(2) {
+			latitude += pair[0] as f64 * resolution;
+			longitude += pair[1] as f64 * resolution;
+			latitude_resolution = resolution;
+			longitude_resolution = resolution;
+			resolution /= 20.0;
+		}
+		// Any digits after that each divide it into 5 rows by 4 columns.
+		for &value in grid {
+			latitude_resolution /= 5.0;
+			longitude_resolution /= 4.0;
+			latitude += (value / 4) as f64 * latitude_resolution;
+			longitude += (value % 4) as f64 * longitude_resolution;
+		}
+		Self::checked(
+			(latitude + latitude_resolution / 2.0) as f32,
+			(longitude + longitude_resolution / 2.0) as f32,
+		)
+	}
+	/// Great-circle distance in kilometres, by the haversine formula.
+	pub fn distance_km(self, other: Self) -> f32 {
+		const EARTH_RADIUS_KM: f64 = 6371.0;
+		let latitude_a = (self.latitude as f64).to_radians();
+		let latitude_b = (other.latitude as f64).to_radians();
+		let latitude_delta = latitude_b - latitude_a;
+		let longitude_delta = ((other.longitude - self.longitude) as f64).to_radians();
+		let haversine = (latitude_delta / 2.0).sin().powi(2)
+			+ latitude_a.cos() * latitude_b.cos() * (longitude_delta / 2.0).sin().powi(2);
+		(2.0 * EARTH_RADIUS_KM * haversine.sqrt().asin()) as f32
 	}
 	fn get_axis_mut(&mut self, geo_axis: GeoAxis) -> &mut f32 {
 		match geo_axis {
@@ -145,21 +280,82 @@ impl Display for Coordinates {
 	}
 }
 
+/// The domain of locations set outside of any server, such as in direct messages with the bot installed to the user. These also apply in servers where the user has not set a location.
+pub const USER_DOMAIN: i64 = 0;
+
+/// The domain to save a user's location under: the server, or the user's own domain outside of servers.
+pub fn location_domain(guild: Option<GuildId>) -> i64 {
+	guild.map_or(USER_DOMAIN, |guild| guild.get() as i64)
+}
+
+/// An elevation in whole metres, or "unspecified".
+pub fn format_elevation(elevation: Option<f32>) -> String {
+	elevation.map_or_else(
+		|| String::from("unspecified"),
+		|elevation| format!("{elevation:.0} m"),
+	)
+}
+
+/// The label of a saved location set without one.
+pub const DEFAULT_LABEL: &str = "default";
+/// The longest label a saved location can have, in characters.
+const MAX_LABEL_LENGTH: usize = 32;
+
+/// The saved location label given in the command's "label" option, trimmed and checked, if there is one.
+pub fn label_option(interaction: &CommandInteraction) -> Result<Option<&str>, Error> {
+	interaction
+		.string_option("label")
+		.map(validate_label)
+		.transpose()
+}
+
+fn validate_label(label: &str) -> Result<&str, Error> {
+	let label = label.trim();
+	if label.is_empty() {
+		return Err(Error::friendly("Labels can't be empty"));
+	}
+	if label.chars().count() > MAX_LABEL_LENGTH {
+		return Err(Error::friendly(format!(
+			"Labels can be at most {MAX_LABEL_LENGTH} characters long"
+		)));
+	}
+	if label.chars().any(char::is_control) {
+		return Err(Error::friendly(
+			"Labels can't contain control characters like line breaks",
+		));
+	}
+	Ok(label)
+}
+
+pub fn create_label_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::String,
+		"label",
+		"Which of your saved locations to use, if not your primary one.",
+	)
+	.required(false)
+}
+
 /// A location, consisting of coordinates and optional information about it.
 pub struct Location {
 	name: Option<String>,
 	coordinates: Coordinates,
 	country: Option<String>,
 	feature_code: Option<String>,
+	/// In metres above sea level.
+	elevation: Option<f32>,
 }
 
 impl Location {
+	/// Open-Meteo sometimes gives empty strings rather than leaving fields out, which are treated as missing.
 	pub fn from_geocoding_result(geocoding: GeocodingResult) -> Self {
+		let non_empty = |value: String| Some(value).filter(|value| !value.trim().is_empty());
 		Self {
 			name: Some(geocoding.name),
 			coordinates: Coordinates::new(geocoding.latitude, geocoding.longitude),
-			country: geocoding.country,
-			feature_code: Some(geocoding.feature_code),
+			country: geocoding.country.and_then(non_empty),
+			feature_code: non_empty(geocoding.feature_code),
+			elevation: geocoding.elevation,
 		}
 	}
 	pub fn from_coords(coordinates: Coordinates) -> Self {
@@ -168,31 +364,75 @@ impl Location {
 			coordinates,
 			country: None,
 			feature_code: None,
+			elevation: None,
 		}
 	}
-	pub async fn try_from_arg(arg: &str, client: &Client) -> Result<Self, Error> {
+	pub async fn try_from_arg(
+		arg: &str,
+		client: &Client,
+		database: &Pool<Sqlite>,
+	) -> Result<Self, Error> {
+		if let Some(coords) = Coordinates::from_map_url(arg.trim()) {
+			return Ok(Self::from_coords(coords));
+		}
 		if let Some(coords) = Coordinates::parse(arg) {
 			return Ok(Self::from_coords(coords));
 		}
-		GeocodingResult::get(arg, client)
+		GeocodingResult::get(arg, client, database)
 			.await
 			.map(Self::from_geocoding_result)
 	}
+	/// Get the user's location with this label, or their primary one without a label, for the server, falling back to the ones they set outside of servers.
 	pub async fn get_for_user(
 		database: &Pool<Sqlite>,
 		user: UserId,
-		domain: GuildId,
+		guild: Option<GuildId>,
+		label: Option<&str>,
 	) -> Result<Option<Self>, Error> {
 		let user = user.get() as i64;
-		let domain = domain.get() as i64;
+		let domain = location_domain(guild);
+		let user_domain = USER_DOMAIN;
 		let Some(result) = query!(
 			"
-			SELECT place_name, latitude, longitude, country, feature_code
+			SELECT place_name, latitude, longitude, country, feature_code, elevation
 			FROM user_locations
-			WHERE domain = ? AND user = ?
+			WHERE (domain = ? OR domain = ?) AND user = ? AND (label = ? OR (? IS NULL AND is_primary))
+			ORDER BY domain = ?
+			LIMIT 1
 			",
 			domain,
-			user
+			user_domain,
+			user,
+			label,
+			label,
+			user_domain
+		)
+		.fetch_optional(database)
+		.await?
+		else {
+			return Ok(None);
+		};
+		Ok(Some(Self {
+			name: result.place_name,
+			coordinates: Coordinates::new(result.latitude as f32, result.longitude as f32),
+			country: result.country,
+			feature_code: result.feature_code.filter(|code| !code.is_empty()),
+			elevation: result.elevation.map(|elevation| elevation as f32),
+		}))
+	}
+	/// Get the default location a server's admins set for everyone in it.
+	pub async fn get_for_guild(
+		database: &Pool<Sqlite>,
+		guild: GuildId,
+	) -> Result<Option<Self>, Error> {
+		let guild = guild.get() as i64;
+		let Some(result) = query!(
+			"
+			SELECT place_name, latitude, longitude, country, feature_code, elevation
+			FROM guild_locations
+			WHERE guild = ?
+			",
+			guild
 		)
 		.fetch_optional(database)
 		.await?
@@ -203,55 +443,132 @@ impl Location {
 			name: result.place_name,
 			coordinates: Coordinates::new(result.latitude as f32, result.longitude as f32),
 			country: result.country,
-			feature_code: result.feature_code,
+			feature_code: result.feature_code.filter(|code| !code.is_empty()),
+			elevation: result.elevation.map(|elevation| elevation as f32),
 		}))
 	}
+	/// Save the location as the server's default, replacing any it already had.
+	pub async fn set_for_guild(
+		&self,
+		database: &Pool<Sqlite>,
+		guild: GuildId,
+	) -> Result<(), Error> {
+		let guild = guild.get() as i64;
+		let feature_code = self.stored_feature_code();
+		query!(
+			"
+			INSERT INTO guild_locations (guild, place_name, latitude, longitude, country, feature_code, elevation)
+			VALUES (?, ?, ?, ?, ?, ?, ?)
+			",
+			guild,
+			self.name,
+			self.coordinates.latitude,
+			self.coordinates.longitude,
+			self.country,
+			feature_code,
+			self.elevation
+		)
+		.execute(database)
+		.await?;
+		Ok(())
+	}
+	/// Save the location under the label, replacing any the user already had with that label in the same domain.
+	///
+	/// It becomes the primary location if `primary` is set or if the user has no other primary location there yet. Returns whether it did.
 	pub async fn set_for_user(
 		&self,
 		database: &Pool<Sqlite>,
 		user: UserId,
-		domain: GuildId,
-	) -> Result<(), Error> {
+		guild: Option<GuildId>,
+		label: &str,
+		primary: bool,
+	) -> Result<bool, Error> {
 		let user = user.get() as i64;
-		let domain = domain.get() as i64;
+		let domain = location_domain(guild);
+		let feature_code = self.stored_feature_code();
+		let mut transaction = database.begin().await?;
+		let other_primary = query!(
+			"
+			SELECT EXISTS (
+				SELECT 1 FROM user_locations
+				WHERE domain = ? AND user = ? AND is_primary AND label != ?
+			) AS \"other_primary!: bool\"
+			",
+			domain,
+			user,
+			label
+		)
+		.fetch_one(&mut *transaction)
+		.await?
+		.other_primary;
+		let primary = primary || !other_primary;
+		if primary {
+			query!(
+				"
+				UPDATE user_locations SET is_primary = FALSE
+				WHERE domain = ? AND user = ?
+				",
+				domain,
+				user
+			)
+			.execute(&mut *transaction)
+			.await?;
+		}
 		query!(
 			"
-			INSERT INTO user_locations (domain, user, place_name, latitude, longitude, country, feature_code)
-			VALUES (?, ?, ?, ?, ?, ?, ?)
+			INSERT INTO user_locations (domain, user, label, is_primary, place_name, latitude, longitude, country, feature_code, elevation)
+			VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 		",
 			domain,
 			user,
+			label,
+			primary,
 			self.name,
 			self.coordinates.latitude,
 			self.coordinates.longitude,
 			self.country,
-			self.feature_code
+			feature_code,
+			self.elevation
 		)
-		.execute(database)
+		.execute(&mut *transaction)
 		.await?;
-		Ok(())
+		transaction.commit().await?;
+		Ok(primary)
+	}
+	/// The feature code as saved. Named places without one store it empty, since the tables require named places to have a feature code.
+	fn stored_feature_code(&self) -> Option<&str> {
+		match (&self.name, &self.feature_code) {
+			(_, Some(code)) => Some(code),
+			(Some(_), None) => Some(""),
+			(None, None) => None,
+		}
 	}
 	pub async fn get_from_argument_or_for_user(
 		interaction: &CommandInteraction,
 		client: &Client,
 		database: &Pool<Sqlite>,
 	) -> Result<Self, Error> {
-		let location = match interaction
-			.data
-			.options
-			.first()
-			.and_then(|option| option.value.as_str())
-		{
-			Some(arg) => Location::try_from_arg(arg, client).await?,
-			None => Location::get_for_user(
-				database,
-				interaction.user.id,
-				interaction
-					.guild_id
-					.ok_or_else(|| Error::custom_unfriendly("Somehow could not get guild ID"))?,
-			)
-			.await?
-			.ok_or_else(|| Error::friendly("No location set, and no location provided"))?,
+		let location = match interaction.string_option("place") {
+			Some(arg) => Location::try_from_arg(arg, client, database).await?,
+			None => {
+				let label = label_option(interaction)?;
+				let location = Location::get_for_user(
+					database,
+					interaction.user.id,
+					interaction.guild_id,
+					label,
+				)
+				.await?;
+				// Only the primary location falls back to the server's, since a label asks for a specific one of the user's own.
+				let location = match (location, label, interaction.guild_id) {
+					(None, None, Some(guild)) => Location::get_for_guild(database, guild).await?,
+					(location, _, _) => location,
+				};
+				location.ok_or_else(|| match label {
+					Some(label) => Error::friendly(format!("No location saved as \"{label}\"")),
+					None => Error::friendly("No location set, and no location provided"),
+				})?
+			}
 		};
 		Ok(location)
 	}
@@ -261,18 +578,117 @@ impl Location {
 	pub fn coordinates(&self) -> Coordinates {
 		self.coordinates
 	}
-	pub fn country(&self) -> &str {
-		self.country.as_deref().unwrap_or("unspecified")
-	}
 	pub fn feature_code(&self) -> &str {
 		self.feature_code.as_deref().unwrap_or("unspecified")
 	}
+	pub fn elevation(&self) -> String {
+		format_elevation(self.elevation)
+	}
+	/// The place name if there is one, and the coordinates otherwise.
+	pub fn short_description(&self) -> String {
+		self.name
+			.clone()
+			.unwrap_or_else(|| self.coordinates.to_string())
+	}
+}
+
+/// Like "Bergen, Norway (60.39, 5.32)", leaving out whatever isn't known. Without a name, just the coordinates.
+impl Display for Location {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match (&self.name, &self.country) {
+			(Some(name), Some(country)) => write!(f, "{name}, {country} ({})", self.coordinates),
+			(Some(name), None) => write!(f, "{name} ({})", self.coordinates),
+			(None, _) => write!(f, "{}", self.coordinates),
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 
+	#[test]
+	fn empty_geocoding_fields_unspecified() {
+		let result = GeocodingResult {
+			id: 0,
+			name: String::from("Ny-Ålesund"),
+			latitude: 78.92,
+			longitude: 11.93,
+			elevation: None,
+			feature_code: String::new(),
+			country_code: None,
+			country: Some(String::from(" ")),
+			admin1: None,
+			population: None,
+		};
+		let location = Location::from_geocoding_result(result);
+		assert_eq!(location.feature_code(), "unspecified");
+		// The blank country is left out.
+		assert_eq!(location.to_string(), "Ny-Ålesund (78.92, 11.93)");
+	}
+	#[tokio::test]
+	async fn missing_feature_code_saved() {
+		let database = crate::database::in_memory().await;
+		let location = Location {
+			name: Some(String::from("Ny-Ålesund")),
+			coordinates: Coordinates::new(78.92, 11.93),
+			country: None,
+			feature_code: None,
+			elevation: None,
+		};
+		let user = UserId::new(1);
+		let guild = GuildId::new(2);
+		location
+			.set_for_user(&database, user, None, DEFAULT_LABEL, true)
+			.await
+			.unwrap();
+		location.set_for_guild(&database, guild).await.unwrap();
+		let saved = Location::get_for_user(&database, user, None, None)
+			.await
+			.unwrap()
+			.unwrap();
+		assert_eq!(saved.name(), "Ny-Ålesund");
+		assert_eq!(saved.feature_code(), "unspecified");
+		let saved = Location::get_for_guild(&database, guild)
+			.await
+			.unwrap()
+			.unwrap();
+		assert_eq!(saved.feature_code(), "unspecified");
+	}
+	#[test]
+	fn location_display() {
+		let location = Location {
+			name: Some(String::from("Bergen")),
+			coordinates: Coordinates::new(60.39, 5.32),
+			country: Some(String::from("Norway")),
+			feature_code: Some(String::from("PPLA")),
+			elevation: Some(12.0),
+		};
+		assert_eq!(location.to_string(), "Bergen, Norway (60.39, 5.32)");
+		let coordinates = Location::from_coords(Coordinates::new(-33.9, 18.42));
+		assert_eq!(coordinates.to_string(), "-33.9, 18.42");
+	}
+	#[test]
+	fn labels_validated() {
+		assert_eq!(validate_label("  work ").unwrap(), "work");
+		assert!(matches!(validate_label("   "), Err(Error::Friendly(_))));
+		assert!(matches!(
+			validate_label(&"a".repeat(MAX_LABEL_LENGTH + 1)),
+			Err(Error::Friendly(_))
+		));
+		assert!(matches!(
+			validate_label("home\nwork"),
+			Err(Error::Friendly(_))
+		));
+	}
+
+	#[test]
+	fn elevation_formatted() {
+		assert_eq!(format_elevation(Some(1062.4)), "1062 m");
+		assert_eq!(format_elevation(Some(-2.0)), "-2 m");
+		assert_eq!(format_elevation(None), "unspecified");
+	}
+
 	fn is_close_enough(num_one: f32, num_two: f32, precision: i32) -> bool {
 		let delta = num_one.abs() * 1.0 / 10.0f32.powi(precision);
 		let start = num_one - delta;
@@ -313,4 +729,119 @@ mod tests {
 		assert!(is_close_enough(coords_a.latitude, coords_b.latitude, 5));
 		assert!(is_close_enough(coords_a.longitude, coords_b.longitude, 5));
 	}
+	#[test]
+	fn coord_parsing_decimal_minutes() {
+		let coords_a = Coordinates::parse("52.87619043426636, -118.0795914761888").unwrap();
+		let coords_b = Coordinates::parse("52° 52.57′ N, 118° 4.78′ W").unwrap();
+		assert!(is_close_enough(coords_a.latitude, coords_b.latitude, 4));
+		assert!(is_close_enough(coords_a.longitude, coords_b.longitude, 4));
+
+		let coords = Coordinates::parse("1°2.5'S4°5'e").unwrap();
+		assert!(is_close_enough(-(1.0 + 2.5 / 60.0), coords.latitude, 6));
+		assert!(is_close_enough(4.0 + 5.0 / 60.0, coords.longitude, 6));
+		assert!(Coordinates::parse("1°60.5'N 4°5'E").is_none());
+	}
+	#[test]
+	fn coord_parsing_plus_code() {
+		let coords_a = Coordinates::parse("52.87619043426636, -118.0795914761888").unwrap();
+		let coords_b = Coordinates::parse("9543VWGC+F5").unwrap();
+		assert!(is_close_enough(coords_a.latitude, coords_b.latitude, 4));
+		assert!(is_close_enough(coords_a.longitude, coords_b.longitude, 4));
+		let longer = Coordinates::parse("9543vwgc+f5f").unwrap();
+		assert!(is_close_enough(coords_a.latitude, longer.latitude, 5));
+		assert!(is_close_enough(coords_a.longitude, longer.longitude, 5));
+
+		let zurich = Coordinates::parse("8FVC9G8F+6W").unwrap();
+		assert!(is_close_enough(47.3656, zurich.latitude, 4));
+		assert!(is_close_enough(8.5248, zurich.longitude, 4));
+		// Padded codes describe a larger area.
+		let padded = Coordinates::parse("8FVC0000+").unwrap();
+		assert!(is_close_enough(47.5, padded.latitude, 6));
+		assert!(is_close_enough(8.5, padded.longitude, 6));
+	}
+	#[test]
+	fn coord_parsing_unrecognized() {
+		assert!(Coordinates::parse("9G8F+6W").is_none()); // Short code
+		assert!(Coordinates::parse("8FVC9G8F+6").is_none());
+		assert!(Coordinates::parse("8FVC9G0F+").is_none());
+		assert!(Coordinates::parse("8FVC9G8A+6W").is_none());
+		assert!(Coordinates::parse("Zürich").is_none());
+	}
+	#[test]
+	fn coord_parsing_poles_and_antimeridian() {
+		let north = Coordinates::parse("90, 180").unwrap();
+		assert_eq!(north.latitude, 90.0);
+		assert_eq!(north.longitude, -180.0);
+		let south = Coordinates::parse("-90, -180").unwrap();
+		assert_eq!(south.latitude, -90.0);
+		assert_eq!(south.longitude, -180.0);
+		let fancy = Coordinates::parse(r#"90°0'0"S 180°0'0"E"#).unwrap();
+		assert_eq!(fancy.latitude, -90.0);
+		assert_eq!(fancy.longitude, -180.0);
+	}
+	#[test]
+	fn coord_parsing_off_the_map() {
+		assert!(Coordinates::parse("90.5, 0").is_none());
+		assert!(Coordinates::parse("0, -180.5").is_none());
+		assert!(Coordinates::parse(r#"91°0'0"N 0°0'0"E"#).is_none());
+	}
+	#[test]
+	fn coords_from_google_maps_url() {
+		let view = Coordinates::from_map_url("https://www.google.com/maps/@52.5,13.4,12z").unwrap();
+		assert_eq!((view.latitude, view.longitude), (52.5, 13.4));
+		let search =
+			Coordinates::from_map_url("https://www.google.com/maps?q=-33.86,151.21").unwrap();
+		assert_eq!((search.latitude, search.longitude), (-33.86, 151.21));
+		// The searched point wins over the centre of the view.
+		let both =
+			Coordinates::from_map_url("https://www.google.co.uk/maps/@51.4,-0.2,10z?q=51.5,-0.12")
+				.unwrap();
+		assert_eq!((both.latitude, both.longitude), (51.5, -0.12));
+		let api = Coordinates::from_map_url(
+			"https://www.google.com/maps/search/?api=1&query=51.5%2C-0.12",
+		)
+		.unwrap();
+		assert_eq!((api.latitude, api.longitude), (51.5, -0.12));
+		assert!(Coordinates::from_map_url("https://www.google.com/maps/place/Berlin").is_none());
+	}
+	#[test]
+	fn coords_from_openstreetmap_url() {
+		let view =
+			Coordinates::from_map_url("https://www.openstreetmap.org/#map=12/52.5/13.4").unwrap();
+		assert_eq!((view.latitude, view.longitude), (52.5, 13.4));
+		let marker = Coordinates::from_map_url(
+			"https://www.openstreetmap.org/?mlat=47.37&mlon=8.54#map=10/47.3/8.5",
+		)
+		.unwrap();
+		assert_eq!((marker.latitude, marker.longitude), (47.37, 8.54));
+		let search =
+			Coordinates::from_map_url("https://www.openstreetmap.org/search?query=64.1,-21.9")
+				.unwrap();
+		assert_eq!((search.latitude, search.longitude), (64.1, -21.9));
+		assert!(
+			Coordinates::from_map_url("https://www.openstreetmap.org/#map=12/95/13.4").is_none()
+		);
+		assert!(Coordinates::from_map_url("https://example.com/@52.5,13.4").is_none());
+	}
+	#[test]
+	fn longitude_wraps() {
+		assert_eq!(Coordinates::new(0.0, 180.0).longitude, -180.0);
+		assert_eq!(Coordinates::new(0.0, 190.0).longitude, -170.0);
+		assert_eq!(Coordinates::new(0.0, 4.89).longitude, 4.89);
+	}
+	#[test]
+	fn distance() {
+		let paris = Coordinates::new(48.8566, 2.3522);
+		let london = Coordinates::new(51.5072, -0.1276);
+		assert!(is_close_enough(344.0, paris.distance_km(london), 2));
+		assert_eq!(paris.distance_km(paris), 0.0);
+	}
+	#[test]
+	fn distance_across_antimeridian_and_poles() {
+		let west = Coordinates::new(0.0, 179.5);
+		let east = Coordinates::new(0.0, -179.5);
+		assert!(is_close_enough(111.2, west.distance_km(east), 2));
+		let north_pole = Coordinates::new(90.0, 0.0);
+		assert!(north_pole.distance_km(Coordinates::new(90.0, 120.0)) < 0.01);
+	}
 }
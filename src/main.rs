@@ -1,43 +1,161 @@
 use std::fs;
 
+use ab_glyph::FontRef;
 use database::init_database;
 use discord_event_handler::DiscordEventHandler;
 use location::Coordinates;
+use render_archive::rerender;
 use serenity::all::GatewayIntents;
 
+mod alerts;
+mod almanac;
+mod cache;
+mod csv;
 mod current;
-mod daily_forecast;
 mod database;
 mod discord_event_handler;
 mod error;
+mod event;
+mod fog;
+mod forecasts;
 mod geocoding;
-mod hourly_forecast;
+mod home_timezone;
+mod i18n;
+mod layout;
 mod location;
+mod moon;
+mod open_meteo;
+mod palette;
+mod pin;
+mod random_place;
+mod refresh;
+mod render;
+mod render_archive;
 mod reply_shortcuts;
+mod status;
+mod subscriptions;
 mod sunrise_sunset;
+mod units;
 mod user_locations;
 mod util;
 
 #[tokio::main]
 async fn main() {
-	let db_pool = init_database("./data/db.db").await;
+	tracing_subscriber::fmt::init();
+	let db_path = std::env::var_os("WEATHERBOT_DB_PATH").unwrap_or_else(|| "./data/db.db".into());
+	let db_pool = init_database(&db_path).await;
 
-	let font_data: &[u8] = include_bytes!("../RobotoCondensed-Regular.ttf");
-	let font = ab_glyph::FontRef::try_from_slice(font_data).expect("Failed to read font");
-	let font_data: &[u8] = include_bytes!("../Roboto-Black.ttf");
-	let header_font = ab_glyph::FontRef::try_from_slice(font_data).expect("Failed to read font");
+	let font = load_font(
+		"FONT_PATH",
+		include_bytes!("../RobotoCondensed-Regular.ttf"),
+	);
+	let header_font = load_font("HEADER_FONT_PATH", include_bytes!("../Roboto-Black.ttf"));
 
-	let discord_token = fs::read_to_string("./token.txt").expect("Could not read token file");
+	// `rerender <id>` draws an archived forecast to a file instead of starting the bot.
+	if std::env::args().nth(1).as_deref() == Some("rerender") {
+		let id = std::env::args()
+			.nth(2)
+			.and_then(|id| id.parse().ok())
+			.expect("Usage: rerender <archive ID>");
+		let (kind, image) = rerender(&db_pool, id, &font, &header_font)
+			.await
+			.expect("Failed to rerender");
+		let path = format!("rerender-{id}-{}", kind.file_name());
+		fs::write(&path, image).expect("Failed to write image");
+		tracing::info!(path, "Wrote rerendered forecast");
+		return;
+	}
+
+	let discord_token = read_token();
 
 	let _init = Coordinates::parse(r#"1°2'3"N4°5'6"E"#).unwrap();
 
-	let handler = DiscordEventHandler::new(db_pool, font, header_font);
-	let mut client = serenity::Client::builder(&discord_token, GatewayIntents::empty())
+	// Answering mentions needs the message content intent, which is privileged, so it has to be enabled for the bot in the developer portal as well.
+	let answer_mentions = std::env::var_os("WEATHERBOT_ANSWER_MENTIONS").is_some();
+	let intents = if answer_mentions {
+		GatewayIntents::GUILD_MESSAGES
+			| GatewayIntents::DIRECT_MESSAGES
+			| GatewayIntents::MESSAGE_CONTENT
+	} else {
+		GatewayIntents::empty()
+	};
+
+	let handler = DiscordEventHandler::new(db_pool.clone(), font, header_font, answer_mentions);
+	let mut client = serenity::Client::builder(&discord_token, intents)
 		.event_handler(handler)
 		.await
 		.expect("Error creating Discord client");
 
+	let shard_manager = client.shard_manager.clone();
+	tokio::spawn(async move {
+		shutdown_signal().await;
+		tracing::info!("Shutting down Discord shards");
+		shard_manager.shutdown_all().await;
+	});
+
 	if let Err(why) = client.start().await {
-		eprintln!("Error with client: {:?}", why);
+		tracing::error!(error = ?why, "Error with client");
+	}
+
+	// Wait for in-flight writes, so the next start doesn't find the database locked.
+	tracing::info!("Closing database");
+	db_pool.close().await;
+	tracing::info!("Shut down");
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM where there is such a thing, like when a container is stopped.
+async fn shutdown_signal() {
+	let ctrl_c = async {
+		tokio::signal::ctrl_c()
+			.await
+			.expect("Failed to listen for Ctrl+C");
+	};
+	#[cfg(unix)]
+	let terminate = async {
+		tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+			.expect("Failed to listen for SIGTERM")
+			.recv()
+			.await;
+	};
+	#[cfg(not(unix))]
+	let terminate = std::future::pending::<()>();
+
+	tokio::select! {
+		() = ctrl_c => {}
+		() = terminate => {}
 	}
+	tracing::info!("Received shutdown signal");
+}
+
+/// The Discord token from `WEATHERBOT_TOKEN`, or else from the file `WEATHERBOT_TOKEN_FILE` points to, which defaults to `./token.txt`.
+fn read_token() -> String {
+	if let Ok(token) = std::env::var("WEATHERBOT_TOKEN") {
+		return token;
+	}
+	let path = std::env::var_os("WEATHERBOT_TOKEN_FILE").unwrap_or_else(|| "./token.txt".into());
+	fs::read_to_string(&path).unwrap_or_else(|error| {
+		panic!(
+			"Could not read token file {} ({error}); set WEATHERBOT_TOKEN, or WEATHERBOT_TOKEN_FILE to the token file's path",
+			path.to_string_lossy()
+		)
+	})
+}
+
+/// The font in the file the environment variable points to, or the embedded one when it is unset, so operators can supply a font that covers more scripts. A font read from a file is leaked, since it is used for as long as the bot runs.
+fn load_font(variable: &str, embedded: &'static [u8]) -> FontRef<'static> {
+	let data: &'static [u8] = match std::env::var_os(variable) {
+		Some(path) => {
+			let data = fs::read(&path).unwrap_or_else(|error| {
+				panic!(
+					"Could not read font {} from {variable}: {error}",
+					path.to_string_lossy()
+				)
+			});
+			tracing::info!(variable, path = %path.to_string_lossy(), "Using font from file");
+			Box::leak(data.into_boxed_slice())
+		}
+		None => embedded,
+	};
+	FontRef::try_from_slice(data)
+		.unwrap_or_else(|error| panic!("Failed to parse the font from {variable}: {error}"))
 }
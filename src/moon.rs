@@ -0,0 +1,366 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Duration, Utc};
+use serenity::all::{
+	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	open_meteo::{self, utc_offset},
+	reply_shortcuts::ReplyShortcuts,
+	util::capitalize,
+};
+
+/// Obliquity of the ecliptic, in radians.
+const OBLIQUITY: f64 = 23.4397 * PI / 180.0;
+/// Altitude of the moon's centre at moonrise and moonset, accounting for its parallax and radius, in radians.
+const MOON_HORIZON_ALTITUDE: f64 = 0.133 * PI / 180.0;
+/// Distance from the Earth to the sun, in km.
+const SUN_DISTANCE: f64 = 149_598_000.0;
+
+/// Days since the J2000.0 epoch.
+fn days_since_j2000(time: DateTime<Utc>) -> f64 {
+	time.timestamp_millis() as f64 / 86_400_000.0 - 10_957.5
+}
+
+/// Equatorial coordinates, in radians.
+struct Equatorial {
+	right_ascension: f64,
+	declination: f64,
+}
+
+impl Equatorial {
+	fn from_ecliptic(longitude: f64, latitude: f64) -> Self {
+		Self {
+			right_ascension: (longitude.sin() * OBLIQUITY.cos() - latitude.tan() * OBLIQUITY.sin())
+				.atan2(longitude.cos()),
+			declination: (latitude.sin() * OBLIQUITY.cos()
+				+ latitude.cos() * OBLIQUITY.sin() * longitude.sin())
+			.asin(),
+		}
+	}
+}
+
+/// Geocentric ecliptic longitude and latitude of the moon in radians, and its distance in km.
+///
+/// Mean elements from https://aa.quae.nl/en/reken/hemelpositie.html plus the largest periodic terms, good to a few arcminutes.
+fn moon_ecliptic(days: f64) -> (f64, f64, f64) {
+	let mean_longitude = (218.316 + 13.176396 * days).to_radians();
+	let anomaly = (134.963 + 13.064993 * days).to_radians();
+	let argument_of_latitude = (93.272 + 13.229350 * days).to_radians();
+	let elongation = (297.8502 + 12.19074912 * days).to_radians();
+	let sun_anomaly = (357.5291 + 0.98560028 * days).to_radians();
+
+	let longitude = mean_longitude
+		+ (6.289 * anomaly.sin()
+			+ 1.274 * (2.0 * elongation - anomaly).sin()
+			+ 0.658 * (2.0 * elongation).sin()
+			+ 0.214 * (2.0 * anomaly).sin()
+			- 0.186 * sun_anomaly.sin()
+			- 0.114 * (2.0 * argument_of_latitude).sin())
+		.to_radians();
+	let latitude = (5.128 * argument_of_latitude.sin()
+		+ 0.280 * (anomaly + argument_of_latitude).sin()
+		+ 0.277 * (anomaly - argument_of_latitude).sin()
+		+ 0.173 * (2.0 * elongation - argument_of_latitude).sin())
+	.to_radians();
+	let distance = 385_001.0
+		- 20_905.0 * anomaly.cos()
+		- 3_699.0 * (2.0 * elongation - anomaly).cos()
+		- 2_956.0 * (2.0 * elongation).cos();
+	(longitude, latitude, distance)
+}
+
+fn sun_equatorial(days: f64) -> Equatorial {
+	let anomaly = (357.5291 + 0.98560028 * days).to_radians();
+	let equation_of_centre =
+		(1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin())
+			.to_radians();
+	let perihelion = 102.9372f64.to_radians();
+	Equatorial::from_ecliptic(anomaly + equation_of_centre + perihelion + PI, 0.0)
+}
+
+/// The sun's declination at `time`, in radians.
+pub fn sun_declination(time: DateTime<Utc>) -> f64 {
+	sun_equatorial(days_since_j2000(time)).declination
+}
+
+/// The moon's altitude above the horizon in radians, corrected for atmospheric refraction.
+fn moon_altitude(time: DateTime<Utc>, coordinates: Coordinates) -> f64 {
+	let days = days_since_j2000(time);
+	let (longitude, latitude, _) = moon_ecliptic(days);
+	let moon = Equatorial::from_ecliptic(longitude, latitude);
+	let observer_latitude = (coordinates.latitude as f64).to_radians();
+	let sidereal_time =
+		(280.16 + 360.9856235 * days).to_radians() + (coordinates.longitude as f64).to_radians();
+	let hour_angle = sidereal_time - moon.right_ascension;
+	let altitude = (observer_latitude.sin() * moon.declination.sin()
+		+ observer_latitude.cos() * moon.declination.cos() * hour_angle.cos())
+	.asin();
+	let clamped = altitude.max(0.0);
+	altitude + 0.0002967 / (clamped + 0.00312536 / (clamped + 0.08901179)).tan()
+}
+
+/// Moonrise and moonset within a 24-hour window. Either can be missing, since the moon doesn't rise or set every day.
+#[derive(Debug)]
+pub struct MoonTimes {
+	pub rise: Option<DateTime<Utc>>,
+	pub set: Option<DateTime<Utc>>,
+}
+
+/// Find moonrise and moonset in the 24 hours from `start`, by fitting a parabola through the moon's altitude every two hours and checking it for roots.
+///
+/// Method from http://www.stargazing.net/kepler/moonrise.html.
+pub fn moon_times(start: DateTime<Utc>, coordinates: Coordinates) -> MoonTimes {
+	let altitude_at = |hours: i64| {
+		moon_altitude(start + Duration::hours(hours), coordinates) - MOON_HORIZON_ALTITUDE
+	};
+	let mut rise = None;
+	let mut set = None;
+	let mut previous = altitude_at(0);
+	for hour in (1..=23).step_by(2) {
+		let middle = altitude_at(hour);
+		let next = altitude_at(hour + 1);
+		let a = (previous + next) / 2.0 - middle;
+		let b = (next - previous) / 2.0;
+		let extreme_x = -b / (2.0 * a);
+		let extreme_y = (a * extreme_x + b) * extreme_x + middle;
+		let discriminant = b * b - 4.0 * a * middle;
+		if discriminant >= 0.0 {
+			let delta = discriminant.sqrt() / (a.abs() * 2.0);
+			let (first, second) = (extreme_x - delta, extreme_x + delta);
+			let at =
+				|x: f64| start + Duration::milliseconds(((hour as f64 + x) * 3_600_000.0) as i64);
+			match (first.abs() <= 1.0, second.abs() <= 1.0) {
+				(true, true) => {
+					let (rise_x, set_x) = if extreme_y < 0.0 {
+						(second, first)
+					} else {
+						(first, second)
+					};
+					rise = Some(at(rise_x));
+					set = Some(at(set_x));
+				}
+				(true, false) | (false, true) => {
+					let x = if first.abs() <= 1.0 { first } else { second };
+					if previous < 0.0 {
+						rise = Some(at(x));
+					} else {
+						set = Some(at(x));
+					}
+				}
+				(false, false) => (),
+			}
+		}
+		if rise.is_some() && set.is_some() {
+			break;
+		}
+		previous = next;
+	}
+	MoonTimes { rise, set }
+}
+
+/// The moon's phase and how much of it is lit.
+pub struct MoonPhase {
+	/// From 0 (new moon) through 0.25 (first quarter), 0.5 (full moon) and 0.75 (last quarter) back towards 1.
+	pub phase: f64,
+	/// The illuminated fraction of the disc, from 0 to 1.
+	pub illumination: f64,
+}
+
+impl MoonPhase {
+	pub fn at(time: DateTime<Utc>) -> Self {
+		let days = days_since_j2000(time);
+		let sun = sun_equatorial(days);
+		let (longitude, latitude, distance) = moon_ecliptic(days);
+		let moon = Equatorial::from_ecliptic(longitude, latitude);
+		let elongation = (sun.declination.sin() * moon.declination.sin()
+			+ sun.declination.cos()
+				* moon.declination.cos()
+				* (sun.right_ascension - moon.right_ascension).cos())
+		.acos();
+		let phase_angle =
+			(SUN_DISTANCE * elongation.sin()).atan2(distance - SUN_DISTANCE * elongation.cos());
+		let position_angle =
+			(sun.declination.cos() * (sun.right_ascension - moon.right_ascension).sin()).atan2(
+				sun.declination.sin() * moon.declination.cos()
+					- sun.declination.cos()
+						* moon.declination.sin()
+						* (sun.right_ascension - moon.right_ascension).cos(),
+			);
+		Self {
+			phase: 0.5 + 0.5 * phase_angle * position_angle.signum() / PI,
+			illumination: (1.0 + phase_angle.cos()) / 2.0,
+		}
+	}
+	/// Which of the eight named phases this is, counting from the new moon.
+	fn octant(&self) -> u8 {
+		(self.phase * 8.0).round() as u8 % 8
+	}
+	pub fn name(&self) -> &'static str {
+		match self.octant() {
+			0 => "new moon",
+			1 => "waxing crescent",
+			2 => "first quarter",
+			3 => "waxing gibbous",
+			4 => "full moon",
+			5 => "waning gibbous",
+			6 => "last quarter",
+			_ => "waning crescent",
+		}
+	}
+	pub fn emoji(&self) -> &'static str {
+		["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"][self.octant() as usize]
+	}
+}
+
+pub async fn handle_moon(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	// The phase is the same everywhere, but the date it falls on is not.
+	let offset = utc_offset(location.coordinates(), &client).await?;
+	let now = Utc::now();
+	let phase = MoonPhase::at(now);
+	let message = format!(
+		"{} {} on {}, {:.0}% illuminated.",
+		phase.emoji(),
+		capitalize(phase.name()),
+		now.with_timezone(&offset).format("%A %B %-d"),
+		phase.illumination * 100.0
+	);
+	interaction.public_reply(&context.http, message).await?;
+	Ok(())
+}
+
+pub async fn handle_moonrise(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+
+	let offset = utc_offset(location.coordinates(), &client).await?;
+	let now = Utc::now();
+	let midnight = now
+		.with_timezone(&offset)
+		.date_naive()
+		.and_hms_opt(0, 0, 0)
+		.and_then(|midnight| midnight.and_local_timezone(offset).single())
+		.ok_or_else(|| Error::custom_unfriendly("Could not determine local midnight"))?
+		.with_timezone(&Utc);
+
+	let times = moon_times(midnight, location.coordinates());
+	let format_time = |time: Option<DateTime<Utc>>| {
+		time.map_or_else(
+			|| String::from("n/a"),
+			|time| time.with_timezone(&offset).format("%H:%M").to_string(),
+		)
+	};
+	let phase = MoonPhase::at(now);
+	let message = format!(
+		"Moonrise {}, moonset {}. {}, {:.0}% illuminated.",
+		format_time(times.rise),
+		format_time(times.set),
+		capitalize(phase.name()),
+		phase.illumination * 100.0
+	);
+	interaction.public_reply(&context.http, message).await?;
+	Ok(())
+}
+
+pub fn create_moonrise() -> CreateCommand {
+	i18n::command(
+		"moonrise",
+		"Today's moonrise and moonset, and the moon's phase",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the moonrise and moonset of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+}
+
+pub fn create_moon() -> CreateCommand {
+	i18n::command("moon", "The moon's phase today")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place whose date to show the moon's phase on.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn assert_within_minutes(actual: DateTime<Utc>, expected: DateTime<Utc>, minutes: i64) {
+		let difference = (actual - expected).num_seconds().abs();
+		assert!(
+			difference <= minutes * 60,
+			"{actual} is more than {minutes} minutes from {expected}"
+		);
+	}
+
+	#[test]
+	fn moon_position_meeus_example() {
+		// Example 47.a from Astronomical Algorithms by Jean Meeus: λ = 133.162655°, β = -3.229126° on 1992-04-12 at 0h TD.
+		let time = Utc.with_ymd_and_hms(1992, 4, 12, 0, 0, 0).unwrap();
+		let (longitude, latitude, _) = moon_ecliptic(days_since_j2000(time));
+		assert!((longitude.to_degrees().rem_euclid(360.0) - 133.162655).abs() < 0.2);
+		assert!((latitude.to_degrees() - -3.229126).abs() < 0.2);
+	}
+	#[test]
+	fn phases_at_known_moons() {
+		// New moon on 2024-01-11 at 11:57 UTC and full moon on 2024-01-25 at 17:54 UTC.
+		let new = MoonPhase::at(Utc.with_ymd_and_hms(2024, 1, 11, 11, 57, 0).unwrap());
+		// The moon passes a few degrees north or south of the sun, so it is never quite at phase 0 or 0.5.
+		assert!(new.phase < 0.02 || new.phase > 0.98);
+		assert!(new.illumination < 0.01);
+		assert_eq!((new.name(), new.emoji()), ("new moon", "🌑"));
+		let full = MoonPhase::at(Utc.with_ymd_and_hms(2024, 1, 25, 17, 54, 0).unwrap());
+		assert!((full.phase - 0.5).abs() < 0.02);
+		assert!(full.illumination > 0.99);
+		assert_eq!((full.name(), full.emoji()), ("full moon", "🌕"));
+		// And the last quarter after it, on 2024-02-02 at 23:18 UTC.
+		let waning = MoonPhase::at(Utc.with_ymd_and_hms(2024, 2, 2, 23, 18, 0).unwrap());
+		assert_eq!((waning.name(), waning.emoji()), ("last quarter", "🌗"));
+	}
+	#[test]
+	fn moon_times_reference() {
+		// Reference values from the SunCalc test suite, for 50.5° N, 30.5° E.
+		let start = Utc.with_ymd_and_hms(2013, 3, 4, 0, 0, 0).unwrap();
+		let times = moon_times(start, Coordinates::new(50.5, 30.5));
+		assert_within_minutes(
+			times.rise.unwrap(),
+			Utc.with_ymd_and_hms(2013, 3, 4, 23, 54, 29).unwrap(),
+			5,
+		);
+		assert_within_minutes(
+			times.set.unwrap(),
+			Utc.with_ymd_and_hms(2013, 3, 4, 7, 47, 58).unwrap(),
+			5,
+		);
+	}
+}
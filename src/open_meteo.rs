@@ -0,0 +1,737 @@
+use std::{
+	sync::{
+		atomic::{AtomicI64, AtomicU32, Ordering},
+		LazyLock, Mutex,
+	},
+	time::Duration,
+};
+
+use chrono::{FixedOffset, Utc};
+use reqwest::{header::RETRY_AFTER, Client, RequestBuilder, Response, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serenity::async_trait;
+use tokio::time::Instant;
+
+use crate::{cache::TtlCache, error::Error, location::Coordinates, util::ForecastTimezone};
+
+/// How many requests in a row have to fail before the circuit breaker opens.
+const FAILURE_THRESHOLD: u32 = 5;
+/// How long the circuit breaker stays open before letting a trial request through, in seconds.
+const COOLDOWN_SECONDS: i64 = 60;
+/// How long a trial request can go without a result before another is let through, in seconds. Longer than a request can take with all its retries.
+const TRIAL_TIMEOUT_SECONDS: i64 = 60;
+/// How many times a request is retried after failing in a way that might not happen again.
+const MAX_RETRIES: u32 = 3;
+/// The wait before the first retry, doubled for every retry after it.
+const BASE_BACKOFF: Duration = Duration::from_millis(250);
+/// The longest a request can take with all its retries and the waits between them, so a command doesn't keep someone waiting for long on a service that isn't answering.
+const MAX_SEND_TIME: Duration = Duration::from_secs(15);
+/// The longest `Retry-After` that is waited out. Anything longer fails right away rather than letting the command time out.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(2);
+/// The time between requests to Open-Meteo once a burst is used up. Together with the burst, this stays under the free tier's 600 requests a minute and 5000 an hour.
+const RATE_LIMIT_INTERVAL: Duration = Duration::from_millis(800);
+/// How many requests to Open-Meteo can go at once after a quiet spell.
+const RATE_LIMIT_BURST: u32 = 500;
+/// The free tier's limit of requests a day, which the spacing alone would let the bot go over when it is busy all day.
+const RATE_LIMIT_DAILY: u32 = 10_000;
+/// How long the daily limit counts requests for before starting over.
+const RATE_LIMIT_DAY: Duration = Duration::from_secs(24 * 60 * 60);
+/// The longest a request waits for its turn before failing, for the same reason as `MAX_RETRY_AFTER`.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(2);
+
+/// How long a response from Open-Meteo is reused for the same request. Forecasts are only updated every hour or so, and this saves a fetch when several people in a server look up the same place.
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// How many decimals of the coordinates count towards a cached request. Two decimals is about a kilometre, well within a forecast's grid cell.
+const CACHE_COORDINATE_DECIMALS: usize = 2;
+
+/// Identifies the bot to the APIs it calls, so their operators know who to contact.
+const USER_AGENT: &str = concat!(
+	env!("CARGO_PKG_NAME"),
+	"/",
+	env!("CARGO_PKG_VERSION"),
+	" (+https://github.com/Pulau-Komodo/weatherbot)"
+);
+/// How long to wait for a connection to be made.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a whole request can take, so a hanging server doesn't hang the command with it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The largest response body that is read, in bytes. The biggest forecasts are a few hundred kilobytes, so anything much larger is a broken response that isn't worth the memory.
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// The HTTP client shared by every command, so connections are reused. Open-Meteo responses for many variables are large, so they are requested compressed.
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+	Client::builder()
+		.gzip(true)
+		.brotli(true)
+		.user_agent(USER_AGENT)
+		.connect_timeout(CONNECT_TIMEOUT)
+		.timeout(REQUEST_TIMEOUT)
+		.build()
+		.expect("HTTP client should build")
+});
+
+/// Get a handle to the shared HTTP client.
+pub fn client() -> Client {
+	CLIENT.clone()
+}
+
+/// The circuit breaker shared by every request to Open-Meteo.
+pub static OPEN_METEO_BREAKER: CircuitBreaker = CircuitBreaker::new();
+
+/// The state of a circuit breaker, for display.
+pub enum BreakerState {
+	/// Requests go through normally.
+	Closed { consecutive_failures: u32 },
+	/// Requests are rejected without being sent.
+	Open { seconds_left: i64 },
+	/// The cooldown is over and a trial request decides whether to close again.
+	HalfOpen,
+}
+
+/// Stops sending requests for a while after several failed in a row, so an upstream outage doesn't make every command hang until it times out.
+pub struct CircuitBreaker {
+	consecutive_failures: AtomicU32,
+	/// Unix timestamp of when the breaker opened, or 0 if it is closed.
+	opened_at: AtomicI64,
+	/// Unix timestamp of when the single trial request allowed while half-open was sent, or 0 if there is none.
+	trial_started_at: AtomicI64,
+}
+
+impl CircuitBreaker {
+	const fn new() -> Self {
+		Self {
+			consecutive_failures: AtomicU32::new(0),
+			opened_at: AtomicI64::new(0),
+			trial_started_at: AtomicI64::new(0),
+		}
+	}
+	fn allow_request(&self, now: i64) -> bool {
+		let opened_at = self.opened_at.load(Ordering::SeqCst);
+		if opened_at == 0 {
+			return true;
+		}
+		if now - opened_at < COOLDOWN_SECONDS {
+			return false;
+		}
+		// A trial that never reported back, like one whose command was cancelled, is given up on so another can take its place.
+		let trial_started_at = self.trial_started_at.load(Ordering::SeqCst);
+		if trial_started_at != 0 && now - trial_started_at < TRIAL_TIMEOUT_SECONDS {
+			return false;
+		}
+		self.trial_started_at
+			.compare_exchange(trial_started_at, now, Ordering::SeqCst, Ordering::SeqCst)
+			.is_ok()
+	}
+	fn record_success(&self) {
+		self.consecutive_failures.store(0, Ordering::SeqCst);
+		self.opened_at.store(0, Ordering::SeqCst);
+		self.trial_started_at.store(0, Ordering::SeqCst);
+	}
+	fn record_failure(&self, now: i64) {
+		let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+		if failures >= FAILURE_THRESHOLD || self.trial_started_at.load(Ordering::SeqCst) != 0 {
+			self.opened_at.store(now, Ordering::SeqCst);
+			self.trial_started_at.store(0, Ordering::SeqCst);
+		}
+	}
+	pub fn state(&self) -> BreakerState {
+		let opened_at = self.opened_at.load(Ordering::SeqCst);
+		if opened_at == 0 {
+			return BreakerState::Closed {
+				consecutive_failures: self.consecutive_failures.load(Ordering::SeqCst),
+			};
+		}
+		let seconds_left = opened_at + COOLDOWN_SECONDS - Utc::now().timestamp();
+		if seconds_left > 0 {
+			BreakerState::Open { seconds_left }
+		} else {
+			BreakerState::HalfOpen
+		}
+	}
+}
+
+/// The rate limiter shared by every request to Open-Meteo.
+static OPEN_METEO_LIMITER: RateLimiter =
+	RateLimiter::new(RATE_LIMIT_INTERVAL, RATE_LIMIT_BURST, RATE_LIMIT_DAILY);
+
+/// Spaces requests out so a burst of commands doesn't get the bot blocked, and stops them for the rest of the day once the day's are used up. Requests beyond the burst wait their turn, in the order they came in.
+struct RateLimiter {
+	interval: Duration,
+	burst: u32,
+	daily: u32,
+	state: Mutex<LimiterState>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LimiterState {
+	/// When the next request would be sent if there were no burst to use. The limiter is a generic cell rate algorithm in effect.
+	next: Option<Instant>,
+	/// When the first request of the day being counted was sent.
+	day_started: Option<Instant>,
+	requests_today: u32,
+}
+
+impl RateLimiter {
+	const fn new(interval: Duration, burst: u32, daily: u32) -> Self {
+		Self {
+			interval,
+			burst,
+			daily,
+			state: Mutex::new(LimiterState {
+				next: None,
+				day_started: None,
+				requests_today: 0,
+			}),
+		}
+	}
+	/// Wait for a turn to send a request. Fails without taking a turn if that would take longer than `max_wait`, or if the day's requests are used up.
+	async fn acquire(&self, max_wait: Duration) -> Result<(), Error> {
+		let now = Instant::now();
+		let wait = {
+			let mut state = self.state.lock().unwrap();
+			let new_day =
+				!matches!(state.day_started, Some(started) if now < started + RATE_LIMIT_DAY);
+			let requests_today = if new_day { 0 } else { state.requests_today };
+			if requests_today >= self.daily {
+				return Err(Error::friendly(
+					"Weather service's daily limit is used up, try again later",
+				));
+			}
+			let slot = state.next.map_or(now, |next| next.max(now));
+			let wait = (slot - now).saturating_sub(self.interval * self.burst.saturating_sub(1));
+			if wait > max_wait {
+				return Err(Error::friendly(
+					"Weather service is busy, try again shortly",
+				));
+			}
+			if new_day {
+				state.day_started = Some(now);
+			}
+			state.requests_today = requests_today + 1;
+			state.next = Some(slot + self.interval);
+			wait
+		};
+		tokio::time::sleep(wait).await;
+		Ok(())
+	}
+}
+
+/// Recent response bodies from Open-Meteo, by request. Bodies are kept rather than parsed results, so one cache serves every kind of result.
+static OPEN_METEO_CACHE: LazyLock<TtlCache<String, String>> =
+	LazyLock::new(|| TtlCache::new(CACHE_TTL));
+
+/// The key a request is cached under: its endpoint and query, with the coordinates rounded so nearby lookups of the same place share it. Everything else in the query, like the variables and the number of hours or days, has to match exactly.
+fn cache_key(url: &Url) -> String {
+	let mut key = url.clone();
+	let query: Vec<(String, String)> = url
+		.query_pairs()
+		.map(|(name, value)| {
+			let value = match (name.as_ref(), value.parse::<f32>()) {
+				("latitude" | "longitude", Ok(degrees)) => {
+					format!("{degrees:.CACHE_COORDINATE_DECIMALS$}")
+				}
+				_ => value.into_owned(),
+			};
+			(name.into_owned(), value)
+		})
+		.collect();
+	key.query_pairs_mut().clear().extend_pairs(query);
+	key.into()
+}
+
+#[async_trait]
+pub trait RequestBuilderExt {
+	/// Send a request to Open-Meteo, retrying transient failures and short-circuiting with a friendly error while the service seems to be down. Requests are rate limited to stay within the free tier.
+	async fn send_open_meteo(self) -> Result<Response, Error>;
+	/// Send a request to Open-Meteo like [`RequestBuilderExt::send_open_meteo`] and parse the response, reusing a response to the same request from the last few minutes if there is one.
+	async fn fetch_open_meteo<T>(self) -> Result<T, Error>
+	where
+		T: DeserializeOwned;
+}
+
+#[async_trait]
+impl RequestBuilderExt for RequestBuilder {
+	async fn send_open_meteo(self) -> Result<Response, Error> {
+		// The turn is taken first, because once the breaker lets a request through it has to report back how it went.
+		OPEN_METEO_LIMITER.acquire(MAX_RATE_LIMIT_WAIT).await?;
+		if !OPEN_METEO_BREAKER.allow_request(Utc::now().timestamp()) {
+			return Err(Error::friendly(
+				"Weather service is temporarily unavailable",
+			));
+		}
+		match send_with_retries(self, MAX_SEND_TIME).await {
+			Ok(response) => {
+				OPEN_METEO_BREAKER.record_success();
+				Ok(response)
+			}
+			Err(error) => {
+				OPEN_METEO_BREAKER.record_failure(Utc::now().timestamp());
+				Err(request_error(error))
+			}
+		}
+	}
+	async fn fetch_open_meteo<T>(self) -> Result<T, Error>
+	where
+		T: DeserializeOwned,
+	{
+		fetch_cached(self, &OPEN_METEO_CACHE).await
+	}
+}
+
+async fn fetch_cached<T>(
+	request: RequestBuilder,
+	cache: &TtlCache<String, String>,
+) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	let key = request
+		.try_clone()
+		.and_then(|request| request.build().ok())
+		.map(|request| cache_key(request.url()));
+	if let Some(body) = key.as_ref().and_then(|key| cache.get(key)) {
+		return Ok(serde_json::from_str(&body)?);
+	}
+	let response = request.send_open_meteo().await?;
+	let success = response.status().is_success();
+	let body = read_body(response, MAX_BODY_SIZE).await?;
+	let result = parse_body(success, &body)?;
+	// Only successful responses that parsed are kept, so an error from Open-Meteo is asked for again next time.
+	if let Some(key) = key.filter(|_| success) {
+		cache.insert(key, body);
+	}
+	Ok(result)
+}
+
+#[async_trait]
+pub trait ResponseExt {
+	/// Parse the response as JSON, failing with a friendly error rather than reading it all if it is unreasonably large.
+	async fn json_limited<T>(self) -> Result<T, Error>
+	where
+		T: DeserializeOwned;
+}
+
+#[async_trait]
+impl ResponseExt for Response {
+	async fn json_limited<T>(self) -> Result<T, Error>
+	where
+		T: DeserializeOwned,
+	{
+		let success = self.status().is_success();
+		let body = read_body(self, MAX_BODY_SIZE).await?;
+		parse_body(success, &body)
+	}
+}
+
+/// The body Open-Meteo sends with a 400 when a request is invalid, like for coordinates out of range.
+#[derive(Debug, Deserialize)]
+struct OpenMeteoError {
+	#[serde(rename = "error")]
+	_error: bool,
+	reason: String,
+}
+
+/// Parse a response body, turning Open-Meteo's explanation of a failed request into a friendly error with its reason.
+fn parse_body<T>(success: bool, body: &str) -> Result<T, Error>
+where
+	T: DeserializeOwned,
+{
+	if !success {
+		if let Ok(error) = serde_json::from_str::<OpenMeteoError>(body) {
+			return Err(Error::friendly(error.reason));
+		}
+	}
+	Ok(serde_json::from_str(body)?)
+}
+
+/// Read the body of a response as text, as long as it is no more than `limit` bytes. The body is read in chunks, so a response without a length, or one that lies about it, is still cut off once it passes the limit.
+async fn read_body(mut response: Response, limit: usize) -> Result<String, Error> {
+	let too_large = || Error::friendly("The weather service sent a response that was too large");
+	if response
+		.content_length()
+		.is_some_and(|length| length > limit as u64)
+	{
+		return Err(too_large());
+	}
+	let mut body = Vec::new();
+	while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+		if body.len() + chunk.len() > limit {
+			return Err(too_large());
+		}
+		body.extend_from_slice(&chunk);
+	}
+	Ok(String::from_utf8(body)?)
+}
+
+/// A failed request as an error, which is friendly if the weather service took too long, since that is up to the service rather than the bot.
+fn request_error(error: reqwest::Error) -> Error {
+	if error.is_timeout() {
+		Error::friendly("The weather service took too long to respond")
+	} else {
+		error.into()
+	}
+}
+
+/// Send a request, retrying connection errors and responses that ask to try again later with exponential backoff. Server errors and rate limiting that are still there after the last retry become errors.
+///
+/// All the attempts together take no longer than `time_limit`. A retry that couldn't start before then isn't made.
+async fn send_with_retries(
+	mut request: RequestBuilder,
+	time_limit: Duration,
+) -> Result<Response, reqwest::Error> {
+	let deadline = Instant::now() + time_limit;
+	let mut attempt = 0;
+	loop {
+		// Requests with a streaming body can't be cloned, and so can't be retried.
+		let retry = request.try_clone();
+		let remaining = deadline.saturating_duration_since(Instant::now());
+		let result = request.timeout(remaining.min(REQUEST_TIMEOUT)).send().await;
+		let delay = match &result {
+			Ok(response) if is_retryable(response.status()) => {
+				backoff(attempt, retry_after(response))
+			}
+			Err(error) if error.is_connect() || error.is_timeout() => backoff(attempt, None),
+			_ => None,
+		}
+		.filter(|delay| Instant::now() + *delay < deadline);
+		match (retry, delay) {
+			(Some(retry), Some(delay)) => {
+				tokio::time::sleep(delay).await;
+				request = retry;
+				attempt += 1;
+			}
+			_ => {
+				return result.and_then(|response| {
+					if is_retryable(response.status()) || response.status().is_server_error() {
+						response.error_for_status()
+					} else {
+						Ok(response)
+					}
+				})
+			}
+		}
+	}
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+	matches!(
+		status,
+		StatusCode::TOO_MANY_REQUESTS
+			| StatusCode::INTERNAL_SERVER_ERROR
+			| StatusCode::BAD_GATEWAY
+			| StatusCode::SERVICE_UNAVAILABLE
+			| StatusCode::GATEWAY_TIMEOUT
+	)
+}
+
+/// The `Retry-After` header, if it is given in seconds rather than as a date.
+fn retry_after(response: &Response) -> Option<Duration> {
+	let seconds = response
+		.headers()
+		.get(RETRY_AFTER)?
+		.to_str()
+		.ok()?
+		.parse()
+		.ok()?;
+	Some(Duration::from_secs(seconds))
+}
+
+/// How long to wait before retrying after `attempt` retries, or `None` to give up.
+fn backoff(attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+	if attempt >= MAX_RETRIES {
+		return None;
+	}
+	match retry_after {
+		Some(retry_after) if retry_after > MAX_RETRY_AFTER => None,
+		Some(retry_after) => Some(retry_after),
+		None => Some(BASE_BACKOFF * 2u32.pow(attempt)),
+	}
+}
+
+/// Deserialize a weather variable's series, leaving it empty if Open-Meteo left it out, sent `null` for it, or has gaps in it, so one missing variable drops its chart line rather than the whole forecast. Use with `#[serde(default, deserialize_with = "complete_series")]`.
+pub fn complete_series<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: Deserialize<'de>,
+{
+	let series = Option::<Vec<Option<T>>>::deserialize(deserializer)?;
+	Ok(series
+		.and_then(|series| series.into_iter().collect())
+		.unwrap_or_default())
+}
+
+#[derive(Debug, Deserialize)]
+struct TimezoneResult {
+	utc_offset_seconds: i32,
+	timezone: Option<String>,
+}
+
+async fn get_timezone(coordinates: Coordinates, client: &Client) -> Result<TimezoneResult, Error> {
+	client
+		.get("https://api.open-meteo.com/v1/forecast")
+		.query(&[("timezone", "auto")])
+		.query(&[("forecast_days", 1)])
+		.query(&[
+			("latitude", coordinates.latitude),
+			("longitude", coordinates.longitude),
+		])
+		.fetch_open_meteo()
+		.await
+}
+
+/// Get the current UTC offset of the timezone at the coordinates, for commands that don't otherwise need a forecast.
+pub async fn utc_offset(coordinates: Coordinates, client: &Client) -> Result<FixedOffset, Error> {
+	let offset = get_timezone(coordinates, client).await?.utc_offset_seconds;
+	FixedOffset::east_opt(offset).ok_or_else(|| Error::custom_unfriendly("Invalid UTC offset"))
+}
+
+/// Get the timezone at the coordinates, for times that have to follow its daylight saving time.
+pub async fn timezone(
+	coordinates: Coordinates,
+	client: &Client,
+) -> Result<ForecastTimezone, Error> {
+	let result = get_timezone(coordinates, client).await?;
+	if FixedOffset::east_opt(result.utc_offset_seconds).is_none() {
+		return Err(Error::custom_unfriendly("Invalid UTC offset"));
+	}
+	Ok(ForecastTimezone::new(
+		result.timezone.as_deref(),
+		result.utc_offset_seconds,
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		io::{BufRead, BufReader, Write},
+		net::TcpListener,
+		sync::Arc,
+		thread,
+	};
+
+	use super::*;
+
+	/// Serve the responses to one connection each, in order, and return the URL to reach them at and a count of the connections so far.
+	fn mock_server(responses: &'static [&'static str]) -> (String, Arc<AtomicU32>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		let connections = Arc::new(AtomicU32::new(0));
+		let counter = Arc::clone(&connections);
+		thread::spawn(move || {
+			for response in responses {
+				let (mut stream, _) = listener.accept().unwrap();
+				counter.fetch_add(1, Ordering::Relaxed);
+				let mut reader = BufReader::new(stream.try_clone().unwrap());
+				let mut line = String::new();
+				while reader.read_line(&mut line).unwrap() > 2 {
+					line.clear();
+				}
+				stream.write_all(response.as_bytes()).unwrap();
+			}
+		});
+		(format!("http://{address}/"), connections)
+	}
+
+	const UNAVAILABLE: &str =
+		"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+	const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok";
+
+	#[test]
+	fn breaker_opens_and_recovers() {
+		let breaker = CircuitBreaker::new();
+		for _ in 0..FAILURE_THRESHOLD - 1 {
+			assert!(breaker.allow_request(100));
+			breaker.record_failure(100);
+		}
+		assert!(breaker.allow_request(100));
+		breaker.record_failure(100);
+		// Open for the cooldown.
+		assert!(!breaker.allow_request(100));
+		assert!(!breaker.allow_request(100 + COOLDOWN_SECONDS - 1));
+		// Half-open: one trial at a time.
+		let half_open = 100 + COOLDOWN_SECONDS;
+		assert!(breaker.allow_request(half_open));
+		assert!(!breaker.allow_request(half_open));
+		// A failed trial opens it again.
+		breaker.record_failure(half_open);
+		assert!(!breaker.allow_request(half_open + 1));
+		let half_open = half_open + COOLDOWN_SECONDS;
+		assert!(breaker.allow_request(half_open));
+		// A successful trial closes it.
+		breaker.record_success();
+		assert!(breaker.allow_request(half_open));
+		assert!(breaker.allow_request(half_open));
+		assert!(matches!(
+			breaker.state(),
+			BreakerState::Closed {
+				consecutive_failures: 0
+			}
+		));
+	}
+	#[test]
+	fn abandoned_trial_expires() {
+		let breaker = CircuitBreaker::new();
+		for _ in 0..FAILURE_THRESHOLD {
+			breaker.record_failure(100);
+		}
+		let half_open = 100 + COOLDOWN_SECONDS;
+		assert!(breaker.allow_request(half_open));
+		// The trial never reports back.
+		assert!(!breaker.allow_request(half_open + TRIAL_TIMEOUT_SECONDS - 1));
+		assert!(breaker.allow_request(half_open + TRIAL_TIMEOUT_SECONDS));
+		breaker.record_success();
+		assert!(breaker.allow_request(half_open + TRIAL_TIMEOUT_SECONDS));
+	}
+	#[test]
+	fn backoff_doubles_then_gives_up() {
+		assert_eq!(backoff(0, None), Some(Duration::from_millis(250)));
+		assert_eq!(backoff(2, None), Some(Duration::from_millis(1000)));
+		assert_eq!(backoff(3, None), None);
+		assert_eq!(
+			backoff(0, Some(Duration::from_secs(1))),
+			Some(Duration::from_secs(1))
+		);
+		assert_eq!(backoff(0, Some(Duration::from_secs(120))), None);
+	}
+	#[tokio::test]
+	async fn rate_limiter_spaces_requests_after_burst() {
+		tokio::time::pause();
+		let interval = Duration::from_millis(50);
+		let limiter = RateLimiter::new(interval, 2, 100);
+		let start = Instant::now();
+		let turn = || async {
+			limiter.acquire(Duration::from_secs(1)).await.unwrap();
+			start.elapsed()
+		};
+		let (a, b, c, d, e) = tokio::join!(turn(), turn(), turn(), turn(), turn());
+		let mut elapsed = [a, b, c, d, e];
+		elapsed.sort();
+		// Two go right away, and every one after waits another interval, give or take the timer's resolution of a millisecond.
+		for (index, elapsed) in elapsed.into_iter().enumerate() {
+			let expected = interval * (index.saturating_sub(1) as u32);
+			assert!(
+				elapsed >= expected && elapsed <= expected + Duration::from_millis(1),
+				"request {index} went after {elapsed:?}"
+			);
+		}
+	}
+	#[tokio::test]
+	async fn rate_limiter_rejects_long_waits() {
+		let limiter = RateLimiter::new(Duration::from_secs(10), 1, 100);
+		limiter.acquire(Duration::ZERO).await.unwrap();
+		let state = *limiter.state.lock().unwrap();
+		assert!(matches!(
+			limiter.acquire(Duration::from_secs(1)).await,
+			Err(Error::Friendly(_))
+		));
+		// The rejected request didn't take a turn.
+		assert_eq!(*limiter.state.lock().unwrap(), state);
+	}
+	#[tokio::test]
+	async fn rate_limiter_stops_for_the_day() {
+		tokio::time::pause();
+		let limiter = RateLimiter::new(Duration::ZERO, 1, 2);
+		limiter.acquire(Duration::ZERO).await.unwrap();
+		limiter.acquire(Duration::ZERO).await.unwrap();
+		assert!(matches!(
+			limiter.acquire(Duration::ZERO).await,
+			Err(Error::Friendly(_))
+		));
+		tokio::time::advance(RATE_LIMIT_DAY - Duration::from_secs(1)).await;
+		assert!(limiter.acquire(Duration::ZERO).await.is_err());
+		tokio::time::advance(Duration::from_secs(1)).await;
+		limiter.acquire(Duration::ZERO).await.unwrap();
+	}
+	#[tokio::test]
+	async fn retries_until_available() {
+		let (url, _) = mock_server(&[UNAVAILABLE, UNAVAILABLE, OK]);
+		let response = send_with_retries(client().get(url), MAX_SEND_TIME)
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.text().await.unwrap(), "ok");
+	}
+	#[tokio::test]
+	async fn long_retry_after_is_not_waited_out() {
+		const SLOW_DOWN: &str = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 60\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+		let (url, _) = mock_server(&[SLOW_DOWN]);
+		let error = send_with_retries(client().get(url), MAX_SEND_TIME)
+			.await
+			.unwrap_err();
+		assert_eq!(error.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+	}
+	#[tokio::test]
+	async fn retries_stop_at_time_limit() {
+		let (url, connections) = mock_server(&[UNAVAILABLE, UNAVAILABLE]);
+		// Too short to wait out the first backoff.
+		let error = send_with_retries(client().get(url), BASE_BACKOFF / 2)
+			.await
+			.unwrap_err();
+		assert_eq!(error.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+		assert_eq!(connections.load(Ordering::Relaxed), 1);
+	}
+	#[test]
+	fn cache_key_rounds_coordinates() {
+		let key = |query: &str| {
+			cache_key(
+				&Url::parse(&format!("https://api.open-meteo.com/v1/forecast?{query}")).unwrap(),
+			)
+		};
+		assert_eq!(
+			key("hourly=uv_index&latitude=52.3731&longitude=4.8922&forecast_hours=24"),
+			key("hourly=uv_index&latitude=52.3689&longitude=4.8949&forecast_hours=24")
+		);
+		assert_ne!(
+			key("hourly=uv_index&latitude=52.3731&longitude=4.8922&forecast_hours=24"),
+			key("hourly=uv_index&latitude=52.3731&longitude=4.8922&forecast_hours=48")
+		);
+	}
+	#[tokio::test]
+	async fn cached_response_reused_within_ttl() {
+		const OFFSET: &str = "HTTP/1.1 200 OK\r\nContent-Length: 27\r\nConnection: close\r\n\r\n{\"utc_offset_seconds\":3600}";
+		let (url, connections) = mock_server(&[OFFSET, OFFSET]);
+		let cache = TtlCache::new(Duration::from_secs(60));
+		let fetch = || async {
+			let result: TimezoneResult =
+				fetch_cached(client().get(&url).query(&[("latitude", 52.3731)]), &cache)
+					.await
+					.unwrap();
+			result.utc_offset_seconds
+		};
+		assert_eq!(fetch().await, 3600);
+		assert_eq!(fetch().await, 3600);
+		assert_eq!(connections.load(Ordering::Relaxed), 1);
+	}
+	#[tokio::test]
+	async fn oversized_body_rejected() {
+		const DECLARED: &str =
+			"HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\n{\"a\":\"long\"}";
+		const UNDECLARED: &str = "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{\"a\":\"long\"}";
+		let (url, _) = mock_server(&[DECLARED, UNDECLARED, OK]);
+		for _ in 0..2 {
+			let response = client().get(&url).send().await.unwrap();
+			assert!(matches!(
+				read_body(response, 8).await,
+				Err(Error::Friendly(_))
+			));
+		}
+		let response = client().get(&url).send().await.unwrap();
+		assert_eq!(read_body(response, 8).await.unwrap(), "ok");
+	}
+	#[tokio::test]
+	async fn error_reason_becomes_friendly() {
+		const INVALID: &str = "HTTP/1.1 400 Bad Request\r\nContent-Length: 80\r\nConnection: close\r\n\r\n{\"error\":true,\"reason\":\"Latitude must be in range of -90 to 90°. Given: 91.0.\"}";
+		let (url, _) = mock_server(&[INVALID]);
+		let cache = TtlCache::new(Duration::from_secs(60));
+		let result: Result<TimezoneResult, _> = fetch_cached(client().get(&url), &cache).await;
+		match result {
+			Err(Error::Friendly(reason)) => {
+				assert_eq!(
+					reason,
+					"Latitude must be in range of -90 to 90°. Given: 91.0."
+				)
+			}
+			other => panic!("expected a friendly error, got {other:?}"),
+		}
+	}
+}
@@ -0,0 +1,274 @@
+use graph::{
+	common_types::{GradientPoint, MultiPointGradient},
+	generic_graph::Rgb,
+	text_box::TextSegment,
+};
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommandOption};
+
+use crate::{error::Error, util::CommandInteractionExt};
+
+/// The colours used by the forecast charts.
+pub struct Palette {
+	/// Dry bulb and maximum temperatures.
+	pub temperature: [u8; 3],
+	/// Wet bulb and minimum temperatures.
+	pub low_temperature: [u8; 3],
+	pub apparent_temperature: [u8; 3],
+	pub dew_point: [u8; 3],
+	/// Apparent temperatures on the daily chart, which are drawn next to both of the other temperatures.
+	pub daily_apparent_temperature: [u8; 3],
+	/// Precipitation in general, and rain in particular where it is split by type.
+	pub precipitation: [u8; 3],
+	pub showers: [u8; 3],
+	pub snow: [u8; 3],
+	pub precipitation_probability: [u8; 3],
+	/// Total cloud cover, drawn behind the layers so it should be darker.
+	pub cloud_cover: [u8; 3],
+	/// Low, mid and high clouds.
+	pub cloud_layers: [[u8; 3]; 3],
+	pub uv_clear_sky: [u8; 3],
+	/// The bands of [`UV_BANDS`], from low to extreme.
+	pub uv_bands: [[u8; 3]; 5],
+	/// The line at [`UV_PROTECTION_THRESHOLD`].
+	pub uv_protection: [u8; 3],
+	/// From calm to storm.
+	pub wind_gradient: [[u8; 3]; 4],
+	/// From calm to storm, drawn behind the wind speed so it should be darker.
+	pub gust_gradient: [[u8; 3]; 4],
+	/// Gusts that stand out, drawn over the gust gradient to draw the eye.
+	pub strong_gust: [u8; 3],
+	/// Comfortable, humid and oppressive air.
+	pub comfort: [[u8; 3]; 3],
+	/// Hours with a thunderstorm.
+	pub thunderstorm: [u8; 3],
+	/// The first hour of each day, marked behind the data so it should be dim.
+	pub day_separator: [u8; 3],
+	pub visibility: [u8; 3],
+}
+
+const DEFAULT: Palette = Palette {
+	temperature: [255, 0, 0],
+	low_temperature: [0, 148, 255],
+	apparent_temperature: [0, 255, 33],
+	dew_point: [178, 102, 255],
+	daily_apparent_temperature: [0, 170, 33],
+	precipitation: [0, 148, 255],
+	showers: [125, 200, 255],
+	snow: [255, 255, 255],
+	precipitation_probability: [0, 180, 255],
+	cloud_cover: [130, 130, 130],
+	cloud_layers: [[255, 255, 255], [255, 216, 0], [118, 215, 234]],
+	uv_clear_sky: [118, 215, 234],
+	uv_bands: [
+		[41, 149, 1],
+		[247, 228, 1],
+		[249, 89, 1],
+		[217, 0, 17],
+		[108, 73, 203],
+	],
+	uv_protection: [255, 255, 255],
+	wind_gradient: [[0, 255, 33], [255, 255, 33], [255, 0, 33], [188, 66, 255]],
+	gust_gradient: [[70, 119, 67], [118, 118, 62], [122, 67, 62], [103, 78, 122]],
+	strong_gust: [255, 40, 40],
+	comfort: [[0, 255, 33], [255, 255, 33], [255, 0, 33]],
+	thunderstorm: [255, 230, 0],
+	day_separator: [60, 60, 60],
+	visibility: [190, 190, 190],
+};
+
+/// Based on the Okabe-Ito palette, which stays distinguishable with deuteranopia and protanopia. Colours that are drawn together also differ in lightness, so they can be told apart even without hue.
+const COLOURBLIND: Palette = Palette {
+	temperature: [230, 159, 0],
+	low_temperature: [86, 180, 233],
+	apparent_temperature: [204, 121, 167],
+	dew_point: [0, 158, 115],
+	daily_apparent_temperature: [204, 121, 167],
+	precipitation: [86, 180, 233],
+	showers: [175, 218, 243],
+	snow: [255, 255, 255],
+	precipitation_probability: [86, 180, 233],
+	cloud_cover: [130, 130, 130],
+	cloud_layers: [[255, 255, 255], [230, 159, 0], [86, 180, 233]],
+	uv_clear_sky: [86, 180, 233],
+	uv_bands: [
+		[0, 158, 115],
+		[240, 228, 66],
+		[230, 159, 0],
+		[213, 94, 0],
+		[204, 121, 167],
+	],
+	uv_protection: [255, 255, 255],
+	wind_gradient: [
+		[86, 180, 233],
+		[240, 228, 66],
+		[213, 94, 0],
+		[204, 121, 167],
+	],
+	gust_gradient: [[43, 90, 116], [120, 114, 33], [106, 47, 0], [102, 60, 83]],
+	strong_gust: [213, 94, 0],
+	comfort: [[86, 180, 233], [240, 228, 66], [213, 94, 0]],
+	thunderstorm: [240, 228, 66],
+	day_separator: [60, 60, 60],
+	visibility: [190, 190, 190],
+};
+
+/// The WHO UV index bands, as the lowest UV index in each and its name.
+pub const UV_BANDS: [(f32, &str); 5] = [
+	(0.0, "low"),
+	(3.0, "moderate"),
+	(6.0, "high"),
+	(8.0, "very high"),
+	(11.0, "extreme"),
+];
+/// The UV index from which the WHO advises sun protection.
+pub const UV_PROTECTION_THRESHOLD: f32 = 3.0;
+
+impl Palette {
+	/// Colours UV index bars by band, with a sharp edge between bands. Takes the height of the bars' base and of one UV index, in pixels.
+	pub fn uv_gradient(&self, base: u32, unit_height: u32) -> MultiPointGradient {
+		let height = |uv: f32| base + (uv * unit_height as f32) as u32;
+		let mut points = vec![GradientPoint::from_rgb(base, self.uv_bands[0])];
+		for (index, &(lowest, _)) in UV_BANDS.iter().enumerate().skip(1) {
+			points.push(GradientPoint::from_rgb(
+				height(lowest) - 1,
+				self.uv_bands[index - 1],
+			));
+			points.push(GradientPoint::from_rgb(
+				height(lowest),
+				self.uv_bands[index],
+			));
+		}
+		MultiPointGradient::new(points)
+	}
+	/// The colour of the band this UV index is in.
+	pub fn uv_colour(&self, uv: f32) -> [u8; 3] {
+		let index = UV_BANDS
+			.iter()
+			.rposition(|&(lowest, _)| uv >= lowest)
+			.unwrap_or(0);
+		self.uv_bands[index]
+	}
+	/// The names of the UV index bands in their colours, for chart headers.
+	pub fn uv_legend(&self) -> Vec<TextSegment<'static>> {
+		let mut segments = Vec::new();
+		for (index, &(_, name)) in UV_BANDS.iter().enumerate() {
+			if index > 0 {
+				segments.push(TextSegment::white(", "));
+			}
+			segments.push(TextSegment::new(name, Rgb(self.uv_bands[index])));
+		}
+		segments
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+	Default,
+	Colourblind,
+}
+
+impl Theme {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"default" => Some(Self::Default),
+			"colourblind" => Some(Self::Colourblind),
+			_ => None,
+		}
+	}
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Default => "default",
+			Self::Colourblind => "colourblind",
+		}
+	}
+	pub fn palette(self) -> &'static Palette {
+		match self {
+			Self::Default => &DEFAULT,
+			Self::Colourblind => &COLOURBLIND,
+		}
+	}
+	/// The theme chosen in the command's "theme" option, or the default if there is none.
+	pub fn from_interaction(interaction: &CommandInteraction) -> Result<Self, Error> {
+		match interaction.string_option("theme") {
+			Some(name) => {
+				Self::from_name(name).ok_or_else(|| Error::custom_unfriendly("Unknown theme"))
+			}
+			None => Ok(Self::Default),
+		}
+	}
+}
+
+pub fn create_theme_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::String,
+		"theme",
+		"The colours to draw the chart with.",
+	)
+	.add_string_choice("default", "default")
+	.add_string_choice("colour-blind friendly", "colourblind")
+	.required(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Approximate what someone with deuteranopia sees, with the simulation matrix from Viénot, Brettel and Mollon (1999) applied to linear RGB.
+	fn simulate_deuteranopia([r, g, b]: [u8; 3]) -> [f32; 3] {
+		let linear = |channel: u8| (channel as f32 / 255.0).powf(2.2);
+		let (r, g, b) = (linear(r), linear(g), linear(b));
+		[
+			0.29275 * r + 0.70725 * g,
+			0.29275 * r + 0.70725 * g,
+			-0.02234 * r + 0.02234 * g + b,
+		]
+	}
+
+	fn simulated_distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+		let (a, b) = (simulate_deuteranopia(a), simulate_deuteranopia(b));
+		a.iter()
+			.zip(b)
+			.map(|(a, b)| (a - b).powi(2))
+			.sum::<f32>()
+			.sqrt()
+	}
+
+	#[test]
+	fn colourblind_temperatures_are_distinguishable() {
+		let palette = Theme::Colourblind.palette();
+		let colours = [
+			palette.temperature,
+			palette.low_temperature,
+			palette.apparent_temperature,
+		];
+		for (index, &a) in colours.iter().enumerate() {
+			for &b in &colours[index + 1..] {
+				assert!(simulated_distance(a, b) > 0.2, "{a:?} and {b:?}");
+			}
+		}
+	}
+	#[test]
+	fn daily_temperatures_only_distinguishable_with_colourblind_theme() {
+		// The red maximum and green apparent temperature lines of the daily chart look the same with deuteranopia, which is what this theme is for.
+		let default = Theme::Default.palette();
+		assert!(simulated_distance(default.temperature, default.daily_apparent_temperature) < 0.1);
+		let colourblind = Theme::Colourblind.palette();
+		assert!(
+			simulated_distance(
+				colourblind.temperature,
+				colourblind.daily_apparent_temperature
+			) > 0.2
+		);
+	}
+	#[test]
+	fn uv_colours_follow_bands() {
+		let palette = Theme::Default.palette();
+		assert_eq!(palette.uv_colour(0.0), palette.uv_bands[0]);
+		assert_eq!(palette.uv_colour(2.9), palette.uv_bands[0]);
+		assert_eq!(palette.uv_colour(3.0), palette.uv_bands[1]);
+		assert_eq!(palette.uv_colour(7.5), palette.uv_bands[2]);
+		assert_eq!(palette.uv_colour(10.0), palette.uv_bands[3]);
+		assert_eq!(palette.uv_colour(14.0), palette.uv_bands[4]);
+		assert_eq!(palette.uv_legend().len(), UV_BANDS.len() * 2 - 1);
+	}
+}
@@ -0,0 +1,244 @@
+use std::{sync::Arc, time::Duration};
+
+use ab_glyph::FontRef;
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use serenity::{
+	all::{
+		ChannelId, CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+		CreateCommandOption, CreateInteractionResponseFollowup, EditMessage, HttpError, MessageId,
+	},
+	http::Http,
+};
+use sqlx::{query, Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	layout::{create_layout_option, Layout},
+	location::{create_label_option, Coordinates, Location},
+	open_meteo,
+	palette::{create_theme_option, Theme},
+	render::{ForecastKind, Style, TemperatureLines},
+	reply_shortcuts::ReplyShortcuts,
+	units::UnitSystem,
+	util::CommandInteractionExt,
+};
+
+/// How many live forecasts a single channel can have, so a channel can't be turned into a wall of them.
+const MAX_PINS_PER_CHANNEL: i32 = 3;
+/// How often pinned forecasts are re-rendered.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn handle_pin(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let kind = match interaction.string_option("forecast") {
+		Some(name) => ForecastKind::from_name(name)
+			.ok_or_else(|| Error::custom_unfriendly("Unknown forecast kind"))?,
+		None => ForecastKind::Hourly,
+	};
+	let style = Style::from_interaction(interaction, database).await?;
+	let channel = interaction.channel_id.get() as i64;
+	let pin_count = query!(
+		"SELECT COUNT(*) AS count FROM pins WHERE channel = ?",
+		channel
+	)
+	.fetch_one(database)
+	.await?
+	.count;
+	if pin_count >= MAX_PINS_PER_CHANNEL {
+		return Err(Error::friendly(format!(
+			"This channel already has {MAX_PINS_PER_CHANNEL} pinned forecasts. Delete one to pin another."
+		)));
+	}
+
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	interaction.defer_reply(&context.http, false).await?;
+	let place = location.short_description();
+	let coordinates = location.coordinates();
+	let image = kind
+		.render(coordinates, style, &client, database, font, header_font)
+		.await?;
+
+	let message = interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.content(pin_content(kind, &place))
+				.add_file(CreateAttachment::bytes(image, kind.file_name())),
+		)
+		.await?
+		.id
+		.get() as i64;
+	let forecast = kind.name();
+	let theme = style.theme.name();
+	let layout = style.layout.name();
+	let units = style.units.name();
+	query!(
+		"
+		INSERT INTO pins (message, channel, forecast, theme, layout, units, place, latitude, longitude)
+		VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+		",
+		message,
+		channel,
+		forecast,
+		theme,
+		layout,
+		units,
+		place,
+		coordinates.latitude,
+		coordinates.longitude
+	)
+	.execute(database)
+	.await?;
+	Ok(())
+}
+
+fn pin_content(kind: ForecastKind, place: &str) -> String {
+	format!(
+		"Live {} forecast for {}, last updated <t:{}:R>. Delete this message to stop updating it.",
+		kind.name(),
+		place,
+		Utc::now().timestamp()
+	)
+}
+
+/// Re-render every pinned forecast once per update interval, forever.
+pub async fn update_pins_periodically(
+	http: Arc<Http>,
+	database: Pool<Sqlite>,
+	font: FontRef<'static>,
+	header_font: FontRef<'static>,
+) {
+	let client = open_meteo::client();
+	let mut interval = tokio::time::interval(UPDATE_INTERVAL);
+	loop {
+		interval.tick().await;
+		if let Err(error) = update_pins(&http, &database, &client, &font, &header_font).await {
+			tracing::error!(error = %error.chain(), "Failed to update pins");
+		}
+	}
+}
+
+async fn update_pins(
+	http: &Arc<Http>,
+	database: &Pool<Sqlite>,
+	client: &Client,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let pins = query!(
+		"SELECT message, channel, forecast, theme, layout, units, place, latitude, longitude FROM pins"
+	)
+	.fetch_all(database)
+	.await?;
+	for pin in pins {
+		let Some(kind) = ForecastKind::from_name(&pin.forecast) else {
+			tracing::warn!(
+				pin = pin.message,
+				forecast = pin.forecast,
+				"Unknown forecast kind for pin"
+			);
+			continue;
+		};
+		let Some(theme) = Theme::from_name(&pin.theme) else {
+			tracing::warn!(
+				pin = pin.message,
+				theme = pin.theme,
+				"Unknown theme for pin"
+			);
+			continue;
+		};
+		let Some(layout) = Layout::from_name(&pin.layout) else {
+			tracing::warn!(
+				pin = pin.message,
+				layout = pin.layout,
+				"Unknown layout for pin"
+			);
+			continue;
+		};
+		let Some(units) = UnitSystem::from_name(&pin.units) else {
+			tracing::warn!(
+				pin = pin.message,
+				units = pin.units,
+				"Unknown units for pin"
+			);
+			continue;
+		};
+		let style = Style {
+			theme,
+			layout,
+			units,
+			horizon: None,
+			start: 0,
+			scale: 1,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		let coordinates = Coordinates::new(pin.latitude as f32, pin.longitude as f32);
+		let image = match kind
+			.render(coordinates, style, client, database, font, header_font)
+			.await
+		{
+			Ok(image) => image,
+			Err(error) => {
+				tracing::error!(
+					pin = pin.message,
+					error = %error.chain(),
+					"Failed to render pin"
+				);
+				continue;
+			}
+		};
+		let edit = EditMessage::new()
+			.content(pin_content(kind, &pin.place))
+			.new_attachment(CreateAttachment::bytes(image, kind.file_name()));
+		let result = ChannelId::new(pin.channel as u64)
+			.edit_message(http, MessageId::new(pin.message as u64), edit)
+			.await;
+		match result {
+			Ok(_) => (),
+			Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+				if response.status_code == StatusCode::NOT_FOUND =>
+			{
+				query!("DELETE FROM pins WHERE message = ?", pin.message)
+					.execute(database)
+					.await?;
+			}
+			Err(error) => tracing::error!(pin = pin.message, %error, "Failed to update pin"),
+		}
+	}
+	Ok(())
+}
+
+pub fn create_pin() -> CreateCommand {
+	i18n::command("pin", "Post a forecast that keeps updating itself")
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"place",
+				"The place to get the weather forecast of.",
+			)
+			.required(false)
+			.set_autocomplete(true),
+		)
+		.add_option(create_label_option())
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"forecast",
+				"Which forecast to keep updated (hourly by default).",
+			)
+			.add_string_choice("hourly", "hourly")
+			.add_string_choice("daily", "daily")
+			.required(false),
+		)
+		.add_option(create_theme_option())
+		.add_option(create_layout_option())
+}
@@ -0,0 +1,94 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::location::Coordinates;
+
+/// A place `/random_place` can wander to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomPlace {
+	pub name: &'static str,
+	pub latitude: f32,
+	pub longitude: f32,
+}
+
+impl RandomPlace {
+	pub fn coordinates(&self) -> Coordinates {
+		Coordinates::new(self.latitude, self.longitude)
+	}
+}
+
+const fn place(name: &'static str, latitude: f32, longitude: f32) -> RandomPlace {
+	RandomPlace {
+		name,
+		latitude,
+		longitude,
+	}
+}
+
+/// Populated places spread over every continent and a wide range of climates, so wandering turns up something other than mild weather now and then.
+const PLACES: [RandomPlace; 40] = [
+	place("Reykjavík, Iceland", 64.15, -21.94),
+	place("Tromsø, Norway", 69.65, 18.96),
+	place("Amsterdam, Netherlands", 52.37, 4.9),
+	place("Lisbon, Portugal", 38.72, -9.14),
+	place("Palermo, Italy", 38.12, 13.36),
+	place("Istanbul, Turkey", 41.01, 28.98),
+	place("Moscow, Russia", 55.76, 37.62),
+	place("Yakutsk, Russia", 62.03, 129.73),
+	place("Cairo, Egypt", 30.04, 31.24),
+	place("Timbuktu, Mali", 16.77, -3.01),
+	place("Lagos, Nigeria", 6.52, 3.38),
+	place("Nairobi, Kenya", -1.29, 36.82),
+	place("Addis Ababa, Ethiopia", 9.03, 38.74),
+	place("Cape Town, South Africa", -33.92, 18.42),
+	place("Antananarivo, Madagascar", -18.88, 47.51),
+	place("Dubai, United Arab Emirates", 25.2, 55.27),
+	place("Mumbai, India", 19.08, 72.88),
+	place("Leh, India", 34.15, 77.58),
+	place("Kathmandu, Nepal", 27.72, 85.32),
+	place("Ulaanbaatar, Mongolia", 47.89, 106.91),
+	place("Singapore", 1.35, 103.82),
+	place("Manila, Philippines", 14.6, 120.98),
+	place("Tokyo, Japan", 35.68, 139.69),
+	place("Sapporo, Japan", 43.06, 141.35),
+	place("Alice Springs, Australia", -23.7, 133.88),
+	place("Hobart, Australia", -42.88, 147.33),
+	place("Wellington, New Zealand", -41.29, 174.78),
+	place("Suva, Fiji", -18.14, 178.44),
+	place("Honolulu, United States", 21.31, -157.86),
+	place("Anchorage, United States", 61.22, -149.9),
+	place("Vancouver, Canada", 49.28, -123.12),
+	place("Phoenix, United States", 33.45, -112.07),
+	place("Chicago, United States", 41.88, -87.63),
+	place("Iqaluit, Canada", 63.75, -68.52),
+	place("Havana, Cuba", 23.11, -82.37),
+	place("Mexico City, Mexico", 19.43, -99.13),
+	place("Bogotá, Colombia", 4.71, -74.07),
+	place("Manaus, Brazil", -3.12, -60.02),
+	place("La Paz, Bolivia", -16.5, -68.15),
+	place("Ushuaia, Argentina", -54.8, -68.3),
+];
+
+/// One of the places, picked by the given random number generator.
+pub fn pick(rng: &mut impl Rng) -> RandomPlace {
+	*PLACES.choose(rng).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use super::*;
+
+	#[test]
+	fn picks_follow_seed() {
+		let picks = |seed| {
+			let mut rng = StdRng::seed_from_u64(seed);
+			(0..10).map(|_| pick(&mut rng)).collect::<Vec<_>>()
+		};
+		assert_eq!(picks(1), picks(1));
+		assert_ne!(picks(1), picks(2));
+		assert!(PLACES.iter().all(|place| {
+			(-90.0..=90.0).contains(&place.latitude) && (-180.0..=180.0).contains(&place.longitude)
+		}));
+	}
+}
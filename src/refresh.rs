@@ -0,0 +1,301 @@
+use ab_glyph::FontRef;
+use serenity::all::{
+	ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateAttachment, CreateButton,
+	CreateInteractionResponse, EditInteractionResponse,
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	home_timezone,
+	layout::Layout,
+	location::Coordinates,
+	open_meteo,
+	palette::Theme,
+	render::{ForecastKind, Style, TemperatureLines, MAX_SCALE},
+	units::UnitSystem,
+};
+
+/// Prefix of the custom ID of refresh buttons, to tell them apart from other components.
+const REFRESH_PREFIX: &str = "refresh";
+/// The longest custom ID Discord accepts.
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+/// A row with a button that re-renders the forecast in the message it is attached to, or `None` if its custom ID would be too long for Discord.
+///
+/// The forecast, its style and its coordinates are stored in the button's custom ID, so nothing needs to be remembered in between. Clicking the button is a new interaction with its own token, so it keeps working after the original command's token has expired.
+pub fn refresh_button(
+	kind: ForecastKind,
+	style: Style,
+	coordinates: Coordinates,
+) -> Option<CreateActionRow> {
+	let id = refresh_id(kind, style, coordinates);
+	if id.len() > MAX_CUSTOM_ID_LENGTH {
+		tracing::warn!(id, "Refresh button ID too long, leaving the button out");
+		return None;
+	}
+	Some(CreateActionRow::Buttons(vec![CreateButton::new(id)
+		.style(ButtonStyle::Secondary)
+		.emoji('🔄')
+		.label("Refresh")]))
+}
+
+fn refresh_id(kind: ForecastKind, style: Style, coordinates: Coordinates) -> String {
+	let mut horizon = style
+		.horizon
+		.map(|horizon| horizon.to_string())
+		.unwrap_or_default();
+	if style.start > 0 {
+		horizon += &format!("+{}", style.start);
+	}
+	if style.scale > 1 {
+		horizon += &format!("x{}", style.scale);
+	}
+	if style.temperature_lines != TemperatureLines::ALL {
+		horizon.push('-');
+		if !style.temperature_lines.wet_bulb {
+			horizon.push('w');
+		}
+		if !style.temperature_lines.apparent {
+			horizon.push('a');
+		}
+	}
+	format!(
+		"{REFRESH_PREFIX}:{}:{}:{}:{}:{}:{}:{}",
+		kind.name(),
+		style.theme.name(),
+		style.layout.name(),
+		style.units.name(),
+		horizon,
+		id_coordinate(coordinates.latitude),
+		id_coordinate(coordinates.longitude)
+	)
+}
+
+/// A coordinate to at most six decimals, which is well within a metre. Written out in full, a coordinate very close to zero could have dozens of digits and push the ID past [`MAX_CUSTOM_ID_LENGTH`].
+fn id_coordinate(coordinate: f32) -> String {
+	let coordinate = format!("{coordinate:.6}");
+	coordinate
+		.trim_end_matches('0')
+		.trim_end_matches('.')
+		.to_string()
+}
+
+fn parse_refresh_id(custom_id: &str) -> Option<(ForecastKind, Style, Coordinates)> {
+	let mut parts = custom_id.split(':');
+	if parts.next()? != REFRESH_PREFIX {
+		return None;
+	}
+	let kind = ForecastKind::from_name(parts.next()?)?;
+	let theme = Theme::from_name(parts.next()?)?;
+	let layout = Layout::from_name(parts.next()?)?;
+	let units = UnitSystem::from_name(parts.next()?)?;
+	// An empty horizon means the forecast's default length. A start other than now follows it after a plus, a scale other than 1 after an x, and the temperature lines left out after a minus, which buttons from before there were any of these don't have.
+	let horizon = parts.next()?;
+	let (horizon, temperature_lines) = match horizon.split_once('-') {
+		Some((horizon, hidden)) => {
+			if !hidden.chars().all(|line| matches!(line, 'w' | 'a')) {
+				return None;
+			}
+			let lines = TemperatureLines {
+				wet_bulb: !hidden.contains('w'),
+				apparent: !hidden.contains('a'),
+			};
+			(horizon, lines)
+		}
+		None => (horizon, TemperatureLines::ALL),
+	};
+	let (horizon, scale) = match horizon.split_once('x') {
+		Some((horizon, scale)) => (horizon, scale.parse().ok()?),
+		None => (horizon, 1),
+	};
+	if !(1..=MAX_SCALE).contains(&scale) {
+		return None;
+	}
+	let (horizon, start) = match horizon.split_once('+') {
+		Some((horizon, start)) => (horizon, start.parse().ok()?),
+		None => (horizon, 0),
+	};
+	let horizon = match horizon {
+		"" => None,
+		horizon => Some(horizon.parse().ok()?),
+	};
+	let latitude = parts.next()?.parse().ok()?;
+	let longitude = parts.next()?.parse().ok()?;
+	if parts.next().is_some() {
+		return None;
+	}
+	let style = Style {
+		theme,
+		layout,
+		units,
+		horizon,
+		start,
+		scale,
+		temperature_lines,
+		home_timezone: None,
+	};
+	Some((kind, style, Coordinates::new(latitude, longitude)))
+}
+
+/// Whether this component interaction is one of the refresh buttons.
+pub fn is_refresh(interaction: &ComponentInteraction) -> bool {
+	interaction
+		.data
+		.custom_id
+		.starts_with(&format!("{REFRESH_PREFIX}:"))
+}
+
+pub async fn handle_refresh(
+	context: &Context,
+	interaction: &ComponentInteraction,
+	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	// Rendering can take longer than Discord's three seconds, so acknowledge first.
+	interaction
+		.create_response(context, CreateInteractionResponse::Acknowledge)
+		.await?;
+	let (kind, mut style, coordinates) = parse_refresh_id(&interaction.data.custom_id)
+		.ok_or_else(|| Error::custom_unfriendly("Malformed refresh button ID"))?;
+	// A timezone name doesn't fit in the button's ID, so the home timezone is that of whoever refreshes.
+	style.home_timezone = home_timezone::get_for_user(database, interaction.user.id).await?;
+	let client = open_meteo::client();
+	let image = kind
+		.render(coordinates, style, &client, database, font, header_font)
+		.await?;
+	interaction
+		.edit_response(
+			context,
+			EditInteractionResponse::new()
+				.new_attachment(CreateAttachment::bytes(image, kind.file_name())),
+		)
+		.await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn refresh_id_round_trip() {
+		let coordinates = Coordinates::new(-52.370216, -104.895164);
+		let style = Style {
+			theme: Theme::Colourblind,
+			layout: Layout::Compact,
+			units: UnitSystem::Imperial,
+			horizon: Some(384),
+			start: 0,
+			scale: 1,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		let id = refresh_id(ForecastKind::Daily, style, coordinates);
+		assert!(id.len() <= MAX_CUSTOM_ID_LENGTH);
+		let (kind, parsed_style, parsed) = parse_refresh_id(&id).unwrap();
+		assert_eq!(kind, ForecastKind::Daily);
+		assert_eq!(parsed_style, style);
+		assert_eq!(parsed.latitude, coordinates.latitude);
+		assert_eq!(parsed.longitude, coordinates.longitude);
+	}
+	#[test]
+	fn refresh_id_with_start() {
+		let style = Style {
+			theme: Theme::Default,
+			layout: Layout::Headers,
+			units: UnitSystem::Metric,
+			horizon: None,
+			start: 24,
+			scale: 1,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		let id = refresh_id(ForecastKind::Hourly, style, Coordinates::new(1.0, 2.0));
+		assert_eq!(id, "refresh:hourly:default:headers:metric:+24:1:2");
+		assert_eq!(parse_refresh_id(&id).unwrap().1, style);
+		let (_, older, _) =
+			parse_refresh_id("refresh:hourly:default:headers:metric:48:1:2").unwrap();
+		assert_eq!(older.horizon, Some(48));
+		assert_eq!(older.start, 0);
+		assert_eq!(older.scale, 1);
+	}
+	#[test]
+	fn refresh_id_with_scale() {
+		let style = Style {
+			theme: Theme::Default,
+			layout: Layout::Headers,
+			units: UnitSystem::Metric,
+			horizon: Some(48),
+			start: 24,
+			scale: 2,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		let id = refresh_id(ForecastKind::Hourly, style, Coordinates::new(1.0, 2.0));
+		assert_eq!(id, "refresh:hourly:default:headers:metric:48+24x2:1:2");
+		assert_eq!(parse_refresh_id(&id).unwrap().1, style);
+		assert!(parse_refresh_id("refresh:hourly:default:headers:metric:x9:1:2").is_none());
+	}
+	#[test]
+	fn refresh_id_with_temperature_lines() {
+		let style = Style {
+			theme: Theme::Default,
+			layout: Layout::Headers,
+			units: UnitSystem::Metric,
+			horizon: None,
+			start: 0,
+			scale: 2,
+			temperature_lines: TemperatureLines {
+				wet_bulb: false,
+				apparent: false,
+			},
+			home_timezone: None,
+		};
+		let id = refresh_id(ForecastKind::Hourly, style, Coordinates::new(1.0, 2.0));
+		assert_eq!(id, "refresh:hourly:default:headers:metric:x2-wa:1:2");
+		assert_eq!(parse_refresh_id(&id).unwrap().1, style);
+		let (_, only_wet_bulb, _) =
+			parse_refresh_id("refresh:hourly:default:headers:metric:48-a:1:2").unwrap();
+		assert_eq!(
+			only_wet_bulb.temperature_lines,
+			TemperatureLines {
+				wet_bulb: true,
+				apparent: false,
+			}
+		);
+		assert!(parse_refresh_id("refresh:hourly:default:headers:metric:-d:1:2").is_none());
+	}
+	#[test]
+	fn refresh_id_length_bounded() {
+		let style = Style {
+			theme: Theme::Colourblind,
+			layout: Layout::Compact,
+			units: UnitSystem::Imperial,
+			horizon: Some(384),
+			start: 384,
+			scale: MAX_SCALE,
+			temperature_lines: TemperatureLines {
+				wet_bulb: false,
+				apparent: false,
+			},
+			home_timezone: None,
+		};
+		// Without rounding, these would be written out with dozens of zeros.
+		let coordinates = Coordinates::new(-1e-30, -179.99999);
+		let id = refresh_id(ForecastKind::Hourly, style, coordinates);
+		assert!(id.len() <= MAX_CUSTOM_ID_LENGTH, "{id}");
+		let (_, _, parsed) = parse_refresh_id(&id).unwrap();
+		assert_eq!(parsed.latitude, 0.0);
+		assert_eq!(parsed.longitude, -179.99999);
+	}
+	#[test]
+	fn refresh_id_rejects_other_ids() {
+		assert!(parse_refresh_id("something:hourly:default:headers:metric::1:2").is_none());
+		assert!(parse_refresh_id("refresh:weekly:default:headers:metric::1:2").is_none());
+		assert!(parse_refresh_id("refresh:hourly:default:headers:metric::1").is_none());
+		assert!(parse_refresh_id("refresh:hourly:default:headers:metric:1:2").is_none());
+		assert!(parse_refresh_id("refresh:hourly:default:headers:metric:x:1:2").is_none());
+	}
+}
@@ -0,0 +1,341 @@
+use ab_glyph::{FontRef, PxScale};
+use chrono_tz::Tz;
+use graph::drawing::{Padding, Spacing};
+use reqwest::Client;
+use serenity::all::{CommandInteraction, CommandOptionType, CreateCommandOption};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	forecasts::{
+		daily::{draw_archived_daily, render_daily},
+		hourly::{draw_archived_hourly, render_hourly},
+	},
+	home_timezone,
+	layout::Layout,
+	location::Coordinates,
+	palette::Theme,
+	units::UnitSystem,
+	util::{CommandInteractionExt, ForecastTimezone},
+};
+
+/// How a forecast is presented, apart from the place it is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+	pub theme: Theme,
+	pub layout: Layout,
+	pub units: UnitSystem,
+	/// How many hours or days to forecast, depending on the forecast, or its default length if `None`.
+	pub horizon: Option<u32>,
+	/// How many hours into the forecast to start, for the hourly forecast.
+	pub start: u32,
+	/// How many times the normal size to draw the charts at, for sharper images.
+	pub scale: u32,
+	/// Which lines the hourly temperature chart draws besides the dry bulb temperature.
+	pub temperature_lines: TemperatureLines,
+	/// The timezone the user chose to see hourly times in, instead of the location's.
+	pub home_timezone: Option<Tz>,
+}
+
+impl Style {
+	/// The style chosen in the command's "theme" and "layout" options, with defaults for whichever is left out, in the units the user chose.
+	pub async fn from_interaction(
+		interaction: &CommandInteraction,
+		database: &Pool<Sqlite>,
+	) -> Result<Self, Error> {
+		Ok(Self {
+			theme: Theme::from_interaction(interaction)?,
+			layout: Layout::from_interaction(interaction)?,
+			units: UnitSystem::get_for_user(database, interaction.user.id).await?,
+			horizon: None,
+			start: 0,
+			scale: scale_option(interaction),
+			temperature_lines: TemperatureLines::from_interaction(interaction),
+			home_timezone: home_timezone::get_for_user(database, interaction.user.id).await?,
+		})
+	}
+	/// The timezone to show times in: the user's home timezone if they chose one, or else the location's own.
+	pub fn timezone(self, local: ForecastTimezone) -> ForecastTimezone {
+		self.home_timezone.map_or(local, ForecastTimezone::Named)
+	}
+	/// The chart spacing at this style's scale.
+	pub fn spacing(self, spacing: Spacing) -> Spacing {
+		Spacing {
+			horizontal: spacing.horizontal * self.scale,
+			vertical: spacing.vertical * self.scale,
+		}
+	}
+	/// The chart padding at this style's scale.
+	pub fn padding(self, padding: Padding) -> Padding {
+		Padding {
+			above: padding.above * self.scale,
+			below: padding.below * self.scale,
+			left: padding.left * self.scale,
+			right: padding.right * self.scale,
+		}
+	}
+	/// The font size at this style's scale.
+	pub fn font_size(self, size: PxScale) -> PxScale {
+		PxScale {
+			x: size.x * self.scale as f32,
+			y: size.y * self.scale as f32,
+		}
+	}
+}
+
+/// The largest scale charts can be drawn at.
+pub const MAX_SCALE: u32 = 3;
+
+/// The scale from the command's "scale" option, or 1 if there is none.
+fn scale_option(interaction: &CommandInteraction) -> u32 {
+	interaction
+		.integer_option("scale")
+		.and_then(|scale| u32::try_from(scale).ok())
+		.filter(|scale| (1..=MAX_SCALE).contains(scale))
+		.unwrap_or(1)
+}
+
+pub fn create_scale_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::Integer,
+		"scale",
+		"How large to draw the image, for sharper pictures to share.",
+	)
+	.add_int_choice("1x", 1)
+	.add_int_choice("2x", 2)
+	.add_int_choice("3x", 3)
+	.required(false)
+}
+
+/// The temperature lines that can be left out of the hourly temperature chart, which can get busy with all three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemperatureLines {
+	pub wet_bulb: bool,
+	pub apparent: bool,
+}
+
+impl TemperatureLines {
+	pub const ALL: Self = Self {
+		wet_bulb: true,
+		apparent: true,
+	};
+	/// The lines chosen in the command's "wet_bulb" and "apparent" options, all of them by default.
+	fn from_interaction(interaction: &CommandInteraction) -> Self {
+		Self {
+			wet_bulb: interaction.bool_option("wet_bulb").unwrap_or(true),
+			apparent: interaction.bool_option("apparent").unwrap_or(true),
+		}
+	}
+}
+
+pub fn create_wet_bulb_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::Boolean,
+		"wet_bulb",
+		"Whether to draw the wet bulb temperature on the temperature chart (yes by default).",
+	)
+	.required(false)
+}
+
+pub fn create_apparent_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::Boolean,
+		"apparent",
+		"Whether to draw the apparent temperature on the temperature chart (yes by default).",
+	)
+	.required(false)
+}
+
+/// The shortest forecast that can be asked for, so the charts still have points to draw lines between.
+pub const MIN_HORIZON: u32 = 2;
+
+/// The forecast length from the command's option of this name, if there is one.
+pub fn horizon_option(
+	interaction: &CommandInteraction,
+	name: &str,
+	max: u32,
+) -> Result<Option<u32>, Error> {
+	interaction
+		.integer_option(name)
+		.map(|horizon| check_horizon(horizon, name, max))
+		.transpose()
+}
+
+/// A friendly error if a forecast came back with too few time steps to chart, as a degenerate response can, rather than drawing a chart without points.
+pub fn check_forecast_data(times: &[i64]) -> Result<(), Error> {
+	if times.len() < MIN_HORIZON as usize {
+		return Err(Error::friendly(
+			"No forecast data available for this location.",
+		));
+	}
+	Ok(())
+}
+
+fn check_horizon(horizon: i64, name: &str, max: u32) -> Result<u32, Error> {
+	u32::try_from(horizon)
+		.ok()
+		.filter(|horizon| (MIN_HORIZON..=max).contains(horizon))
+		.ok_or_else(|| {
+			Error::friendly(format!(
+				"The number of {name} must be between {MIN_HORIZON} and {max}."
+			))
+		})
+}
+
+/// How far into the forecast to start from the command's option of this name, or 0 if there is none. The forecast of `length` from there has to end within `max`.
+pub fn start_option(
+	interaction: &CommandInteraction,
+	name: &str,
+	length: u32,
+	max: u32,
+) -> Result<u32, Error> {
+	interaction
+		.integer_option(name)
+		.map_or(Ok(0), |start| check_start(start, length, max))
+}
+
+fn check_start(start: i64, length: u32, max: u32) -> Result<u32, Error> {
+	u32::try_from(start)
+		.ok()
+		.filter(|start| start.checked_add(length).is_some_and(|end| end <= max))
+		.ok_or_else(|| {
+			Error::friendly(format!(
+				"The forecast only goes {max} hours ahead, so {length} hours starting {start} hours from now don't fit."
+			))
+		})
+}
+
+pub fn create_start_option(description: &str, max: u32) -> CreateCommandOption {
+	CreateCommandOption::new(CommandOptionType::Integer, "start", description)
+		.min_int_value(0)
+		.max_int_value(max as u64)
+		.required(false)
+}
+
+pub fn create_horizon_option(name: &str, description: &str, max: u32) -> CreateCommandOption {
+	CreateCommandOption::new(CommandOptionType::Integer, name, description)
+		.min_int_value(MIN_HORIZON as u64)
+		.max_int_value(max as u64)
+		.required(false)
+}
+
+/// A forecast that can be rendered to an image outside of its own command, for example to update it later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForecastKind {
+	Hourly,
+	Daily,
+}
+
+impl ForecastKind {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"hourly" => Some(Self::Hourly),
+			"daily" => Some(Self::Daily),
+			_ => None,
+		}
+	}
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Hourly => "hourly",
+			Self::Daily => "daily",
+		}
+	}
+	pub fn file_name(self) -> &'static str {
+		match self {
+			Self::Hourly => "hourly.png",
+			Self::Daily => "daily.png",
+		}
+	}
+	/// Fetch this forecast for the coordinates and draw it as a PNG.
+	pub async fn render(
+		self,
+		coordinates: Coordinates,
+		style: Style,
+		client: &Client,
+		database: &Pool<Sqlite>,
+		font: &FontRef<'static>,
+		header_font: &FontRef<'static>,
+	) -> Result<Vec<u8>, Error> {
+		match self {
+			Self::Hourly => {
+				render_hourly(coordinates, style, client, database, font, header_font).await
+			}
+			Self::Daily => {
+				render_daily(coordinates, style, client, database, font, header_font).await
+			}
+		}
+	}
+	/// Draw this forecast from data stored in the render archive.
+	pub fn draw_archived(
+		self,
+		input: &str,
+		style: Style,
+		font: &FontRef<'static>,
+		header_font: &FontRef<'static>,
+	) -> Result<Vec<u8>, Error> {
+		match self {
+			Self::Hourly => draw_archived_hourly(input, style, font, header_font),
+			Self::Daily => draw_archived_daily(input, style, font, header_font),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn horizon_bounds() {
+		assert_eq!(check_horizon(12, "hours", 384).ok(), Some(12));
+		assert_eq!(check_horizon(384, "hours", 384).ok(), Some(384));
+		assert!(check_horizon(385, "hours", 384).is_err());
+		assert!(check_horizon(1, "days", 16).is_err());
+		assert!(check_horizon(-5, "days", 16).is_err());
+	}
+	#[test]
+	fn forecast_data_needs_two_points() {
+		assert!(check_forecast_data(&[]).is_err());
+		assert!(check_forecast_data(&[0]).is_err());
+		assert!(check_forecast_data(&[0, 3600]).is_ok());
+	}
+	#[test]
+	fn start_bounds() {
+		assert_eq!(check_start(24, 48, 384).ok(), Some(24));
+		assert_eq!(check_start(336, 48, 384).ok(), Some(336));
+		assert!(check_start(337, 48, 384).is_err());
+		assert!(check_start(-1, 48, 384).is_err());
+		assert!(check_start(i64::from(u32::MAX), 48, 384).is_err());
+	}
+	#[test]
+	fn scaled_panels_stay_aligned() {
+		let style = |scale| Style {
+			theme: Theme::Default,
+			layout: Layout::Headers,
+			units: UnitSystem::Metric,
+			horizon: None,
+			start: 0,
+			scale,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		// A panel of bars has a column more than a panel of lines, so both have to grow by the same factor to keep lining up.
+		let width = |style: Style, columns: u32| {
+			let padding = style.padding(Padding {
+				above: 3,
+				below: 19,
+				left: 21,
+				right: 3,
+			});
+			let spacing = style.spacing(Spacing {
+				horizontal: 8,
+				vertical: 3,
+			});
+			padding.left + columns * spacing.horizontal + padding.right
+		};
+		for scale in 1..=MAX_SCALE {
+			assert_eq!(width(style(scale), 48), width(style(1), 48) * scale);
+			assert_eq!(width(style(scale), 49), width(style(1), 49) * scale);
+		}
+		assert_eq!(style(3).font_size(PxScale { x: 14.0, y: 14.0 }).y, 42.0);
+	}
+}
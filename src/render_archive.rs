@@ -0,0 +1,125 @@
+use std::sync::LazyLock;
+
+use ab_glyph::FontRef;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::{query, Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	layout::Layout,
+	location::Coordinates,
+	palette::Theme,
+	render::{ForecastKind, Style, TemperatureLines},
+	units::UnitSystem,
+};
+
+/// How many recent renders to keep for debugging, from the `RENDER_ARCHIVE_SIZE` environment variable. Nothing is archived when it is unset or 0.
+static ARCHIVE_SIZE: LazyLock<u32> = LazyLock::new(|| {
+	std::env::var("RENDER_ARCHIVE_SIZE")
+		.ok()
+		.and_then(|size| size.parse().ok())
+		.unwrap_or(0)
+});
+
+/// If the archive is enabled, store the data a forecast image was drawn from, so it can be drawn again exactly when someone reports a chart looking wrong.
+///
+/// Failing to archive is logged rather than returned, since it shouldn't stop the forecast from being sent.
+pub async fn archive_render<T>(
+	database: &Pool<Sqlite>,
+	kind: ForecastKind,
+	style: Style,
+	coordinates: Coordinates,
+	input: &T,
+) where
+	T: Serialize,
+{
+	if *ARCHIVE_SIZE == 0 {
+		return;
+	}
+	if let Err(error) = insert(database, kind, style, coordinates, input).await {
+		tracing::error!(error = %error.chain(), "Failed to archive render");
+	}
+}
+
+async fn insert<T>(
+	database: &Pool<Sqlite>,
+	kind: ForecastKind,
+	style: Style,
+	coordinates: Coordinates,
+	input: &T,
+) -> Result<(), Error>
+where
+	T: Serialize,
+{
+	let forecast = kind.name();
+	let theme = style.theme.name();
+	let layout = style.layout.name();
+	let units = style.units.name();
+	let created = Utc::now().timestamp();
+	let input = serde_json::to_string(input)?;
+	let size = *ARCHIVE_SIZE;
+	query!(
+		"
+		INSERT INTO render_archive (forecast, theme, layout, units, latitude, longitude, created, input)
+		VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+		",
+		forecast,
+		theme,
+		layout,
+		units,
+		coordinates.latitude,
+		coordinates.longitude,
+		created,
+		input
+	)
+	.execute(database)
+	.await?;
+	// Only the most recent renders are kept, like a ring buffer.
+	query!(
+		"
+		DELETE FROM render_archive
+		WHERE id NOT IN (SELECT id FROM render_archive ORDER BY id DESC LIMIT ?)
+		",
+		size
+	)
+	.execute(database)
+	.await?;
+	Ok(())
+}
+
+/// Draw an archived forecast again from its stored data.
+pub async fn rerender(
+	database: &Pool<Sqlite>,
+	id: i64,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(ForecastKind, Vec<u8>), Error> {
+	let archived = query!(
+		"SELECT forecast, theme, layout, units, input FROM render_archive WHERE id = ?",
+		id
+	)
+	.fetch_optional(database)
+	.await?
+	.ok_or_else(|| Error::custom_unfriendly(format!("No archived render with ID {id}")))?;
+	let kind = ForecastKind::from_name(&archived.forecast)
+		.ok_or_else(|| Error::custom_unfriendly("Unknown forecast kind"))?;
+	let theme = Theme::from_name(&archived.theme)
+		.ok_or_else(|| Error::custom_unfriendly("Unknown theme"))?;
+	let layout = Layout::from_name(&archived.layout)
+		.ok_or_else(|| Error::custom_unfriendly("Unknown layout"))?;
+	let units = UnitSystem::from_name(&archived.units)
+		.ok_or_else(|| Error::custom_unfriendly("Unknown units"))?;
+	let style = Style {
+		theme,
+		layout,
+		units,
+		horizon: None,
+		start: 0,
+		scale: 1,
+		temperature_lines: TemperatureLines::ALL,
+		home_timezone: None,
+	};
+	let image = kind.draw_archived(&archived.input, style, font, header_font)?;
+	Ok((kind, image))
+}
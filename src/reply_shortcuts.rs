@@ -1,18 +1,70 @@
-use std::sync::Arc;
+use std::{cell::Cell, future::Future, sync::Arc};
 
 use serenity::{
-	all::CommandInteraction,
+	all::{CommandInteraction, CommandOptionType, CreateCommandOption},
 	async_trait,
 	builder::{
-		CreateAttachment, CreateInteractionResponse, CreateInteractionResponseFollowup,
-		CreateInteractionResponseMessage,
+		CreateAttachment, CreateEmbed, CreateInteractionResponse,
+		CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
 	},
 	http::Http,
 	Result as SerenityResult,
 };
 
+use crate::util::CommandInteractionExt;
+
+/// Whether the command's "private" option asks for the reply to be shown only to the user.
+pub fn private_option(interaction: &CommandInteraction) -> bool {
+	interaction.bool_option("private").unwrap_or(false)
+}
+
+pub fn create_private_option() -> CreateCommandOption {
+	CreateCommandOption::new(
+		CommandOptionType::Boolean,
+		"private",
+		"Whether to show the forecast only to you, to keep busy channels clear.",
+	)
+	.required(false)
+}
+
+/// How far a command has got with responding, which decides how an error can still reach the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseState {
+	/// Nothing was sent yet, so the error can be the response.
+	Pending,
+	/// The response was deferred, and whether it is ephemeral was decided with it, so the error has to replace it.
+	Deferred,
+	/// A message was sent as the response, so the error has to be a follow-up.
+	Sent,
+}
+
+tokio::task_local! {
+	static RESPONSE_STATE: Cell<ResponseState>;
+}
+
+/// Handle a command while keeping track of how far it got with responding, for [`response_state`].
+pub async fn track_response<F: Future>(handler: F) -> F::Output {
+	RESPONSE_STATE
+		.scope(Cell::new(ResponseState::Pending), handler)
+		.await
+}
+
+/// How far the command being handled got with responding. Outside of [`track_response`], this is always [`ResponseState::Pending`].
+pub fn response_state() -> ResponseState {
+	RESPONSE_STATE
+		.try_with(Cell::get)
+		.unwrap_or(ResponseState::Pending)
+}
+
+/// Note that the command being handled has responded, for responses sent without [`ReplyShortcuts`].
+pub fn record_response(state: ResponseState) {
+	let _ = RESPONSE_STATE.try_with(|current| current.set(state));
+}
+
 #[async_trait]
 pub trait ReplyShortcuts {
+	/// Acknowledge the command so it can take longer than 3 seconds. Whether the reply will be ephemeral has to be decided here already.
+	async fn defer_reply(&self, http: &Arc<Http>, ephemeral: bool) -> SerenityResult<()>;
 	async fn reply<S>(&self, http: &Arc<Http>, content: S, ephemeral: bool) -> SerenityResult<()>
 	where
 		S: Into<String> + Send;
@@ -22,6 +74,12 @@ pub trait ReplyShortcuts {
 	async fn public_reply<S>(&self, http: &Arc<Http>, content: S) -> SerenityResult<()>
 	where
 		S: Into<String> + std::marker::Send;
+	async fn reply_embed(
+		&self,
+		http: &Arc<Http>,
+		embed: CreateEmbed,
+		ephemeral: bool,
+	) -> SerenityResult<()>;
 	async fn reply_image(
 		&self,
 		http: &Arc<Http>,
@@ -51,6 +109,15 @@ pub trait ReplyShortcuts {
 
 #[async_trait]
 impl ReplyShortcuts for CommandInteraction {
+	async fn defer_reply(&self, http: &Arc<Http>, ephemeral: bool) -> SerenityResult<()> {
+		if ephemeral {
+			self.defer_ephemeral(http).await?;
+		} else {
+			self.defer(http).await?;
+		}
+		record_response(ResponseState::Deferred);
+		Ok(())
+	}
 	async fn reply<S>(&self, http: &Arc<Http>, content: S, ephemeral: bool) -> SerenityResult<()>
 	where
 		S: Into<String> + Send,
@@ -63,7 +130,9 @@ impl ReplyShortcuts for CommandInteraction {
 					.ephemeral(ephemeral),
 			),
 		)
-		.await
+		.await?;
+		record_response(ResponseState::Sent);
+		Ok(())
 	}
 	async fn ephemeral_reply<S>(&self, http: &Arc<Http>, content: S) -> SerenityResult<()>
 	where
@@ -77,6 +146,24 @@ impl ReplyShortcuts for CommandInteraction {
 	{
 		self.reply(http, content, false).await
 	}
+	async fn reply_embed(
+		&self,
+		http: &Arc<Http>,
+		embed: CreateEmbed,
+		ephemeral: bool,
+	) -> SerenityResult<()> {
+		self.create_response(
+			http,
+			CreateInteractionResponse::Message(
+				CreateInteractionResponseMessage::new()
+					.embed(embed)
+					.ephemeral(ephemeral),
+			),
+		)
+		.await?;
+		record_response(ResponseState::Sent);
+		Ok(())
+	}
 	async fn reply_image(
 		&self,
 		http: &Arc<Http>,
@@ -92,7 +179,9 @@ impl ReplyShortcuts for CommandInteraction {
 					.ephemeral(ephemeral),
 			),
 		)
-		.await
+		.await?;
+		record_response(ResponseState::Sent);
+		Ok(())
 	}
 	async fn public_reply_image(
 		&self,
@@ -124,3 +213,22 @@ impl ReplyShortcuts for CommandInteraction {
 		.await
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn responses_tracked_per_command() {
+		let state = track_response(async {
+			assert_eq!(response_state(), ResponseState::Pending);
+			record_response(ResponseState::Deferred);
+			response_state()
+		})
+		.await;
+		assert_eq!(state, ResponseState::Deferred);
+		// Outside of a command, nothing is tracked.
+		record_response(ResponseState::Sent);
+		assert_eq!(response_state(), ResponseState::Pending);
+	}
+}
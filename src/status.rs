@@ -0,0 +1,34 @@
+use serenity::all::{CommandInteraction, Context, CreateCommand};
+
+use crate::{
+	error::Error,
+	i18n,
+	open_meteo::{BreakerState, OPEN_METEO_BREAKER},
+	reply_shortcuts::ReplyShortcuts,
+};
+
+pub async fn handle_status(
+	context: &Context,
+	interaction: &CommandInteraction,
+) -> Result<(), Error> {
+	let breaker = match OPEN_METEO_BREAKER.state() {
+		BreakerState::Closed {
+			consecutive_failures,
+		} => format!("closed ({consecutive_failures} recent failures)"),
+		BreakerState::Open { seconds_left } => {
+			format!("open (trying again in {seconds_left} seconds)")
+		}
+		BreakerState::HalfOpen => String::from("half-open (testing recovery)"),
+	};
+	interaction
+		.ephemeral_reply(
+			&context.http,
+			format!("Open-Meteo circuit breaker: {breaker}"),
+		)
+		.await?;
+	Ok(())
+}
+
+pub fn create_status() -> CreateCommand {
+	i18n::command("status", "Status of the bot and the services it relies on")
+}
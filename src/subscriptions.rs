@@ -0,0 +1,384 @@
+use std::{sync::Arc, time::Duration};
+
+use ab_glyph::FontRef;
+use chrono::{Datelike, Utc, Weekday};
+use reqwest::Client;
+use serenity::{
+	all::{
+		CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+		CreateCommandOption, CreateMessage, HttpError, UserId,
+	},
+	http::Http,
+};
+use sqlx::{query, Pool, Sqlite};
+
+use crate::{
+	error::Error,
+	i18n,
+	layout::{create_layout_option, Layout},
+	location::{create_label_option, Coordinates, Location},
+	open_meteo,
+	palette::{create_theme_option, Theme},
+	render::{ForecastKind, Style, TemperatureLines},
+	reply_shortcuts::ReplyShortcuts,
+	units::UnitSystem,
+	util::{CommandInteractionExt, ForecastTimezone},
+};
+
+/// How often the scheduler looks for subscriptions that are due. Forecasts go out up to this long after their hour.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Discord's error code for a user who doesn't accept direct messages from the bot.
+const CANNOT_MESSAGE_USER: isize = 50007;
+
+pub async fn handle_subscribe(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let kind = match interaction.string_option("forecast") {
+		Some(name) => ForecastKind::from_name(name)
+			.ok_or_else(|| Error::custom_unfriendly("Unknown forecast kind"))?,
+		None => ForecastKind::Daily,
+	};
+	let hour = interaction
+		.integer_option("hour")
+		.and_then(|hour| u32::try_from(hour).ok())
+		.filter(|hour| *hour < 24)
+		.ok_or_else(|| Error::custom_unfriendly("Missing or invalid hour option"))?;
+	let weekday = match interaction.string_option("day") {
+		Some(day) => Some(
+			day.parse::<Weekday>()
+				.map_err(|_| Error::custom_unfriendly("Unknown day option"))?,
+		),
+		None => None,
+	};
+	let style = Style::from_interaction(interaction, database).await?;
+	let client = open_meteo::client();
+	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let coordinates = location.coordinates();
+	// The hour is in the user's home timezone if they set one, and otherwise in the location's.
+	let timezone = match style.home_timezone {
+		Some(timezone) => ForecastTimezone::Named(timezone),
+		None => open_meteo::timezone(coordinates, &client).await?,
+	};
+	let next_run = next_run(Utc::now().timestamp(), timezone, hour, weekday);
+
+	let user = interaction.user.id.get() as i64;
+	let forecast = kind.name();
+	let theme = style.theme.name();
+	let layout = style.layout.name();
+	let units = style.units.name();
+	let place = location.short_description();
+	let (timezone_name, utc_offset_seconds) = match timezone {
+		ForecastTimezone::Named(timezone) => (Some(timezone.name()), 0),
+		ForecastTimezone::Fixed(offset) => (None, offset.local_minus_utc()),
+	};
+	let weekday_number = weekday.map(|weekday| weekday.num_days_from_monday());
+	query!(
+		"
+		INSERT INTO subscriptions (user, forecast, theme, layout, units, place, latitude, longitude, timezone, utc_offset_seconds, hour, weekday, next_run)
+		VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+		",
+		user,
+		forecast,
+		theme,
+		layout,
+		units,
+		place,
+		coordinates.latitude,
+		coordinates.longitude,
+		timezone_name,
+		utc_offset_seconds,
+		hour,
+		weekday_number,
+		next_run
+	)
+	.execute(database)
+	.await?;
+
+	let days = match weekday {
+		Some(weekday) => format!("every {}", weekday_name(weekday)),
+		None => String::from("every day"),
+	};
+	interaction
+		.ephemeral_reply(
+			&context.http,
+			format!(
+				"You will get the {} forecast for {} in a direct message {} at {:02}:00 ({}), starting <t:{}:R>. This replaces any subscription you had before. Use /unsubscribe to stop.",
+				kind.name(),
+				place,
+				days,
+				hour,
+				timezone,
+				next_run
+			),
+		)
+		.await?;
+	Ok(())
+}
+
+pub async fn handle_unsubscribe(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let user = interaction.user.id.get() as i64;
+	let deleted = query!("DELETE FROM subscriptions WHERE user = ?", user)
+		.execute(database)
+		.await?
+		.rows_affected();
+	let content = if deleted > 0 {
+		"You will no longer get forecasts in direct messages."
+	} else {
+		"You weren't subscribed to any forecast."
+	};
+	interaction
+		.ephemeral_reply(&context.http, String::from(content))
+		.await?;
+	Ok(())
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+	match weekday {
+		Weekday::Mon => "Monday",
+		Weekday::Tue => "Tuesday",
+		Weekday::Wed => "Wednesday",
+		Weekday::Thu => "Thursday",
+		Weekday::Fri => "Friday",
+		Weekday::Sat => "Saturday",
+		Weekday::Sun => "Sunday",
+	}
+}
+
+/// The first time after `after` that it is `hour` o'clock in the timezone, on the given day of the week if there is one. Days where the clocks skip that hour are passed over.
+fn next_run(after: i64, timezone: ForecastTimezone, hour: u32, weekday: Option<Weekday>) -> i64 {
+	let mut date = timezone.local(after).date();
+	loop {
+		if weekday.is_none_or(|weekday| date.weekday() == weekday) {
+			let run = date
+				.and_hms_opt(hour, 0, 0)
+				.and_then(|local| timezone.timestamp(local));
+			if let Some(run) = run.filter(|&run| run > after) {
+				return run;
+			}
+		}
+		date = date.succ_opt().unwrap();
+	}
+}
+
+/// Send every subscription that is due once per check interval, forever. Because the next run is stored, subscriptions that came due while the bot was down go out on the first check after it starts again.
+pub async fn send_subscriptions_periodically(
+	http: Arc<Http>,
+	database: Pool<Sqlite>,
+	font: FontRef<'static>,
+	header_font: FontRef<'static>,
+) {
+	let client = open_meteo::client();
+	let mut interval = tokio::time::interval(CHECK_INTERVAL);
+	loop {
+		interval.tick().await;
+		if let Err(error) = send_subscriptions(&http, &database, &client, &font, &header_font).await
+		{
+			tracing::error!(error = %error.chain(), "Failed to send subscriptions");
+		}
+	}
+}
+
+async fn send_subscriptions(
+	http: &Arc<Http>,
+	database: &Pool<Sqlite>,
+	client: &Client,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	let now = Utc::now().timestamp();
+	let subscriptions = query!(
+		"
+		SELECT user, forecast, theme, layout, units, place, latitude, longitude, timezone, utc_offset_seconds, hour, weekday
+		FROM subscriptions WHERE next_run <= ?
+		",
+		now
+	)
+	.fetch_all(database)
+	.await?;
+	for subscription in subscriptions {
+		let timezone = ForecastTimezone::new(
+			subscription.timezone.as_deref(),
+			subscription.utc_offset_seconds as i32,
+		);
+		let weekday = subscription
+			.weekday
+			.and_then(|weekday| Weekday::try_from(weekday as u8).ok());
+		// Moved on before sending, so a forecast that fails to go out isn't retried every check until it does.
+		let next = next_run(now, timezone, subscription.hour as u32, weekday);
+		query!(
+			"UPDATE subscriptions SET next_run = ? WHERE user = ?",
+			next,
+			subscription.user
+		)
+		.execute(database)
+		.await?;
+
+		let (Some(kind), Some(theme), Some(layout), Some(units)) = (
+			ForecastKind::from_name(&subscription.forecast),
+			Theme::from_name(&subscription.theme),
+			Layout::from_name(&subscription.layout),
+			UnitSystem::from_name(&subscription.units),
+		) else {
+			tracing::warn!(
+				user = subscription.user,
+				"Unknown forecast, theme, layout or units for subscription"
+			);
+			continue;
+		};
+		let style = Style {
+			theme,
+			layout,
+			units,
+			horizon: None,
+			start: 0,
+			scale: 1,
+			temperature_lines: TemperatureLines::ALL,
+			home_timezone: None,
+		};
+		let coordinates =
+			Coordinates::new(subscription.latitude as f32, subscription.longitude as f32);
+		let image = match kind
+			.render(coordinates, style, client, database, font, header_font)
+			.await
+		{
+			Ok(image) => image,
+			Err(error) => {
+				tracing::error!(
+					user = subscription.user,
+					error = %error.chain(),
+					"Failed to render subscription"
+				);
+				continue;
+			}
+		};
+		let message = CreateMessage::new()
+			.content(format!(
+				"Your {} forecast for {}. Use /unsubscribe to stop these.",
+				kind.name(),
+				subscription.place
+			))
+			.add_file(CreateAttachment::bytes(image, kind.file_name()));
+		let result = UserId::new(subscription.user as u64)
+			.direct_message(http.as_ref(), message)
+			.await;
+		match result {
+			Ok(_) => (),
+			Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+				if response.error.code == CANNOT_MESSAGE_USER =>
+			{
+				tracing::info!(
+					user = subscription.user,
+					"Unsubscribed user who doesn't accept direct messages"
+				);
+				query!(
+					"DELETE FROM subscriptions WHERE user = ?",
+					subscription.user
+				)
+				.execute(database)
+				.await?;
+			}
+			Err(error) => {
+				tracing::error!(user = subscription.user, %error, "Failed to send subscription")
+			}
+		}
+	}
+	Ok(())
+}
+
+pub fn create_subscribe() -> CreateCommand {
+	let mut day = CreateCommandOption::new(
+		CommandOptionType::String,
+		"day",
+		"The day of the week to send it on (every day by default).",
+	)
+	.required(false);
+	for weekday in [
+		Weekday::Mon,
+		Weekday::Tue,
+		Weekday::Wed,
+		Weekday::Thu,
+		Weekday::Fri,
+		Weekday::Sat,
+		Weekday::Sun,
+	] {
+		day = day.add_string_choice(weekday_name(weekday), weekday_name(weekday));
+	}
+	i18n::command(
+		"subscribe",
+		"Get a forecast in a direct message every day or every week",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::Integer,
+			"hour",
+			"The hour to send it at, in the location's time or your home timezone.",
+		)
+		.min_int_value(0)
+		.max_int_value(23)
+		.required(true),
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the weather forecast of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(day)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"forecast",
+			"Which forecast to send (daily by default).",
+		)
+		.add_string_choice("daily", "daily")
+		.add_string_choice("hourly", "hourly")
+		.required(false),
+	)
+	.add_option(create_theme_option())
+	.add_option(create_layout_option())
+}
+
+pub fn create_unsubscribe() -> CreateCommand {
+	i18n::command("unsubscribe", "Stop getting forecasts in direct messages")
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::FixedOffset;
+	use chrono_tz::Tz;
+
+	use super::*;
+
+	#[test]
+	fn next_run_times() {
+		let amsterdam = ForecastTimezone::Named(Tz::Europe__Amsterdam);
+		// Monday 2024-03-25 06:30 in Amsterdam, still in winter time.
+		let monday_morning = 1_711_344_600;
+		// 07:00 the same day.
+		assert_eq!(next_run(monday_morning, amsterdam, 7, None), 1_711_346_400);
+		// 06:00 has passed, so the next day.
+		assert_eq!(next_run(monday_morning, amsterdam, 6, None), 1_711_429_200);
+		// The coming Saturday at 09:00.
+		assert_eq!(
+			next_run(monday_morning, amsterdam, 9, Some(Weekday::Sat)),
+			1_711_785_600
+		);
+		// The clocks skip 02:00 on Sunday the 31st, so it's the Sunday after.
+		assert_eq!(
+			next_run(1_711_789_200, amsterdam, 2, Some(Weekday::Sun)),
+			1_712_448_000
+		);
+		let fixed = ForecastTimezone::Fixed(FixedOffset::east_opt(-5 * 3600).unwrap());
+		assert_eq!(next_run(0, fixed, 0, None), 5 * 3600);
+	}
+}
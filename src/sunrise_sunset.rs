@@ -1,101 +1,413 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, f64::consts::PI};
 
-use chrono::{DateTime, Utc};
+use ab_glyph::{FontRef, PxScale};
+use chrono::{DateTime, Timelike, Utc};
+use graph::{
+	common_types::Range,
+	drawing::{MarkIntervals, Padding, Spacing},
+	generic_graph::{AxisGridLabels, Chart, Line, Rgb},
+	text_box::{TextBox, TextSegment},
+	util::make_png,
+};
 use reqwest::Client;
 use serde::Deserialize;
 use serenity::all::{
-	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+	Colour, CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+	CreateCommandOption, CreateEmbed, CreateInteractionResponseFollowup,
 };
 use sqlx::{Pool, Sqlite};
 
 use crate::{
 	error::Error,
-	location::{Coordinates, Location},
+	forecasts::daily::day_from_timestamp,
+	i18n,
+	location::{create_label_option, Coordinates, Location},
+	moon::sun_declination,
+	open_meteo::{self, complete_series, RequestBuilderExt},
+	render::MIN_HORIZON,
 	reply_shortcuts::ReplyShortcuts,
+	util::{CommandInteractionExt, ForecastTimezone},
 };
 
+/// Altitude of the sun's centre at the start of civil dawn and the end of civil dusk, in degrees.
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+/// Altitude of the sun's centre below which its light is golden, roughly, in degrees.
+const GOLDEN_HOUR_ALTITUDE: f64 = 6.0;
+
+/// How many days the sunrise and sunset chart covers.
+const CHART_DAYS: u32 = 14;
+
+const SUNRISE: Rgb = Rgb([255, 190, 60]);
+const SUNSET: Rgb = Rgb([230, 90, 140]);
+
 #[derive(Debug, Deserialize)]
 struct SunriseSunset {
+	time: Vec<i64>,
 	sunrise: Vec<i64>,
 	sunset: Vec<i64>,
+	/// In seconds. Empty if Open-Meteo has no complete series for it.
+	#[serde(default, deserialize_with = "complete_series")]
+	daylight_duration: Vec<f32>,
+}
+
+/// The sunrise, sunset and daylight of a single day, with times in UTC.
+#[derive(Debug, PartialEq)]
+struct SunDay {
+	sunrise: i64,
+	sunset: i64,
+	/// In seconds.
+	daylight_duration: f32,
 }
 
 #[derive(Debug, Deserialize)]
-struct SunResult {
+pub struct SunResult {
 	#[serde(rename = "latitude")]
 	_latitude: f32,
 	#[serde(rename = "longitude")]
 	_longitude: f32,
 	utc_offset_seconds: i32,
+	timezone: String,
 	daily: SunriseSunset,
 }
 
+/// The local sunrise and sunset of a day on the chart, in minutes since midnight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChartDay {
+	time: i64,
+	sunrise: i32,
+	sunset: i32,
+}
+
 impl SunResult {
-	async fn get(coordinates: Coordinates, client: &Client) -> Result<Self, Error> {
-		Ok(client
+	pub async fn get(coordinates: Coordinates, days: u32, client: &Client) -> Result<Self, Error> {
+		client
 			.get("https://api.open-meteo.com/v1/forecast")
 			.query(&[
 				("daily", "sunrise"),
 				("daily", "sunset"),
+				("daily", "daylight_duration"),
 				("timeformat", "unixtime"),
 				("timezone", "auto"),
 			])
-			.query(&[("forecast_days", 2)])
+			.query(&[("forecast_days", days)])
 			.query(&[
 				("latitude", coordinates.latitude),
 				("longitude", coordinates.longitude),
 			])
-			.send()
-			.await?
-			.json::<SunResult>()
-			.await?)
-	}
-	fn next_sunrise_and_sunset(self) -> (i64, i64) {
-		let now = Utc::now().timestamp();
-		let sunrise = self
-			.daily
-			.sunrise
-			.into_iter()
-			.find(|time| *time > now)
-			.unwrap() + self.utc_offset_seconds as i64;
-		let sunset = self
-			.daily
-			.sunset
-			.into_iter()
-			.find(|time| *time > now)
-			.unwrap() + self.utc_offset_seconds as i64;
-		(sunrise, sunset)
+			.fetch_open_meteo()
+			.await
+	}
+	/// The next sunrise and sunset after `now`, in local time. Either is `None` if it doesn't happen in the forecast, as near the poles, or if open-meteo returned fewer days than asked for.
+	fn next_sunrise_and_sunset(&self, now: i64) -> (Option<i64>, Option<i64>) {
+		let next = |times: &[i64]| {
+			times
+				.iter()
+				.find(|time| **time > now)
+				.map(|time| time + self.utc_offset_seconds as i64)
+		};
+		(next(&self.daily.sunrise), next(&self.daily.sunset))
+	}
+	/// The day of the next sunset after `now`, which is today until the sun sets.
+	fn upcoming_day(&self, now: i64) -> Option<SunDay> {
+		let index = self.daily.sunset.iter().position(|time| *time > now)?;
+		self.day(index)
+	}
+	fn day(&self, index: usize) -> Option<SunDay> {
+		Some(SunDay {
+			sunrise: *self.daily.sunrise.get(index)?,
+			sunset: *self.daily.sunset.get(index)?,
+			daylight_duration: *self.daily.daylight_duration.get(index)?,
+		})
+	}
+	/// Every day's sunrise and sunset in local minutes since midnight, or `None` for days the sun doesn't rise or set.
+	fn chart_days(&self) -> Vec<Option<ChartDay>> {
+		let timezone = ForecastTimezone::new(Some(&self.timezone), self.utc_offset_seconds);
+		let minutes = |timestamp: i64| {
+			let time = timezone.local(timestamp);
+			(time.hour() * 60 + time.minute()) as i32
+		};
+		(0..self.daily.time.len())
+			.map(|index| {
+				let day = self.day(index).filter(|day| !day.is_polar())?;
+				Some(ChartDay {
+					time: self.daily.time[index],
+					sunrise: minutes(day.sunrise),
+					sunset: minutes(day.sunset),
+				})
+			})
+			.collect()
+	}
+	/// Today's sunrise and sunset in local time, without emoji so it can be drawn on an image.
+	pub fn describe_today(&self) -> Result<String, Error> {
+		let Some(day) = self.day(0) else {
+			return Ok(String::from("No sunrise or sunset forecast"));
+		};
+		Ok(if day.daylight_duration <= 0.0 {
+			String::from("The sun doesn't rise today")
+		} else if day.daylight_duration >= 86_400.0 {
+			String::from("The sun doesn't set today")
+		} else {
+			format!(
+				"Sunrise {}, sunset {}",
+				local_time(day.sunrise, self.utc_offset_seconds)?,
+				local_time(day.sunset, self.utc_offset_seconds)?
+			)
+		})
+	}
+}
+
+impl SunDay {
+	/// Whether the sun stays up or down the whole day, in which case the sunrise and sunset times don't mean much.
+	fn is_polar(&self) -> bool {
+		self.daylight_duration <= 0.0 || self.daylight_duration >= 86_400.0
+	}
+	/// Civil twilight and golden hour around this day's sunrise and sunset, each with its name.
+	fn describe_twilight(
+		&self,
+		latitude: f32,
+		utc_offset_seconds: i32,
+	) -> Result<[(&'static str, String); 2], Error> {
+		let noon = (self.sunrise + self.sunset) / 2;
+		let declination = sun_declination(timestamp_to_date(noon)?);
+		let time = |timestamp: i64| local_time(timestamp, utc_offset_seconds);
+
+		// The sun does rise, so if it never gets down to civil twilight, it must stay above it.
+		let twilight = match time_from_noon(latitude, declination, CIVIL_TWILIGHT_ALTITUDE) {
+			Some(offset) => format!(
+				"{} to {} and {} to {}",
+				time(noon - offset)?,
+				time(self.sunrise)?,
+				time(self.sunset)?,
+				time(noon + offset)?
+			),
+			None => String::from("All night"),
+		};
+		let golden_hour = match time_from_noon(latitude, declination, GOLDEN_HOUR_ALTITUDE) {
+			Some(offset) => format!(
+				"Until {} and from {}",
+				time(noon - offset)?,
+				time(noon + offset)?
+			),
+			None => String::from("All day"),
+		};
+		Ok([("Civil twilight", twilight), ("Golden hour", golden_hour)])
 	}
 }
 
+/// How long before and after solar noon the sun's centre is at `altitude` degrees, in seconds. `None` if it stays above or below that altitude all day.
+///
+/// Takes the latitude in degrees and the sun's declination in radians, and ignores the change in declination over the day.
+fn time_from_noon(latitude: f32, declination: f64, altitude: f64) -> Option<i64> {
+	let latitude = (latitude as f64).to_radians();
+	let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+		/ (latitude.cos() * declination.cos());
+	if !(-1.0..=1.0).contains(&cos_hour_angle) {
+		return None;
+	}
+	Some((cos_hour_angle.acos() / (2.0 * PI) * 86_400.0).round() as i64)
+}
+
+/// The first stretch of at least two days on which the sun rises and sets, since the chart's lines can't skip the days in between.
+fn first_chartable_run(days: &[Option<ChartDay>]) -> Option<&[Option<ChartDay>]> {
+	days.split(Option::is_none)
+		.find(|run| run.len() >= MIN_HORIZON as usize)
+}
+
 pub async fn handle_sun(
 	context: &Context,
 	interaction: &CommandInteraction,
 	database: &Pool<Sqlite>,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
 ) -> Result<(), Error> {
-	let client = Client::new();
+	let client = open_meteo::client();
 	let location = Location::get_from_argument_or_for_user(interaction, &client, database).await?;
+	let chart = interaction.bool_option("chart").unwrap_or(false);
+	if chart {
+		return handle_sun_chart(context, interaction, &location, &client, font, header_font).await;
+	}
+
+	let result = SunResult::get(location.coordinates(), 2, &client).await?;
+	let now = Utc::now().timestamp();
+	let (sunrise, sunset) = result.next_sunrise_and_sunset(now);
+	let mut embed = CreateEmbed::new()
+		.title(location.short_description())
+		.description(describe_sun(sunrise, sunset)?)
+		.colour(Colour::from_rgb(SUNRISE.0[0], SUNRISE.0[1], SUNRISE.0[2]));
+	if let Some(day) = result.upcoming_day(now) {
+		embed = embed.field(
+			"Day length",
+			format_day_length(day.daylight_duration),
+			false,
+		);
+		if !day.is_polar() {
+			let twilight =
+				day.describe_twilight(location.coordinates().latitude, result.utc_offset_seconds)?;
+			embed = embed.fields(twilight.map(|(name, value)| (name, value, false)));
+		}
+	}
+	interaction.reply_embed(&context.http, embed, false).await?;
+	Ok(())
+}
 
-	let (sunrise, sunset) = SunResult::get(location.coordinates(), &client)
-		.await?
-		.next_sunrise_and_sunset();
+async fn handle_sun_chart(
+	context: &Context,
+	interaction: &CommandInteraction,
+	location: &Location,
+	client: &Client,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Result<(), Error> {
+	interaction.defer_reply(&context.http, false).await?;
+
+	let result = SunResult::get(location.coordinates(), CHART_DAYS, client).await?;
+	let timezone = ForecastTimezone::new(Some(&result.timezone), result.utc_offset_seconds);
+	let days = result.chart_days();
+	let Some(run) = first_chartable_run(&days) else {
+		return Err(Error::friendly(
+			"The sun doesn't rise and set on enough of the next two weeks to chart",
+		));
+	};
+	let mut content = format!(
+		"Sunrise and sunset in {} for the next {CHART_DAYS} days.",
+		location.short_description()
+	);
+	if run.len() < days.len() {
+		content += " The chart leaves out days when the sun doesn't rise or set.";
+	}
+	let run: Vec<ChartDay> = run.iter().flatten().copied().collect();
+	let image = draw_sun_chart(&run, timezone, font, header_font);
+
+	interaction
+		.create_followup(
+			context,
+			CreateInteractionResponseFollowup::new()
+				.content(content)
+				.add_file(CreateAttachment::bytes(image, "sun.png")),
+		)
+		.await?;
+	Ok(())
+}
+
+const LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+const AXIS_LABEL_SIZE: PxScale = PxScale { x: 14.0, y: 14.0 };
+
+/// Draw the sunrise and sunset as lines over the whole day, so the days visibly lengthen or shorten.
+fn draw_sun_chart(
+	days: &[ChartDay],
+	timezone: ForecastTimezone,
+	font: &FontRef<'static>,
+	header_font: &FontRef<'static>,
+) -> Vec<u8> {
+	let times: Vec<_> = days
+		.iter()
+		.map(|day| day_from_timestamp(day.time, timezone))
+		.collect();
+	// In hundredths of hours, so the axis labels are whole hours.
+	let hours = |minutes: i32| minutes * 100 / 60;
+	let range = Range::new(0, 24 * 100);
+
+	let padding = Padding {
+		above: 3,
+		below: 19,
+		left: 21,
+		right: 9,
+	};
+	let spacing = Spacing {
+		horizontal: 25,
+		vertical: 10,
+	};
+	let label = TextBox::new(
+		&[
+			TextSegment::new("Sunrise", SUNRISE),
+			TextSegment::white(" and "),
+			TextSegment::new("sunset", SUNSET),
+			TextSegment::white(&format!(" ({timezone})")),
+		],
+		header_font.clone(),
+		LABEL_SIZE,
+		(days.len() as u32 - 1) * spacing.horizontal,
+		2,
+	);
+	let mut chart = Chart::new(
+		days.len(),
+		range.len() as u32,
+		spacing,
+		Padding {
+			above: padding.above + label.height(),
+			left: padding.left + spacing.horizontal / 2,
+			right: padding.right + spacing.horizontal / 2,
+			..padding
+		},
+	);
+	chart.draw(label);
+	chart.draw(AxisGridLabels {
+		vertical_intervals: MarkIntervals::new(1, 3),
+		horizontal_intervals: MarkIntervals::new(1, 1),
+		vertical_label_range: range,
+		horizontal_labels: times.iter().copied(),
+		horizontal_labels_centered: false,
+		font: font.clone(),
+		font_scale: AXIS_LABEL_SIZE,
+	});
+	chart.draw(Line {
+		colour: SUNRISE,
+		data: days.iter().map(|day| hours(day.sunrise)),
+		max: range.end(),
+	});
+	chart.draw(Line {
+		colour: SUNSET,
+		data: days.iter().map(|day| hours(day.sunset)),
+		max: range.end(),
+	});
+
+	make_png(chart.into_canvas())
+}
+
+/// The next sunrise and sunset in local time, or which of them doesn't happen in the forecast window.
+fn describe_sun(sunrise: Option<i64>, sunset: Option<i64>) -> Result<String, Error> {
+	Ok(match (sunrise, sunset) {
+		(Some(sunrise), Some(sunset)) => format_sunrise_and_sunset(sunrise, sunset)?,
+		(Some(sunrise), None) => format!(
+			"🌅{}, but the sun doesn't set in the forecast window.",
+			timestamp_to_date(sunrise)?.format("%H:%M")
+		),
+		(None, Some(sunset)) => format!(
+			"🌃{}, but the sun doesn't rise in the forecast window.",
+			timestamp_to_date(sunset)?.format("%H:%M")
+		),
+		(None, None) => String::from("The sun doesn't rise or set in the forecast window."),
+	})
+}
+
+/// The next sunrise and sunset in local time, in the order they happen.
+fn format_sunrise_and_sunset(sunrise: i64, sunset: i64) -> Result<String, Error> {
 	let sunrise_date = timestamp_to_date(sunrise)?;
 	let sunset_date = timestamp_to_date(sunset)?;
-	let message = match sunrise.cmp(&sunset) {
+	Ok(match sunrise.cmp(&sunset) {
 		Ordering::Less => format!(
 			"🌅{} 🌃{}",
 			sunrise_date.format("%H:%M"),
 			sunset_date.format("%H:%M")
 		),
 		Ordering::Greater => format!(
-			" 🌃{} 🌅{}",
+			"🌃{} 🌅{}",
 			sunset_date.format("%H:%M"),
 			sunrise_date.format("%H:%M")
 		),
 		Ordering::Equal => String::from("Eternal day or night?"),
-	};
-	interaction.public_reply(&context.http, message).await?;
-	Ok(())
+	})
+}
+
+fn format_day_length(seconds: f32) -> String {
+	let minutes = (seconds / 60.0).round() as u32;
+	format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+fn local_time(timestamp: i64, utc_offset_seconds: i32) -> Result<String, Error> {
+	Ok(timestamp_to_date(timestamp + utc_offset_seconds as i64)?
+		.format("%H:%M")
+		.to_string())
 }
 
 fn timestamp_to_date(timestamp: i64) -> Result<DateTime<Utc>, Error> {
@@ -104,14 +416,191 @@ fn timestamp_to_date(timestamp: i64) -> Result<DateTime<Utc>, Error> {
 }
 
 pub fn create_sun() -> CreateCommand {
-	CreateCommand::new("sun")
-		.description("Next sunrise and sunset")
-		.add_option(
-			CreateCommandOption::new(
-				CommandOptionType::String,
-				"place",
-				"The place to get the next sunrise and sunset of.",
-			)
-			.required(false),
+	i18n::command(
+		"sun",
+		"Next sunrise and sunset, with day length and twilight",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"place",
+			"The place to get the next sunrise and sunset of.",
+		)
+		.required(false)
+		.set_autocomplete(true),
+	)
+	.add_option(create_label_option())
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::Boolean,
+			"chart",
+			"Whether to chart sunrise and sunset over the next two weeks instead.",
 		)
+		.required(false),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sun_result(sunrise: Vec<i64>, sunset: Vec<i64>) -> SunResult {
+		let daylight_duration = sunrise
+			.iter()
+			.zip(&sunset)
+			.map(|(sunrise, sunset)| (sunset - sunrise) as f32)
+			.collect();
+		SunResult {
+			_latitude: 0.0,
+			_longitude: 0.0,
+			utc_offset_seconds: 3600,
+			timezone: String::from("GMT+1"),
+			daily: SunriseSunset {
+				time: sunrise.iter().map(|time| time - time % 86_400).collect(),
+				sunrise,
+				sunset,
+				daylight_duration,
+			},
+		}
+	}
+
+	#[test]
+	fn next_sunrise_and_sunset() {
+		let result = sun_result(vec![100, 200], vec![150, 250]);
+		assert_eq!(
+			result.next_sunrise_and_sunset(120),
+			(Some(3800), Some(3750))
+		);
+	}
+	#[test]
+	fn polar_night_has_no_sunrise() {
+		// Nothing left in the forecast after now, as in a polar night.
+		let result = sun_result(vec![0, 86_400], vec![0, 86_400]);
+		assert_eq!(result.next_sunrise_and_sunset(100_000), (None, None));
+	}
+	#[test]
+	fn empty_forecast_has_no_sun() {
+		let result = sun_result(Vec::new(), Vec::new());
+		assert_eq!(result.next_sunrise_and_sunset(0), (None, None));
+		assert!(result.upcoming_day(0).is_none());
+		assert_eq!(
+			describe_sun(None, None).unwrap(),
+			"The sun doesn't rise or set in the forecast window."
+		);
+	}
+	#[test]
+	fn one_sided_forecast() {
+		// The sun sets today and then stays down, as at the start of a polar night.
+		let result = sun_result(vec![0], vec![50_000]);
+		let (sunrise, sunset) = result.next_sunrise_and_sunset(10_000);
+		assert_eq!((sunrise, sunset), (None, Some(53_600)));
+		assert_eq!(
+			describe_sun(sunrise, sunset).unwrap(),
+			"🌃14:53, but the sun doesn't rise in the forecast window."
+		);
+		// Fewer sunsets than sunrises.
+		let result = sun_result(vec![100, 200], vec![150]);
+		let (sunrise, sunset) = result.next_sunrise_and_sunset(160);
+		assert_eq!((sunrise, sunset), (Some(3800), None));
+		assert!(result.upcoming_day(160).is_none());
+		assert!(describe_sun(sunrise, sunset)
+			.unwrap()
+			.ends_with("doesn't set in the forecast window."));
+	}
+	#[test]
+	fn upcoming_day_is_today_until_sunset() {
+		let result = sun_result(vec![100, 200], vec![150, 250]);
+		assert_eq!(
+			result.upcoming_day(120),
+			Some(SunDay {
+				sunrise: 100,
+				sunset: 150,
+				daylight_duration: 50.0
+			})
+		);
+		assert_eq!(result.upcoming_day(160).map(|day| day.sunrise), Some(200));
+	}
+	#[test]
+	fn sunrise_and_sunset_in_order() {
+		let sunrise = 6 * 3600 + 12 * 60;
+		let sunset = 21 * 3600 + 40 * 60;
+		assert_eq!(
+			format_sunrise_and_sunset(sunrise, sunset).unwrap(),
+			"🌅06:12 🌃21:40"
+		);
+		assert_eq!(
+			format_sunrise_and_sunset(sunrise + 86_400, sunset).unwrap(),
+			"🌃21:40 🌅06:12"
+		);
+		assert_eq!(
+			format_sunrise_and_sunset(sunset, sunset).unwrap(),
+			"Eternal day or night?"
+		);
+	}
+	#[test]
+	fn day_length() {
+		assert_eq!(format_day_length(55_680.0), "15h 28m");
+		assert_eq!(format_day_length(3_590.0), "1h 00m");
+	}
+	#[test]
+	fn twilight_at_the_equinox() {
+		// At the equator the sun takes about 24 minutes to sink 6°.
+		let offset = time_from_noon(0.0, 0.0, CIVIL_TWILIGHT_ALTITUDE).unwrap();
+		assert_eq!(offset, 6 * 3600 + 24 * 60);
+		// At midsummer in the far north, it never gets that low.
+		let declination = 23.44f64.to_radians();
+		assert_eq!(
+			time_from_noon(65.0, declination, CIVIL_TWILIGHT_ALTITUDE),
+			None
+		);
+		assert!(time_from_noon(65.0, declination, GOLDEN_HOUR_ALTITUDE).is_some());
+	}
+	#[test]
+	fn chart_skips_polar_days() {
+		// 06:00 to 18:00 UTC, then a day the sun stays down, then three more ordinary days.
+		let mut result = sun_result(
+			(0..5).map(|day| day * 86_400 + 21_600).collect(),
+			(0..5).map(|day| day * 86_400 + 64_800).collect(),
+		);
+		result.daily.daylight_duration[1] = 0.0;
+		let days = result.chart_days();
+		assert_eq!(
+			days[0],
+			Some(ChartDay {
+				time: 0,
+				sunrise: 7 * 60,
+				sunset: 19 * 60
+			})
+		);
+		assert_eq!(days[1], None);
+		// A single day can't make a line, so the chart starts after the polar day.
+		let run = first_chartable_run(&days).unwrap();
+		assert_eq!(run.len(), 3);
+		assert_eq!(run[0].map(|day| day.time), Some(2 * 86_400));
+
+		result.daily.daylight_duration[3] = 86_400.0;
+		assert!(first_chartable_run(&result.chart_days()).is_none());
+	}
+	#[test]
+	fn today_described() {
+		// 06:00 and 18:00 UTC, shown in UTC+1.
+		let result = sun_result(vec![21_600, 108_000], vec![64_800, 151_200]);
+		assert_eq!(
+			result.describe_today().unwrap(),
+			"Sunrise 07:00, sunset 19:00"
+		);
+		let mut polar = sun_result(vec![0], vec![0]);
+		polar.daily.daylight_duration = vec![86_400.0];
+		assert_eq!(polar.describe_today().unwrap(), "The sun doesn't set today");
+		polar.daily.daylight_duration = vec![0.0];
+		assert_eq!(
+			polar.describe_today().unwrap(),
+			"The sun doesn't rise today"
+		);
+		let empty = sun_result(Vec::new(), Vec::new());
+		assert_eq!(
+			empty.describe_today().unwrap(),
+			"No sunrise or sunset forecast"
+		);
+	}
 }
@@ -0,0 +1,242 @@
+use serenity::all::UserId;
+use sqlx::{query, Pool, Sqlite};
+
+use crate::error::Error;
+
+/// Which units a user wants forecasts in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+	Metric,
+	Imperial,
+}
+
+impl UnitSystem {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"metric" => Some(Self::Metric),
+			"imperial" => Some(Self::Imperial),
+			_ => None,
+		}
+	}
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Metric => "metric",
+			Self::Imperial => "imperial",
+		}
+	}
+	/// The user's chosen unit system, or metric if they have not chosen one.
+	pub async fn get_for_user(database: &Pool<Sqlite>, user: UserId) -> Result<Self, Error> {
+		let user = user.get() as i64;
+		let units = query!("SELECT units FROM user_settings WHERE user = ?", user)
+			.fetch_optional(database)
+			.await?;
+		Ok(units
+			.and_then(|settings| Self::from_name(&settings.units))
+			.unwrap_or(Self::Metric))
+	}
+	/// Set the user's unit system, keeping their other settings.
+	pub async fn set_for_user(self, database: &Pool<Sqlite>, user: UserId) -> Result<(), Error> {
+		let user = user.get() as i64;
+		let units = self.name();
+		query!(
+			"INSERT INTO user_settings (user, units) VALUES (?, ?) ON CONFLICT (user) DO UPDATE SET units = excluded.units",
+			user,
+			units
+		)
+		.execute(database)
+		.await?;
+		Ok(())
+	}
+	/// Open-Meteo query parameters to get temperatures and precipitation in these units directly.
+	pub fn query(self) -> [(&'static str, &'static str); 2] {
+		match self {
+			Self::Metric => [
+				("temperature_unit", "celsius"),
+				("precipitation_unit", "mm"),
+			],
+			Self::Imperial => [
+				("temperature_unit", "fahrenheit"),
+				("precipitation_unit", "inch"),
+			],
+		}
+	}
+	/// Open-Meteo's wind speed unit for anything but the charts.
+	pub fn wind_speed_unit(self) -> &'static str {
+		match self {
+			Self::Metric => "kmh",
+			Self::Imperial => "mph",
+		}
+	}
+	/// Open-Meteo's wind speed unit for the charts, which use m/s in metric.
+	pub fn chart_wind_speed_unit(self) -> &'static str {
+		match self {
+			Self::Metric => "ms",
+			Self::Imperial => "mph",
+		}
+	}
+	pub fn temperature_symbol(self) -> &'static str {
+		match self {
+			Self::Metric => "°C",
+			Self::Imperial => "°F",
+		}
+	}
+	pub fn wind_speed_symbol(self) -> &'static str {
+		match self {
+			Self::Metric => "km/h",
+			Self::Imperial => "mph",
+		}
+	}
+	pub fn precipitation_symbol(self) -> &'static str {
+		match self {
+			Self::Metric => "mm",
+			Self::Imperial => "in",
+		}
+	}
+	/// Open-Meteo gives snowfall in cm in metric, but in inches like the rest of the precipitation in imperial.
+	pub fn snowfall_symbol(self) -> &'static str {
+		match self {
+			Self::Metric => "cm",
+			Self::Imperial => "in",
+		}
+	}
+	pub fn chart_wind_speed_symbol(self) -> &'static str {
+		match self {
+			Self::Metric => "m/s",
+			Self::Imperial => "mph",
+		}
+	}
+	/// Pixels per unit of wind speed, so wind charts come out about as tall in either system.
+	pub fn chart_wind_speed_spacing(self) -> u32 {
+		match self {
+			Self::Metric => 5,
+			Self::Imperial => 2,
+		}
+	}
+	/// A wind speed in m/s, in the charts' unit for this system.
+	pub fn wind_speed_from_metres_per_second(self, speed: f32) -> f32 {
+		match self {
+			Self::Metric => speed,
+			Self::Imperial => kmh_to_mph(speed * 3.6),
+		}
+	}
+	/// A wind speed in the charts' unit for this system, in km/h.
+	pub fn chart_wind_speed_to_kmh(self, speed: f32) -> f32 {
+		match self {
+			Self::Metric => speed * 3.6,
+			Self::Imperial => speed * 1.609344,
+		}
+	}
+	/// A wind speed in this system's unit for anything but the charts, in km/h.
+	pub fn wind_speed_to_kmh(self, speed: f32) -> f32 {
+		match self {
+			Self::Metric => speed,
+			Self::Imperial => speed * 1.609344,
+		}
+	}
+	/// A temperature in this system's unit, in °C.
+	pub fn temperature_to_celsius(self, temperature: f32) -> f32 {
+		match self {
+			Self::Metric => temperature,
+			Self::Imperial => fahrenheit_to_celsius(temperature),
+		}
+	}
+	/// A temperature in °C, in this system's unit.
+	pub fn temperature_from_celsius(self, temperature: f32) -> f32 {
+		match self {
+			Self::Metric => temperature,
+			Self::Imperial => celsius_to_fahrenheit(temperature),
+		}
+	}
+	/// A precipitation amount in this system's unit, in mm.
+	pub fn precipitation_to_mm(self, amount: f32) -> f32 {
+		match self {
+			Self::Metric => amount,
+			Self::Imperial => amount * 25.4,
+		}
+	}
+	/// Charts only mark whole units, so inches of precipitation are drawn in tenths to keep the grid useful.
+	pub fn chart_precipitation(self, amount: f32) -> f32 {
+		match self {
+			Self::Metric => amount,
+			Self::Imperial => amount * 10.0,
+		}
+	}
+	pub fn chart_precipitation_label(self) -> &'static str {
+		match self {
+			Self::Metric => "mm",
+			Self::Imperial => "tenths of an inch",
+		}
+	}
+	/// A visibility in metres, in kilometres or miles for charts.
+	pub fn visibility_from_metres(self, metres: f32) -> f32 {
+		match self {
+			Self::Metric => metres / 1000.0,
+			Self::Imperial => metres_to_miles(metres),
+		}
+	}
+	pub fn visibility_label(self) -> &'static str {
+		match self {
+			Self::Metric => "kilometres",
+			Self::Imperial => "miles",
+		}
+	}
+}
+
+pub fn celsius_to_fahrenheit(celsius: f32) -> f32 {
+	celsius * 9.0 / 5.0 + 32.0
+}
+
+pub fn fahrenheit_to_celsius(fahrenheit: f32) -> f32 {
+	(fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+pub fn mm_to_inches(mm: f32) -> f32 {
+	mm / 25.4
+}
+
+pub fn cm_to_inches(cm: f32) -> f32 {
+	cm / 2.54
+}
+
+pub fn kmh_to_mph(kmh: f32) -> f32 {
+	kmh / 1.609344
+}
+
+pub fn metres_to_miles(metres: f32) -> f32 {
+	metres / 1_609.344
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn temperature() {
+		assert_eq!(celsius_to_fahrenheit(0.0), 32.0);
+		assert_eq!(celsius_to_fahrenheit(100.0), 212.0);
+		assert_eq!(celsius_to_fahrenheit(-40.0), -40.0);
+		assert_eq!(fahrenheit_to_celsius(212.0), 100.0);
+		assert_eq!(fahrenheit_to_celsius(-40.0), -40.0);
+	}
+	#[test]
+	fn lengths_and_speed() {
+		assert!((mm_to_inches(25.4) - 1.0).abs() < 1e-6);
+		assert!((cm_to_inches(2.54) - 1.0).abs() < 1e-6);
+		assert!((kmh_to_mph(1.609344) - 1.0).abs() < 1e-6);
+		assert!((metres_to_miles(1_609.344) - 1.0).abs() < 1e-6);
+	}
+	#[test]
+	fn unit_system_round_trips() {
+		for units in [UnitSystem::Metric, UnitSystem::Imperial] {
+			assert_eq!(UnitSystem::from_name(units.name()), Some(units));
+			assert!(
+				(units.temperature_to_celsius(units.temperature_from_celsius(21.5)) - 21.5).abs()
+					< 1e-4
+			);
+		}
+		assert!(
+			(UnitSystem::Imperial.wind_speed_from_metres_per_second(10.0) - 22.369).abs() < 1e-3
+		);
+		assert!((UnitSystem::Imperial.precipitation_to_mm(1.0) - 25.4).abs() < 1e-6);
+	}
+}
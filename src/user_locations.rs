@@ -1,11 +1,18 @@
-use reqwest::Client;
 use serenity::all::{
 	CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+	Permissions, UserId,
 };
 use sqlx::{query, Pool, Sqlite};
 
 use crate::{
-	error::Error, geocoding::GeocodingResult, location::Location, reply_shortcuts::ReplyShortcuts,
+	error::Error,
+	geocoding::{self, Picked},
+	home_timezone, i18n,
+	location::{label_option, location_domain, Coordinates, Location, DEFAULT_LABEL, USER_DOMAIN},
+	open_meteo,
+	reply_shortcuts::ReplyShortcuts,
+	units::UnitSystem,
+	util::CommandInteractionExt,
 };
 
 pub async fn handle_set_location(
@@ -14,54 +21,165 @@ pub async fn handle_set_location(
 	database: &Pool<Sqlite>,
 ) -> Result<(), Error> {
 	let location_arg = interaction
-		.data
-		.options
-		.first()
-		.and_then(|arg| arg.value.as_str())
+		.string_option("location")
 		.ok_or_else(|| Error::custom_unfriendly("Missing argument"))?;
-	let client = Client::new();
-	let geocoding = GeocodingResult::get(location_arg, &client).await?;
-	let location = Location::from_geocoding_result(geocoding);
-	location
+	let label = label_option(interaction)?;
+	let primary = primary_option(interaction);
+	let client = open_meteo::client();
+	let Some(Picked { result, reply }) =
+		geocoding::pick(context, interaction, location_arg, &client, database).await?
+	else {
+		return Ok(());
+	};
+	let location = Location::from_geocoding_result(result);
+	// Without a label, this sets the main location, like it did before locations could be labelled.
+	let is_primary = location
 		.set_for_user(
 			database,
 			interaction.user.id,
-			interaction
-				.guild_id
-				.ok_or_else(|| Error::custom_unfriendly("Somehow had no guild ID"))?,
+			interaction.guild_id,
+			label.unwrap_or(DEFAULT_LABEL),
+			primary || label.is_none(),
 		)
 		.await?;
-	interaction
-		.ephemeral_reply(
-			&context.http,
-			format!(
-				"Location set to {} ({}), country: {}, feature code: {}",
-				location.name(),
-				location.coordinates(),
-				location.country(),
-				location.feature_code()
-			),
-		)
-		.await?;
-	Ok(())
+	let content = format!(
+		"Location \"{}\"{} set to {location}, elevation: {}, feature code: {}",
+		label.unwrap_or(DEFAULT_LABEL),
+		if is_primary { " (primary)" } else { "" },
+		location.elevation(),
+		location.feature_code()
+	);
+	reply.send(context, interaction, content, true).await
+}
+
+/// Whether the "primary" option asks to make the saved location the primary one.
+fn primary_option(interaction: &CommandInteraction) -> bool {
+	interaction.bool_option("primary").unwrap_or(false)
 }
 
 pub fn create_set_location() -> CreateCommand {
-	CreateCommand::new("set_location")
-		.description("Set the location to use by default for weather commands.")
+	i18n::command("set_location", "Set the location to use by default for weather commands.")
 		.add_option(
 			CreateCommandOption::new(
 				CommandOptionType::String,
 				"location",
 				"The location to use by default for weather commands",
 			)
-			.required(true),
+			.required(true)
+			.set_autocomplete(true),
+		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"label",
+				"A name to save it under, like \"home\" or \"work\". Leave it out to set your primary location.",
+			)
+			.required(false),
 		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::Boolean,
+				"primary",
+				"Whether weather commands should use this location when not given a label.",
+			)
+			.required(false),
+		)
+}
+
+pub async fn handle_set_guild_location(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let guild = interaction
+		.guild_id
+		.ok_or_else(|| Error::friendly("A default location can only be set in a server"))?;
+	// Discord already hides the command from everyone else, but server admins can override that.
+	let can_manage = interaction
+		.member
+		.as_ref()
+		.and_then(|member| member.permissions)
+		.is_some_and(Permissions::manage_guild);
+	if !can_manage {
+		return Err(Error::friendly(
+			"Only members who can manage the server can set its default location",
+		));
+	}
+	let location_arg = interaction
+		.string_option("location")
+		.ok_or_else(|| Error::custom_unfriendly("Missing argument"))?;
+	let client = open_meteo::client();
+	let Some(Picked { result, reply }) =
+		geocoding::pick(context, interaction, location_arg, &client, database).await?
+	else {
+		return Ok(());
+	};
+	let location = Location::from_geocoding_result(result);
+	location.set_for_guild(database, guild).await?;
+	let content = format!(
+		"Weather commands in this server now use {location} for anyone without a location of their own."
+	);
+	reply.send(context, interaction, content, false).await
+}
+
+pub fn create_set_guild_location() -> CreateCommand {
+	i18n::command(
+		"set_guild_location",
+		"Set the location weather commands in this server use for anyone who hasn't set their own.",
+	)
+	.default_member_permissions(Permissions::MANAGE_GUILD)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"location",
+			"The location to use by default in this server",
+		)
+		.required(true)
+		.set_autocomplete(true),
+	)
+}
+
+pub async fn handle_set_coords(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let coordinates_arg = interaction
+		.string_option("coordinates")
+		.ok_or_else(|| Error::custom_unfriendly("Missing argument"))?;
+	let label = label_option(interaction)?;
+	let primary = primary_option(interaction);
+	let coordinates = Coordinates::parse(coordinates_arg.trim()).ok_or_else(|| {
+		Error::friendly(
+			"Could not read those coordinates. Try decimal degrees like \"52.876, -118.080\", degrees, minutes and seconds like \"52° 52′ 34″ N, 118° 4′ 46″ W\", degrees and decimal minutes like \"52° 52.57′ N, 118° 4.78′ W\", or a plus code like \"9543VWGC+F5\".",
+		)
+	})?;
+	let location = Location::from_coords(coordinates);
+	// Without a label, this sets the main location, like set_location does.
+	let is_primary = location
+		.set_for_user(
+			database,
+			interaction.user.id,
+			interaction.guild_id,
+			label.unwrap_or(DEFAULT_LABEL),
+			primary || label.is_none(),
+		)
+		.await?;
+	interaction
+		.ephemeral_reply(
+			&context.http,
+			format!(
+				"Location \"{}\"{} set to {location}",
+				label.unwrap_or(DEFAULT_LABEL),
+				if is_primary { " (primary)" } else { "" },
+			),
+		)
+		.await?;
+	Ok(())
 }
 
 pub fn create_set_coords() -> CreateCommand {
-	CreateCommand::new("set_coords")
-		.description("Set the coordinates to use by default for weather commands.")
+	i18n::command("set_coords", "Set the coordinates to use by default for weather commands.")
 		.add_option(
 			CreateCommandOption::new(
 				CommandOptionType::String,
@@ -70,6 +188,22 @@ pub fn create_set_coords() -> CreateCommand {
 			)
 			.required(true),
 		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::String,
+				"label",
+				"A name to save them under, like \"home\" or \"work\". Leave it out to set your primary location.",
+			)
+			.required(false),
+		)
+		.add_option(
+			CreateCommandOption::new(
+				CommandOptionType::Boolean,
+				"primary",
+				"Whether weather commands should use these coordinates when not given a label.",
+			)
+			.required(false),
+		)
 }
 
 pub async fn handle_unset_location(
@@ -78,19 +212,48 @@ pub async fn handle_unset_location(
 	database: &Pool<Sqlite>,
 ) -> Result<(), Error> {
 	let user = interaction.user.id.get() as i64;
-	let domain = interaction
-		.guild_id
-		.ok_or_else(|| Error::custom_unfriendly("Somehow had no guild ID"))?
-		.get() as i64;
-	query!(
+	let domain = location_domain(interaction.guild_id);
+	let label = label_option(interaction)?;
+	let mut transaction = database.begin().await?;
+	let deleted = query!(
 		"
 		DELETE FROM user_locations
-		WHERE domain = ? AND user = ?",
+		WHERE domain = ? AND user = ? AND (label = ? OR (? IS NULL AND is_primary))",
+		domain,
+		user,
+		label,
+		label
+	)
+	.execute(&mut *transaction)
+	.await?
+	.rows_affected();
+	if deleted == 0 {
+		return Err(Error::friendly(match label {
+			Some(label) => format!("No location saved as \"{label}\" here"),
+			None => String::from("No primary location saved here"),
+		}));
+	}
+	// Whichever location was saved first takes over as primary, so commands without a label keep working.
+	query!(
+		"
+		UPDATE user_locations SET is_primary = TRUE
+		WHERE rowid = (
+			SELECT rowid FROM user_locations
+			WHERE domain = ? AND user = ?
+			ORDER BY rowid
+			LIMIT 1
+		) AND NOT EXISTS (
+			SELECT 1 FROM user_locations
+			WHERE domain = ? AND user = ? AND is_primary
+		)",
+		domain,
+		user,
 		domain,
 		user
 	)
-	.execute(database)
+	.execute(&mut *transaction)
 	.await?;
+	transaction.commit().await?;
 	interaction
 		.ephemeral_reply(&context.http, "Successfully unset location.")
 		.await?;
@@ -98,6 +261,199 @@ pub async fn handle_unset_location(
 }
 
 pub fn create_unset_location() -> CreateCommand {
-	CreateCommand::new("unset_location")
-		.description("Unset the location to use by default for weather commands.")
+	i18n::command(
+		"unset_location",
+		"Unset the location to use by default for weather commands.",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"label",
+			"Which saved location to unset. Leave it out to unset your primary location.",
+		)
+		.required(false),
+	)
+}
+
+pub async fn handle_list_locations(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let user = interaction.user.id.get() as i64;
+	let domain = location_domain(interaction.guild_id);
+	let user_domain = USER_DOMAIN;
+	let locations = query!(
+		"
+		SELECT label, is_primary, domain = ? AS \"everywhere!: bool\", place_name, latitude, longitude
+		FROM user_locations
+		WHERE (domain = ? OR domain = ?) AND user = ?
+		ORDER BY domain = ?, is_primary DESC, label
+		",
+		user_domain,
+		domain,
+		user_domain,
+		user,
+		user_domain
+	)
+	.fetch_all(database)
+	.await?;
+	if locations.is_empty() {
+		return Err(Error::friendly(
+			"You have no saved locations. Save one with /set_location.",
+		));
+	}
+	let content = locations
+		.into_iter()
+		.map(|location| {
+			let coordinates = Coordinates::new(location.latitude as f32, location.longitude as f32);
+			let place = match location.place_name {
+				Some(name) => format!("{name} ({coordinates})"),
+				None => coordinates.to_string(),
+			};
+			let mut notes = Vec::new();
+			if location.is_primary {
+				notes.push("primary");
+			}
+			if location.everywhere && interaction.guild_id.is_some() {
+				notes.push("outside this server");
+			}
+			let notes = if notes.is_empty() {
+				String::new()
+			} else {
+				format!(" ({})", notes.join(", "))
+			};
+			format!("**{}**{notes}: {place}", location.label)
+		})
+		.collect::<Vec<_>>()
+		.join("\n");
+	interaction.ephemeral_reply(&context.http, content).await?;
+	Ok(())
+}
+
+pub fn create_list_locations() -> CreateCommand {
+	i18n::command("list_locations", "List the locations you have saved.")
+}
+
+/// Delete everything saved about the user: their locations in every server and outside of servers, and their settings.
+pub async fn handle_forget_me(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let (locations, settings) = forget_user(database, interaction.user.id).await?;
+	let content = if locations == 0 && !settings {
+		String::from("There was nothing saved about you.")
+	} else {
+		format!(
+			"Deleted {locations} saved location{}{}.",
+			if locations == 1 { "" } else { "s" },
+			if settings {
+				" and your settings and subscription"
+			} else {
+				""
+			}
+		)
+	};
+	interaction.ephemeral_reply(&context.http, content).await?;
+	Ok(())
+}
+
+/// Delete all of a user's saved locations, across every domain, their settings and their forecast subscription. Returns how many locations were deleted and whether there were settings or a subscription.
+async fn forget_user(database: &Pool<Sqlite>, user: UserId) -> Result<(u64, bool), Error> {
+	let user = user.get() as i64;
+	let mut transaction = database.begin().await?;
+	let locations = query!("DELETE FROM user_locations WHERE user = ?", user)
+		.execute(&mut *transaction)
+		.await?
+		.rows_affected();
+	let settings = query!("DELETE FROM user_settings WHERE user = ?", user)
+		.execute(&mut *transaction)
+		.await?
+		.rows_affected();
+	let subscriptions = query!("DELETE FROM subscriptions WHERE user = ?", user)
+		.execute(&mut *transaction)
+		.await?
+		.rows_affected();
+	transaction.commit().await?;
+	Ok((locations, settings + subscriptions > 0))
+}
+
+pub fn create_forget_me() -> CreateCommand {
+	i18n::command("forget_me", "Delete your saved locations in every server and in direct messages, your settings and subscription.",
+	)
+}
+
+pub async fn handle_set_units(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let units = interaction
+		.string_option("units")
+		.and_then(UnitSystem::from_name)
+		.ok_or_else(|| Error::custom_unfriendly("Missing or unknown units option"))?;
+	units.set_for_user(database, interaction.user.id).await?;
+	interaction
+		.ephemeral_reply(
+			&context.http,
+			format!("Forecasts will now be shown in {} units.", units.name()),
+		)
+		.await?;
+	Ok(())
+}
+
+pub fn create_set_units() -> CreateCommand {
+	i18n::command(
+		"set_units",
+		"Set whether weather commands use metric or imperial units.",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"units",
+			"The units to show forecasts in",
+		)
+		.add_string_choice("metric (°C, mm, km/h and m/s)", "metric")
+		.add_string_choice("imperial (°F, inches and mph)", "imperial")
+		.required(true),
+	)
+}
+
+pub async fn handle_set_home_timezone(
+	context: &Context,
+	interaction: &CommandInteraction,
+	database: &Pool<Sqlite>,
+) -> Result<(), Error> {
+	let timezone = interaction
+		.string_option("timezone")
+		.map(home_timezone::parse)
+		.transpose()?;
+	home_timezone::set_for_user(database, interaction.user.id, timezone).await?;
+	let message = match timezone {
+		Some(timezone) => format!(
+			"Hourly forecast times will now be shown in {}, wherever the forecast is for.",
+			timezone.name()
+		),
+		None => String::from(
+			"Hourly forecast times will now be shown in the local time of the forecast's location.",
+		),
+	};
+	interaction.ephemeral_reply(&context.http, message).await?;
+	Ok(())
+}
+
+pub fn create_set_home_timezone() -> CreateCommand {
+	i18n::command(
+		"set_home_timezone",
+		"Show hourly forecast times in your own timezone instead of the location's.",
+	)
+	.add_option(
+		CreateCommandOption::new(
+			CommandOptionType::String,
+			"timezone",
+			"An IANA timezone name, like Europe/Amsterdam. Leave it out to go back to local times.",
+		)
+		.required(false),
+	)
 }
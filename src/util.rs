@@ -1,6 +1,121 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction};
+
+use crate::units::{celsius_to_fahrenheit, fahrenheit_to_celsius, UnitSystem};
+
 /// Convert a `f32` into a `i32` and multiply it by 100, because the graph drawing library uses them this way often.
+///
+/// This keeps hundredths, the finest the library can draw, which small amounts like precipitation need. Values that are coarser to begin with should use [`convert_tenths`] or [`convert_whole`].
 pub fn convert_num(n: f32) -> i32 {
-	(n * 100.0).round() as i32
+	convert_rounded(n, 1)
+}
+
+/// Like [`convert_num`], but rounded to tenths first, for values like the UV index that are only reported to one decimal.
+pub fn convert_tenths(n: f32) -> i32 {
+	convert_rounded(n, 10)
+}
+
+/// Like [`convert_num`], but rounded to whole units first, for percentages.
+pub fn convert_whole(n: f32) -> i32 {
+	convert_rounded(n, 100)
+}
+
+/// Convert to hundredths, rounded to a multiple of `step` hundredths.
+fn convert_rounded(n: f32, step: i32) -> i32 {
+	(n * (100 / step) as f32).round() as i32 * step
+}
+
+/// Classify an hourly precipitation rate by the usual meteorological convention: light below 2.5 mm/h, heavy above 7.6 mm/h, and moderate in between.
+pub fn precip_intensity(mm_per_hour: f32) -> &'static str {
+	if mm_per_hour < 2.5 {
+		"light"
+	} else if mm_per_hour <= 7.6 {
+		"moderate"
+	} else {
+		"heavy"
+	}
+}
+
+/// The wind chill in °C by the formula the NWS and Environment Canada use, where it is defined: at 10 °C or colder, with wind above 4.8 km/h.
+pub fn wind_chill(temp_c: f32, wind_kmh: f32) -> Option<f32> {
+	if temp_c > 10.0 || wind_kmh <= 4.8 {
+		return None;
+	}
+	let wind = wind_kmh.powf(0.16);
+	Some(13.12 + 0.6215 * temp_c - 11.37 * wind + 0.3965 * temp_c * wind)
+}
+
+/// The heat index in °C by the NWS's Rothfusz regression and its adjustments for very dry and very humid air, where it is defined: from about 27 °C.
+pub fn heat_index(temp_c: f32, humidity: f32) -> Option<f32> {
+	if temp_c < 27.0 {
+		return None;
+	}
+	// The regression's coefficients need more precision than an f32 has.
+	let (t, rh) = (celsius_to_fahrenheit(temp_c) as f64, humidity as f64);
+	let mut index = -42.379 + 2.04901523 * t + 10.14333127 * rh
+		- 0.22475541 * t * rh
+		- 0.00683783 * t * t
+		- 0.05481717 * rh * rh
+		+ 0.00122874 * t * t * rh
+		+ 0.00085282 * t * rh * rh
+		- 0.00000199 * t * t * rh * rh;
+	if rh < 13.0 && (80.0..=112.0).contains(&t) {
+		index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+	} else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+		index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+	}
+	Some(fahrenheit_to_celsius(index as f32))
+}
+
+/// Gusts at least this many times as strong as the sustained wind stand out.
+const GUST_FACTOR: f32 = 1.6;
+/// Gusts weaker than this in km/h don't stand out however much stronger than the wind they are, since they are still only a breeze.
+const MIN_NOTABLE_GUST_KMH: f32 = 30.0;
+/// Gusts from this strong in km/h always stand out.
+const STRONG_GUST_KMH: f32 = 60.0;
+
+/// Whether a gust is strong in itself, or much stronger than the sustained wind. Takes both in km/h.
+pub fn is_notable_gust(wind_kmh: f32, gust_kmh: f32) -> bool {
+	gust_kmh >= STRONG_GUST_KMH
+		|| (gust_kmh >= MIN_NOTABLE_GUST_KMH && gust_kmh >= wind_kmh * GUST_FACTOR)
+}
+
+/// The gusts that stand out as bars for the wind charts, and nothing for the rest. Takes speeds in the charts' unit.
+pub fn notable_gust_bars(units: UnitSystem, winds: &[f32], gusts: &[f32]) -> Vec<i32> {
+	winds
+		.iter()
+		.zip(gusts)
+		.map(|(&wind, &gust)| {
+			let notable = is_notable_gust(
+				units.chart_wind_speed_to_kmh(wind),
+				units.chart_wind_speed_to_kmh(gust),
+			);
+			if notable {
+				convert_num(gust)
+			} else {
+				0
+			}
+		})
+		.collect()
+}
+
+/// Make the first letter uppercase.
+pub fn capitalize(text: &str) -> String {
+	let mut chars = text.chars();
+	chars
+		.next()
+		.map(|first| first.to_uppercase().chain(chars).collect())
+		.unwrap_or_default()
+}
+
+/// The 8-point compass direction nearest to a bearing in degrees, like "NE" for 40°.
+pub fn compass_point(degrees: f32) -> &'static str {
+	const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+	let index = (degrees.rem_euclid(360.0) / 45.0).round() as usize % POINTS.len();
+	POINTS[index]
 }
 
 pub fn weather_code_to_str(weather_code: u8) -> Option<&'static str> {
@@ -34,9 +149,361 @@ pub fn weather_code_to_str(weather_code: u8) -> Option<&'static str> {
 		96 => "thunderstorm with slight hail",
 		99 => "thunderstorm with heavy hail",
 		num => {
-			println!("Unknown weather code: {num}");
+			tracing::warn!(code = num, "Unknown weather code");
 			return None;
 		}
 	};
 	Some(str)
 }
+
+/// Whether a WMO weather code is one of the thunderstorms, with or without hail.
+pub fn is_thunderstorm(weather_code: u8) -> bool {
+	matches!(weather_code, 95 | 96 | 99)
+}
+
+/// An emoji for the same WMO weather codes as `weather_code_to_str`, grouping them by the kind of weather.
+pub fn weather_code_to_emoji(weather_code: u8) -> Option<&'static str> {
+	let emoji = match weather_code {
+		0 | 1 => "☀️",
+		2 => "⛅",
+		3 => "☁️",
+		45 | 48 => "🌫️",
+		51 | 53 | 55 | 56 | 57 | 61 | 63 | 65 | 66 | 67 | 80 | 81 | 82 => "🌧️",
+		71 | 73 | 75 | 77 | 85 | 86 => "❄️",
+		code if is_thunderstorm(code) => "⛈️",
+		_ => return None,
+	};
+	Some(emoji)
+}
+
+/// The timezone to show forecast times in. Open-Meteo gives the UTC offset at the start of the forecast, which is wrong after a daylight saving time change, so the named timezone is used where it is known.
+#[derive(Debug, Clone, Copy)]
+pub enum ForecastTimezone {
+	Named(Tz),
+	Fixed(FixedOffset),
+}
+
+impl ForecastTimezone {
+	/// The named timezone if there is one chrono-tz knows, or else the fixed offset.
+	pub fn new(name: Option<&str>, utc_offset_seconds: i32) -> Self {
+		match name.and_then(|name| name.parse().ok()) {
+			Some(timezone) => Self::Named(timezone),
+			None => Self::Fixed(FixedOffset::east_opt(utc_offset_seconds).unwrap()),
+		}
+	}
+	/// The local date and time at a Unix timestamp.
+	pub fn local(self, timestamp: i64) -> NaiveDateTime {
+		let time = DateTime::from_timestamp(timestamp, 0).unwrap();
+		match self {
+			Self::Named(timezone) => time.with_timezone(&timezone).naive_local(),
+			Self::Fixed(offset) => time.with_timezone(&offset).naive_local(),
+		}
+	}
+	/// The Unix timestamp of a local date and time, or `None` if the clocks skip over it. Where they go over it twice, the first time.
+	pub fn timestamp(self, local: NaiveDateTime) -> Option<i64> {
+		match self {
+			Self::Named(timezone) => timezone
+				.from_local_datetime(&local)
+				.earliest()
+				.map(|time| time.timestamp()),
+			Self::Fixed(offset) => offset
+				.from_local_datetime(&local)
+				.earliest()
+				.map(|time| time.timestamp()),
+		}
+	}
+}
+
+impl Display for ForecastTimezone {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Named(timezone) => f.write_str(timezone.name()),
+			Self::Fixed(offset) => write!(f, "UTC{offset}"),
+		}
+	}
+}
+
+/// Calculates wet bulb temperature in °C given dry bulb temperature in °C and relative humidity * 100 (0-100).
+///
+/// Supposedly this is only accurate for temperatures between -20 °C and 50 °C, and relative humidities between .05 and .99 (5 and 99).
+pub fn wet_bulb_temp(temp: f32, humidity: f32) -> f32 {
+	temp * (0.15197 * (humidity + 8.313659).sqrt()).atan() + (temp + humidity).atan()
+		- (humidity - 1.676331).atan()
+		+ 0.00391838 * humidity.powf(1.5) * (0.023101 * humidity).atan()
+		- 4.686035
+}
+
+/// Calculates dew point in °C given dry bulb temperature in °C and relative humidity * 100 (0-100), with the Magnus formula and the coefficients from Alduchov and Eskridge (1996).
+pub fn dew_point_temp(temp: f32, humidity: f32) -> f32 {
+	const A: f32 = 17.625;
+	const B: f32 = 243.04;
+	// Humidity is clamped because the logarithm of 0 is negative infinity.
+	let gamma = (humidity.max(1.0) / 100.0).ln() + A * temp / (B + temp);
+	B * gamma / (A - gamma)
+}
+
+/// Calculates absolute humidity in grams of water per cubic metre of air given dry bulb temperature in °C and relative humidity * 100 (0-100).
+pub fn absolute_humidity(temp: f32, humidity: f32) -> f32 {
+	let saturation_vapour_pressure = 6.112 * (17.67 * temp / (temp + 243.5)).exp();
+	saturation_vapour_pressure * humidity * 2.1674 / (273.15 + temp)
+}
+
+/// Absolute humidity in g/m³ from which the air feels humid, roughly a dew point of 14 °C.
+pub const HUMID_ABSOLUTE_HUMIDITY: f32 = 12.0;
+/// Absolute humidity in g/m³ from which the air feels oppressive, roughly a dew point of 20 °C.
+pub const OPPRESSIVE_ABSOLUTE_HUMIDITY: f32 = 17.0;
+
+/// How muggy the air feels, going by how much water it holds regardless of temperature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comfort {
+	Comfortable,
+	Humid,
+	Oppressive,
+}
+
+impl Comfort {
+	/// The position of this class in palettes, from comfortable to oppressive.
+	pub fn index(self) -> usize {
+		self as usize
+	}
+	/// Takes dry bulb temperature in °C and relative humidity * 100 (0-100).
+	pub fn classify(temp: f32, humidity: f32) -> Self {
+		match absolute_humidity(temp, humidity) {
+			absolute if absolute >= OPPRESSIVE_ABSOLUTE_HUMIDITY => Self::Oppressive,
+			absolute if absolute >= HUMID_ABSOLUTE_HUMIDITY => Self::Humid,
+			_ => Self::Comfortable,
+		}
+	}
+}
+
+/// Temperatures in °C and the colours they blend between, from cold to hot.
+const TEMPERATURE_COLOURS: [(f32, [u8; 3]); 7] = [
+	(-20.0, [120, 80, 220]),
+	(-5.0, [60, 130, 240]),
+	(5.0, [60, 200, 220]),
+	(15.0, [80, 200, 80]),
+	(25.0, [250, 200, 40]),
+	(35.0, [240, 100, 30]),
+	(45.0, [200, 20, 30]),
+];
+
+/// A colour for a temperature in °C, from violet for bitter cold to red for scorching heat, like the sidebar of an embed.
+pub fn temperature_colour(temp: f32) -> [u8; 3] {
+	let (first, last) = (TEMPERATURE_COLOURS[0], TEMPERATURE_COLOURS[6]);
+	if temp <= first.0 {
+		return first.1;
+	}
+	for pair in TEMPERATURE_COLOURS.windows(2) {
+		let [(low, low_colour), (high, high_colour)] = [pair[0], pair[1]];
+		if temp <= high {
+			let fraction = (temp - low) / (high - low);
+			return std::array::from_fn(|channel| {
+				let (low, high) = (low_colour[channel] as f32, high_colour[channel] as f32);
+				(low + (high - low) * fraction).round() as u8
+			});
+		}
+	}
+	last.1
+}
+
+/// Command options looked up by name, so handlers don't depend on the order options were declared or given in. Each is `None` if the option wasn't given or has a different type.
+pub trait CommandInteractionExt {
+	fn options(&self) -> &[CommandDataOption];
+	fn option_value(&self, name: &str) -> Option<&CommandDataOptionValue> {
+		self.options()
+			.iter()
+			.find(|option| option.name == name)
+			.map(|option| &option.value)
+	}
+	fn string_option(&self, name: &str) -> Option<&str> {
+		self.option_value(name)?.as_str()
+	}
+	fn integer_option(&self, name: &str) -> Option<i64> {
+		self.option_value(name)?.as_i64()
+	}
+	fn bool_option(&self, name: &str) -> Option<bool> {
+		self.option_value(name)?.as_bool()
+	}
+}
+
+impl CommandInteractionExt for CommandInteraction {
+	fn options(&self) -> &[CommandDataOption] {
+		&self.data.options
+	}
+}
+
+impl CommandInteractionExt for [CommandDataOption] {
+	fn options(&self) -> &[CommandDataOption] {
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn precip_intensity_boundaries() {
+		assert_eq!(precip_intensity(0.1), "light");
+		assert_eq!(precip_intensity(2.49), "light");
+		assert_eq!(precip_intensity(2.5), "moderate");
+		assert_eq!(precip_intensity(7.6), "moderate");
+		assert_eq!(precip_intensity(7.61), "heavy");
+	}
+	#[test]
+	fn wind_chill_reference_values() {
+		// From the NWS wind chill chart, in °F and mph.
+		let chill = |temp_f: f32, wind_mph: f32| {
+			wind_chill(fahrenheit_to_celsius(temp_f), wind_mph * 1.609344)
+				.map(celsius_to_fahrenheit)
+				.unwrap()
+		};
+		assert!((chill(30.0, 10.0) - 21.0).abs() < 0.5);
+		assert!((chill(0.0, 20.0) - -22.0).abs() < 0.5);
+		assert!((chill(-10.0, 15.0) - -32.0).abs() < 0.5);
+		assert_eq!(wind_chill(12.0, 30.0), None);
+		assert_eq!(wind_chill(-5.0, 3.0), None);
+	}
+	#[test]
+	fn heat_index_reference_values() {
+		// From the NWS heat index chart, in °F and percent.
+		let index = |temp_f: f32, humidity: f32| {
+			heat_index(fahrenheit_to_celsius(temp_f), humidity)
+				.map(celsius_to_fahrenheit)
+				.unwrap()
+		};
+		assert!((index(90.0, 60.0) - 100.0).abs() < 1.0);
+		assert!((index(96.0, 65.0) - 121.0).abs() < 1.0);
+		assert!((index(86.0, 90.0) - 105.0).abs() < 1.0);
+		assert!((index(104.0, 40.0) - 119.0).abs() < 1.0);
+		assert_eq!(heat_index(25.0, 80.0), None);
+	}
+	#[test]
+	fn notable_gusts() {
+		assert!(is_notable_gust(50.0, 65.0));
+		assert!(is_notable_gust(15.0, 35.0));
+		assert!(!is_notable_gust(25.0, 35.0));
+		// Much stronger than the wind, but still only a breeze.
+		assert!(!is_notable_gust(5.0, 20.0));
+		// 10 m/s is 36 km/h and 20 mph is 32 km/h.
+		assert_eq!(
+			notable_gust_bars(UnitSystem::Metric, &[4.0, 8.0], &[10.0, 10.0]),
+			[1000, 0]
+		);
+		assert_eq!(
+			notable_gust_bars(UnitSystem::Imperial, &[10.0, 10.0], &[20.0, 15.0]),
+			[2000, 0]
+		);
+	}
+	#[test]
+	fn compass_points() {
+		assert_eq!(compass_point(0.0), "N");
+		assert_eq!(compass_point(22.4), "N");
+		assert_eq!(compass_point(22.6), "NE");
+		assert_eq!(compass_point(225.0), "SW");
+		assert_eq!(compass_point(350.0), "N");
+		assert_eq!(compass_point(-90.0), "W");
+	}
+	#[test]
+	fn every_weather_code_has_an_emoji() {
+		for code in 0..=u8::MAX {
+			assert_eq!(
+				weather_code_to_str(code).is_some(),
+				weather_code_to_emoji(code).is_some(),
+				"weather code {code}"
+			);
+		}
+	}
+	#[test]
+	fn timezone_follows_daylight_saving_time() {
+		use chrono::Timelike;
+
+		// Clocks in Berlin went from 02:00 to 03:00 at 01:00 UTC on 2024-03-31.
+		let before = 1_711_846_800 - 1800;
+		let after = 1_711_846_800 + 1800;
+		let named = ForecastTimezone::new(Some("Europe/Berlin"), 3600);
+		assert_eq!(named.local(before).hour(), 1);
+		assert_eq!(named.local(after).hour(), 3);
+		assert_eq!(named.to_string(), "Europe/Berlin");
+		// Only the offset from the start of the forecast is known without the name.
+		let fixed = ForecastTimezone::new(None, 3600);
+		assert_eq!(fixed.local(after).hour(), 2);
+		assert_eq!(fixed.to_string(), "UTC+01:00");
+		assert!(matches!(
+			ForecastTimezone::new(Some("Not/AZone"), 0),
+			ForecastTimezone::Fixed(_)
+		));
+	}
+	#[test]
+	fn options_by_name() {
+		let options: Vec<CommandDataOption> = serde_json::from_str(
+			r#"[
+				{"name": "label", "type": 3, "value": "home"},
+				{"name": "private", "type": 5, "value": true},
+				{"name": "hours", "type": 4, "value": 12},
+				{"name": "place", "type": 3, "value": "Oslo"}
+			]"#,
+		)
+		.unwrap();
+		let options = options.as_slice();
+		// Not whichever string option happens to come first.
+		assert_eq!(options.string_option("place"), Some("Oslo"));
+		assert_eq!(options.string_option("label"), Some("home"));
+		assert_eq!(options.integer_option("hours"), Some(12));
+		assert_eq!(options.bool_option("private"), Some(true));
+		assert_eq!(options.bool_option("data"), None);
+		// The wrong type is as good as missing.
+		assert_eq!(options.string_option("hours"), None);
+		assert_eq!(options.integer_option("place"), None);
+	}
+	#[test]
+	fn dew_points() {
+		assert!((dew_point_temp(20.0, 50.0) - 9.3).abs() < 0.05);
+		assert!((dew_point_temp(30.0, 70.0) - 23.9).abs() < 0.05);
+		assert!((dew_point_temp(0.0, 80.0) - -3.0).abs() < 0.05);
+		assert!((dew_point_temp(25.0, 100.0) - 25.0).abs() < 0.01);
+	}
+	#[test]
+	fn absolute_humidities() {
+		assert!((absolute_humidity(20.0, 50.0) - 8.6).abs() < 0.05);
+		assert!((absolute_humidity(30.0, 70.0) - 21.2).abs() < 0.05);
+		assert!((absolute_humidity(0.0, 100.0) - 4.8).abs() < 0.05);
+	}
+	#[test]
+	fn comfort_thresholds() {
+		assert_eq!(Comfort::classify(20.0, 50.0), Comfort::Comfortable);
+		// Hot but dry desert air.
+		assert_eq!(Comfort::classify(40.0, 15.0), Comfort::Comfortable);
+		assert_eq!(Comfort::classify(25.0, 60.0), Comfort::Humid);
+		assert_eq!(Comfort::classify(30.0, 70.0), Comfort::Oppressive);
+		// Saturated but cold air still holds too little water to feel muggy.
+		assert_eq!(Comfort::classify(5.0, 100.0), Comfort::Comfortable);
+		// Saturated air is at its dew point, which the thresholds are meant to follow.
+		assert_eq!(Comfort::classify(13.5, 100.0), Comfort::Comfortable);
+		assert_eq!(Comfort::classify(14.5, 100.0), Comfort::Humid);
+		assert_eq!(Comfort::classify(19.5, 100.0), Comfort::Humid);
+		assert_eq!(Comfort::classify(20.5, 100.0), Comfort::Oppressive);
+	}
+	#[test]
+	fn temperature_colours() {
+		assert_eq!(temperature_colour(-40.0), [120, 80, 220]);
+		assert_eq!(temperature_colour(15.0), [80, 200, 80]);
+		// Halfway between green and yellow.
+		assert_eq!(temperature_colour(20.0), [165, 200, 60]);
+		assert_eq!(temperature_colour(60.0), [200, 20, 30]);
+		assert_eq!(temperature_colour(f32::NAN), [200, 20, 30]);
+	}
+	#[test]
+	fn conversions_round_trip() {
+		let back = |n: i32| n as f32 / 100.0;
+		assert_eq!(convert_num(0.07), 7);
+		assert_eq!(back(convert_num(0.07)), 0.07);
+		assert_eq!(convert_num(-3.456), -346);
+		assert_eq!(convert_tenths(5.46), 550);
+		assert_eq!(back(convert_tenths(5.46)), 5.5);
+		assert_eq!(convert_tenths(0.04), 0);
+		assert_eq!(convert_whole(62.4), 6200);
+		assert_eq!(back(convert_whole(62.6)), 63.0);
+		assert_eq!(convert_whole(-0.6), -100);
+	}
+}